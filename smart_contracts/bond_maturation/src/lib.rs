@@ -1,12 +1,27 @@
-//! Scheduled time trigger for bond maturation
+//! Scheduled time trigger for bond maturation, the principal repayments of amortizing bonds,
+//! the settlement of puts and the redemption of called bonds
 #![no_std]
 
 extern crate alloc;
 #[cfg(not(test))]
 extern crate panic_halt;
 
-use alloc::{borrow::ToOwned as _, format};
+use alloc::{borrow::ToOwned as _, format, string::ToString as _, vec::Vec};
+use bond_terms::{
+    call::CallNotice,
+    floating_rate::RateFixing,
+    host::next_payment_idx,
+    inflation::{find_index_ratio, IndexRatio},
+    money::{residual_key, Decimal},
+    put::{put_request_key, PutRequest},
+    triggers::{
+        bond_maturation_trigger_id, coupon_payment_trigger_id, interest_payments_trigger_id,
+        principal_repayment_trigger_id, put_settlement_trigger_id,
+    },
+    BondTerms,
+};
 use dlmalloc::GlobalDlmalloc;
+use iroha_trigger::data_model::query::account::model::FindAccountById;
 use iroha_trigger::log::trace;
 use iroha_trigger::{data_model::prelude::*, debug::dbg_panic, log::{error, info}};
 
@@ -15,13 +30,45 @@ static ALLOC: GlobalDlmalloc = GlobalDlmalloc;
 
 const LIMITS: MetadataLimits = MetadataLimits::new(256, 256);
 
+/// Principal payment the trigger was registered for
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Redemption {
+    /// `n`-th principal repayment of an amortizing bond
+    PrincipalRepayment(usize),
+    /// Puts requested in the `n`-th put window of a puttable bond
+    PutSettlement(usize),
+    /// Redemption of a called bond
+    Call,
+    /// Redemption at maturity
+    Maturity,
+}
+
 #[iroha_trigger::main]
 fn main(id: TriggerId, issuer: AccountId, event: Event) {
-    let bond_id: AssetDefinitionId = id
-        .name()
-        .as_ref()
-        .strip_suffix("%%bond_maturation")
-        .dbg_expect("INTERNAL BUG: Trigger name must end with `%%bond_maturation`")
+    let trigger_name: &str = id.name().as_ref();
+    // Triggers of amortizing bonds repay a slice of the principal and triggers of puttable
+    // bonds settle the puts of a window, whose number follows the suffix, and triggers of
+    // called bonds redeem them before maturity
+    let (bond_id, redemption) =
+        if let Some((bond_id, number)) = trigger_name.split_once("%%principal_repayment%%") {
+            let number = number.parse::<usize>().dbg_expect(
+                "INTERNAL BUG: Unable to parse repayment number from trigger name suffix",
+            );
+            (bond_id, Redemption::PrincipalRepayment(number))
+        } else if let Some((bond_id, number)) = trigger_name.split_once("%%put_settlement%%") {
+            let number = number.parse::<usize>().dbg_expect(
+                "INTERNAL BUG: Unable to parse put window number from trigger name suffix",
+            );
+            (bond_id, Redemption::PutSettlement(number))
+        } else if let Some(bond_id) = trigger_name.strip_suffix("%%bond_call") {
+            (bond_id, Redemption::Call)
+        } else {
+            let bond_id = trigger_name
+                .strip_suffix("%%bond_maturation")
+                .dbg_expect("INTERNAL BUG: Trigger name must end with `%%bond_maturation`");
+            (bond_id, Redemption::Maturity)
+        };
+    let bond_id: AssetDefinitionId = bond_id
         .replace("%%", "#")
         .parse()
         .dbg_expect(
@@ -43,21 +90,81 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
         .execute()
         .dbg_expect(&format!("{bond_id}: Bond not found"));
 
-    let bond_currency: AssetDefinitionId = bond
-        .metadata()
-        .get("currency")
-        .dbg_expect("Currency not found")
-        .to_owned()
-        .try_into()
-        .dbg_expect("`currency` not of the `AssetDefinitionId` type");
-
-    let nominal_value: Fixed = bond
-        .metadata()
-        .get("nominal_value")
-        .dbg_expect("Nominal value not found")
-        .to_owned()
-        .try_into()
-        .dbg_expect("`nominal_value` not of the `NumericValue::Fixed` type");
+    let terms = BondTerms::try_from(bond.metadata())
+        .dbg_expect(&format!("{bond_id}: Invalid bond terms"));
+    let bond_currency = terms.currency.clone();
+    let residual_key = residual_key(&bond_id);
+    // Principal of inflation-linked bonds is scaled by the current index ratio
+    let index_ratio = terms
+        .inflation_index
+        .as_ref()
+        .map(|inflation_index| find_index_ratio(&bond_id, &terms, inflation_index));
+
+    if let Redemption::PrincipalRepayment(n) = redemption {
+        for issued_bond in issued_bonds {
+            let holder = issued_bond.id().account_id().clone();
+            if holder == issuer {
+                trace!(&format!("{bond_id}: Holder is the issuer, skipping principal repayment"));
+
+                continue;
+            }
+
+            let quantity: u32 = issued_bond
+                .value()
+                .to_owned()
+                .try_into()
+                .dbg_expect("INTERNAL BUG: bond quantity is not of the `u32` type");
+            repay_principal(&bond_id, &terms, n, index_ratio, &issuer, holder, quantity);
+        }
+
+        info!(&format!("{bond_id}: Principal repayment {n} made"));
+        return;
+    }
+
+    let coupon_rate = || {
+        terms
+            .current_coupon_rate(RateFixing::from_bond_metadata(bond.metadata()).as_ref())
+            .dbg_expect("INTERNAL BUG: Coupon rate not fixed")
+    };
+
+    if let Redemption::PutSettlement(n) = redemption {
+        let coupon_rate = coupon_rate();
+
+        for issued_bond in &issued_bonds {
+            if *issued_bond.id().account_id() == issuer {
+                trace!(&format!("{bond_id}: Holder is the issuer, skipping put settlement"));
+
+                continue;
+            }
+
+            settle_put(&bond_id, &terms, n, coupon_rate, index_ratio, &issuer, issued_bond);
+        }
+
+        info!(&format!("{bond_id}: Puts of window {n} settled"));
+        return;
+    }
+    let is_call = redemption == Redemption::Call;
+
+    // Called bonds are redeemed at the call price plus accrued interest instead of at maturity
+    let call = is_call.then(|| {
+        let notice = CallNotice::from_bond_metadata(bond.metadata())
+            .dbg_expect(&format!("{bond_id}: Bond was not called"));
+
+        (notice, coupon_rate())
+    });
+    // Final coupon is paid at maturity together with the principal
+    let final_coupon = (!is_call && terms.bond_type.pays_coupons()).then(|| {
+        let period = terms
+            .final_coupon_period()
+            .dbg_expect("INTERNAL BUG: Bond has no final coupon period");
+
+        (period, coupon_rate())
+    });
+    let payment_prefix = if is_call {
+        "call_payment"
+    } else {
+        "maturity_payment"
+    };
 
     for issued_bond in issued_bonds {
         let buyer = issued_bond.id().account_id().clone();
@@ -67,8 +174,52 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
             .to_owned()
             .try_into()
             .dbg_expect("INTERNAL BUG: bond quantity is not of the `u32` type");
-        let amount = Fixed::try_from(quantity as f64)
-            .and_then(|qty| qty.checked_mul(nominal_value))
+        // Residual left over from rounding the coupons is settled together with the principal
+        let carried_residual = find_residual(&buyer, &residual_key);
+        let (principal, accrued_interest) = match call {
+            Some((notice, coupon_rate)) => (
+                called_principal(&terms, &notice, quantity)
+                    .and_then(|principal| terms.index_adjusted(principal, index_ratio))
+                    .dbg_expect("Bond total price overflow"),
+                Some(
+                    terms
+                        .accrued_interest(quantity, notice.redemption_date_ms, coupon_rate)
+                        .and_then(|accrued| terms.index_adjusted(accrued, index_ratio))
+                        .dbg_expect("Accrued interest overflow"),
+                ),
+            ),
+            // Inflation-linked bonds repay at least the original principal even after deflation
+            None => (
+                terms
+                    .outstanding_principal(quantity, terms.maturation_date_ms)
+                    .and_then(|principal| {
+                        terms
+                            .index_adjusted(principal, index_ratio)
+                            .map(|adjusted| adjusted.max(principal))
+                    })
+                    .dbg_expect("Bond total price overflow"),
+                None,
+            ),
+        };
+        let coupon = final_coupon.map(|(period, coupon_rate)| {
+            terms
+                .coupon_amount(quantity, period, coupon_rate)
+                .and_then(|coupon| terms.index_adjusted(coupon, index_ratio))
+                .dbg_expect("Coupon payment overflow")
+        });
+        let payment = principal
+            .checked_add(accrued_interest.unwrap_or(Decimal::ZERO))
+            .and_then(|amount| amount.checked_add(coupon.unwrap_or(Decimal::ZERO)))
+            .and_then(|amount| amount.checked_add(carried_residual.unwrap_or(Decimal::ZERO)))
+            .map(|amount| terms.round(amount))
+            .dbg_expect("Bond total price overflow");
+        let amount = payment
+            .amount
+            .to_fixed()
+            .dbg_expect("Bond total price overflow");
+        let residual = payment
+            .residual
+            .to_fixed()
             .dbg_expect("Bond total price overflow");
 
         // FIXME: Should bonds be burnt or transferred back to the issuer?
@@ -94,8 +245,18 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
                 .execute()
                 .dbg_expect("Sending money failed. Country might have gone bankrupt");
 
+            if carried_residual.is_some() {
+                RemoveKeyValueExpr::new(buyer.clone(), residual_key.clone())
+                    .execute()
+                    .dbg_expect("Failed to remove coupon residual from buyer's metadata");
+            }
+            // Puts that were not settled before the redemption are void
+            if terms.is_puttable() {
+                remove_put_request(&bond_id, &buyer);
+            }
+
             let transfer_metadata_id: Name = format!(
-                "maturity_payment_{}%%{}", bond_id.name(), bond_id.domain_id())
+                "{payment_prefix}_{}%%{}", bond_id.name(), bond_id.domain_id())
                 .parse()
                 .dbg_expect("INTERNAL BUG: Unable to parse transfer metadata id");
 
@@ -106,24 +267,460 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
             transfer_metadata
                 .insert_with_limits("quantity".parse().unwrap(), quantity.into(), LIMITS)
                 .unwrap();
+            transfer_metadata
+                .insert_with_limits("residual".parse().unwrap(), residual.into(), LIMITS)
+                .unwrap();
             transfer_metadata
                 .insert_with_limits("currency".parse().unwrap(), bond_issuer_money.into(), LIMITS)
                 .unwrap();
             transfer_metadata
                 .insert_with_limits("bond_asset_id".parse().unwrap(), bond_id.clone().into(), LIMITS)
                 .unwrap();
+            insert_index_ratio(&mut transfer_metadata, index_ratio);
+            if let (Some(coupon), Some((period, coupon_rate))) = (coupon, final_coupon) {
+                let coupon = coupon.to_fixed().dbg_expect("Coupon payment overflow");
+
+                transfer_metadata
+                    .insert_with_limits("coupon".parse().unwrap(), coupon.into(), LIMITS)
+                    .unwrap();
+                transfer_metadata
+                    .insert_with_limits(
+                        "coupon_rate".parse().unwrap(),
+                        coupon_rate.to_fixed().dbg_expect("Coupon rate overflow").into(),
+                        LIMITS,
+                    )
+                    .unwrap();
+                transfer_metadata
+                    .insert_with_limits(
+                        "period_start_ms".parse().unwrap(),
+                        period.start_ms.into(),
+                        LIMITS,
+                    )
+                    .unwrap();
+                transfer_metadata
+                    .insert_with_limits("period_end_ms".parse().unwrap(), period.end_ms.into(), LIMITS)
+                    .unwrap();
+            }
+            if let Some((notice, _)) = call {
+                let clean_price = principal.to_fixed().dbg_expect("Bond total price overflow");
+                let accrued_interest = accrued_interest
+                    .unwrap_or(Decimal::ZERO)
+                    .to_fixed()
+                    .dbg_expect("Accrued interest overflow");
+
+                transfer_metadata
+                    .insert_with_limits("clean_price".parse().unwrap(), clean_price.into(), LIMITS)
+                    .unwrap();
+                transfer_metadata
+                    .insert_with_limits(
+                        "accrued_interest".parse().unwrap(),
+                        accrued_interest.into(),
+                        LIMITS,
+                    )
+                    .unwrap();
+                transfer_metadata
+                    .insert_with_limits(
+                        "call_price".parse().unwrap(),
+                        Value::String(notice.price.to_string()),
+                        LIMITS,
+                    )
+                    .unwrap();
+                transfer_metadata
+                    .insert_with_limits(
+                        "redemption_date_ms".parse().unwrap(),
+                        notice.redemption_date_ms.into(),
+                        LIMITS,
+                    )
+                    .unwrap();
+            }
 
             SetKeyValueExpr::new(buyer, transfer_metadata_id, transfer_metadata)
                 .execute()
                 .dbg_expect("Failed to set transfer info to buyer's metadata");
 
             info!(&format!(
-                "{bond_id}: Successfully set maturity payment info to buyer's metadata"
+                "{bond_id}: Successfully set {payment_prefix} info to buyer's metadata"
+            ));
+        }
+    }
+
+    let Some((notice, _)) = call else {
+        info!(&format!("{bond_id}: Bond matured"));
+        return;
+    };
+
+    unregister_remaining_triggers(&bond_id, &terms, notice.redemption_date_ms);
+    info!(&format!("{bond_id}: Bond redeemed early"));
+
+    /// Exact price of the given number of called bonds, excluding accrued interest
+    ///
+    /// The call price applies to the face value still outstanding at the redemption.
+    fn called_principal(terms: &BondTerms, notice: &CallNotice, quantity: u32) -> Option<Decimal> {
+        notice
+            .price
+            .per_bond(
+                terms.outstanding_nominal_value(notice.redemption_date_ms)?,
+                terms.rounding,
+            )?
+            .checked_mul_ratio(quantity.into(), 1, terms.rounding)
+    }
+
+    /// Unregisters the coupon, principal repayment, put settlement and maturation triggers
+    /// a called bond no longer needs
+    fn unregister_remaining_triggers(
+        bond_id: &AssetDefinitionId,
+        terms: &BondTerms,
+        redemption_date_ms: u64,
+    ) {
+        let mut trigger_ids = Vec::new();
+
+        if terms.bond_type.pays_coupons() {
+            match &terms.coupon_schedule {
+                None => trigger_ids.push(interest_payments_trigger_id(bond_id)),
+                Some(schedule) => trigger_ids.extend(
+                    schedule
+                        .payment_dates_ms
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, payment_date_ms)| **payment_date_ms > redemption_date_ms)
+                        .map(|(idx, _)| coupon_payment_trigger_id(bond_id, idx + 1)),
+                ),
+            }
+        }
+        trigger_ids.extend(
+            terms
+                .amortization
+                .repayments
+                .iter()
+                .enumerate()
+                .filter(|(_, repayment)| repayment.date_ms > redemption_date_ms)
+                .map(|(idx, _)| principal_repayment_trigger_id(bond_id, idx + 1)),
+        );
+        trigger_ids.extend(
+            (1..=terms.put_schedule.windows.len())
+                .filter(|&n| {
+                    terms
+                        .put_schedule
+                        .settlement_date_ms(n)
+                        .is_some_and(|settlement_date_ms| settlement_date_ms > redemption_date_ms)
+                })
+                .map(|n| put_settlement_trigger_id(bond_id, n)),
+        );
+        trigger_ids.push(bond_maturation_trigger_id(bond_id));
+
+        for trigger_id in trigger_ids {
+            info!(&format!("{trigger_id}: Unregistering trigger of the called bond"));
+
+            if let Err(err) = UnregisterExpr::new(trigger_id.clone()).execute() {
+                error!(&format!(
+                    "{trigger_id}: Failed to unregister trigger (reason = {err:?})"
+                ));
+            }
+        }
+    }
+
+    /// Repays the `n`-th slice of the principal to the holder
+    fn repay_principal(
+        bond_id: &AssetDefinitionId,
+        terms: &BondTerms,
+        n: usize,
+        index_ratio: Option<IndexRatio>,
+        issuer: &AccountId,
+        holder: AccountId,
+        quantity: u32,
+    ) {
+        let residual_key = residual_key(bond_id);
+        let repayment_date_ms = terms
+            .amortization
+            .repayments
+            .get(n - 1)
+            .dbg_expect("INTERNAL BUG: Principal repayment not in the amortization schedule")
+            .date_ms;
+        let carried_residual = find_residual(&holder, &residual_key).unwrap_or(Decimal::ZERO);
+        let repayment = terms
+            .principal_repayment(quantity, n)
+            .and_then(|amount| terms.index_adjusted(amount, index_ratio))
+            .and_then(|amount| amount.checked_add(carried_residual))
+            .map(|amount| terms.round(amount))
+            .dbg_expect("Principal repayment overflow");
+        let amount = repayment
+            .amount
+            .to_fixed()
+            .dbg_expect("Principal repayment overflow");
+        let residual = repayment
+            .residual
+            .to_fixed()
+            .dbg_expect("Principal repayment overflow");
+        let outstanding_nominal_value = terms
+            .outstanding_nominal_value(repayment_date_ms)
+            .and_then(Decimal::to_fixed)
+            .dbg_expect("Outstanding nominal value overflow");
+
+        let issuer_money = AssetId::new(terms.currency.clone(), issuer.clone());
+
+        info!(&format!(
+            "{bond_id}: Transferring {amount} {} from {issuer} to {holder}",
+            terms.currency
+        ));
+        TransferExpr::new(issuer_money.clone(), amount.clone(), holder.clone())
+            .execute()
+            .dbg_expect("Sending money failed. Country might have gone bankrupt");
+        SetKeyValueExpr::new(holder.clone(), residual_key, residual.clone())
+            .execute()
+            .dbg_expect("Failed to set residual to holder's metadata");
+
+        let repayment_idx = next_payment_idx(&holder, "principal_repayment_idx");
+        let transfer_metadata_id: Name = format!(
+            "principal_repayment_{}%%{}%%idx%%{repayment_idx}",
+            bond_id.name(),
+            bond_id.domain_id()
+        )
+        .parse()
+        .dbg_expect("INTERNAL BUG: Unable to parse transfer metadata id");
+
+        let mut transfer_metadata = Metadata::new();
+        transfer_metadata
+            .insert_with_limits("amount".parse().unwrap(), amount.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("quantity".parse().unwrap(), quantity.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("residual".parse().unwrap(), residual.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("currency".parse().unwrap(), issuer_money.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("bond_asset_id".parse().unwrap(), bond_id.clone().into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("repayment_number".parse().unwrap(), (n as u32).into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits(
+                "outstanding_nominal_value".parse().unwrap(),
+                outstanding_nominal_value.into(),
+                LIMITS,
+            )
+            .unwrap();
+        insert_index_ratio(&mut transfer_metadata, index_ratio);
+
+        SetKeyValueExpr::new(holder, transfer_metadata_id, transfer_metadata)
+            .execute()
+            .dbg_expect("Failed to set principal repayment info to holder's metadata");
+    }
+
+    /// Redeems the bonds the holder put in the `n`-th put window at the put price plus
+    /// the interest accrued until the settlement
+    ///
+    /// Only as many bonds as the holder still has are redeemed.
+    fn settle_put(
+        bond_id: &AssetDefinitionId,
+        terms: &BondTerms,
+        n: usize,
+        coupon_rate: Decimal,
+        index_ratio: Option<IndexRatio>,
+        issuer: &AccountId,
+        issued_bond: &Asset,
+    ) {
+        let holder = issued_bond.id().account_id().clone();
+        let holder_account = FindAccountById::new(holder.clone())
+            .execute()
+            .dbg_expect("INTERNAL BUG: Account not found");
+        let Some(request) = PutRequest::from_holder_metadata(holder_account.metadata(), bond_id)
+        else {
+            return;
+        };
+        if request.window as usize != n {
+            error!(&format!(
+                "{bond_id}: Put of {holder} requested in window {}, not {n}",
+                request.window
             ));
+            return;
+        }
+
+        let held_quantity: u32 = issued_bond
+            .value()
+            .to_owned()
+            .try_into()
+            .dbg_expect("INTERNAL BUG: bond quantity is not of the `u32` type");
+        let quantity = request.quantity.min(held_quantity);
+        RemoveKeyValueExpr::new(holder.clone(), put_request_key(bond_id))
+            .execute()
+            .dbg_expect("Failed to remove put request from holder's metadata");
+        if quantity == 0 {
+            error!(&format!("{bond_id}: {holder} no longer holds the bonds put"));
+            return;
         }
+
+        let residual_key = residual_key(bond_id);
+        let settlement_date_ms = terms
+            .put_schedule
+            .settlement_date_ms(n)
+            .dbg_expect("INTERNAL BUG: Put window not in the put schedule");
+        let principal = terms
+            .outstanding_principal(quantity, settlement_date_ms)
+            .and_then(|principal| terms.index_adjusted(principal, index_ratio))
+            .dbg_expect("Bond total price overflow");
+        let put_price = terms
+            .put_price(quantity, n, coupon_rate)
+            .and_then(|price| terms.index_adjusted(price, index_ratio))
+            .dbg_expect("Bond total price overflow");
+        let accrued_interest = terms
+            .accrued_interest(quantity, settlement_date_ms, coupon_rate)
+            .and_then(|accrued| terms.index_adjusted(accrued, index_ratio))
+            .dbg_expect("Accrued interest overflow");
+        let carried_residual = find_residual(&holder, &residual_key).unwrap_or(Decimal::ZERO);
+        let payment = put_price
+            .checked_add(accrued_interest)
+            .and_then(|amount| amount.checked_add(carried_residual))
+            .map(|amount| terms.round(amount))
+            .dbg_expect("Bond total price overflow");
+        let amount = payment
+            .amount
+            .to_fixed()
+            .dbg_expect("Bond total price overflow");
+        let residual = payment
+            .residual
+            .to_fixed()
+            .dbg_expect("Bond total price overflow");
+        let penalty = principal
+            .checked_sub(put_price)
+            .and_then(Decimal::to_fixed)
+            .dbg_expect("Put penalty overflow");
+
+        let issuer_money = AssetId::new(terms.currency.clone(), issuer.clone());
+
+        info!(&format!(
+            "{bond_id}: Transferring {amount} {} from {issuer} to {holder}",
+            terms.currency
+        ));
+        TransferExpr::new(issuer_money, amount.clone(), holder.clone())
+            .execute()
+            .dbg_expect("Sending money failed. Country might have gone bankrupt");
+        BurnExpr::new(quantity, issued_bond.id().clone())
+            .execute()
+            .dbg_expect("Burning bonds failed");
+        SetKeyValueExpr::new(holder.clone(), residual_key, residual.clone())
+            .execute()
+            .dbg_expect("Failed to set residual to holder's metadata");
+
+        let redeem_bond_payment_idx = next_payment_idx(&holder, "redeem_bond_payment_idx");
+        let transfer_metadata_id: Name = format!(
+            "redeem_bond_payment_{}%%{}%%idx%%{redeem_bond_payment_idx}",
+            bond_id.name(),
+            bond_id.domain_id()
+        )
+        .parse()
+        .dbg_expect("INTERNAL BUG: Unable to parse transfer metadata id");
+
+        let put_price = put_price.to_fixed().dbg_expect("Bond total price overflow");
+        let accrued_interest = accrued_interest
+            .to_fixed()
+            .dbg_expect("Accrued interest overflow");
+
+        let mut transfer_metadata = Metadata::new();
+        transfer_metadata
+            .insert_with_limits("amount".parse().unwrap(), amount.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("quantity".parse().unwrap(), quantity.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("currency".parse().unwrap(), terms.currency.clone().into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("bond_asset_id".parse().unwrap(), bond_id.clone().into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("residual".parse().unwrap(), residual.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("put_price".parse().unwrap(), put_price.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits(
+                "accrued_interest".parse().unwrap(),
+                accrued_interest.into(),
+                LIMITS,
+            )
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("penalty".parse().unwrap(), penalty.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("put_window".parse().unwrap(), request.window.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits(
+                "settlement_date_ms".parse().unwrap(),
+                settlement_date_ms.into(),
+                LIMITS,
+            )
+            .unwrap();
+        insert_index_ratio(&mut transfer_metadata, index_ratio);
+
+        SetKeyValueExpr::new(holder, transfer_metadata_id, transfer_metadata)
+            .execute()
+            .dbg_expect("Failed to set redeem bond info to holder's metadata");
+    }
+
+    /// Records the index the principal was scaled by alongside the payment
+    fn insert_index_ratio(transfer_metadata: &mut Metadata, index_ratio: Option<IndexRatio>) {
+        let Some(index_ratio) = index_ratio else {
+            return;
+        };
+
+        transfer_metadata
+            .insert_with_limits(
+                "index_value".parse().unwrap(),
+                index_ratio.index_value.to_fixed().dbg_expect("Index value overflow").into(),
+                LIMITS,
+            )
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits(
+                "index_ratio".parse().unwrap(),
+                index_ratio.ratio.to_fixed().dbg_expect("Index ratio overflow").into(),
+                LIMITS,
+            )
+            .unwrap();
     }
 
-    info!(&format!("{bond_id}: Bond matured"));
+    /// Removes the put request the holder queued for the bond, if any
+    fn remove_put_request(bond_id: &AssetDefinitionId, holder: &AccountId) {
+        let put_request_key = put_request_key(bond_id);
+
+        let has_request = FindAccountById::new(holder.clone())
+            .execute()
+            .dbg_expect("INTERNAL BUG: Account not found")
+            .metadata()
+            .get(&put_request_key)
+            .is_some();
+        if has_request {
+            RemoveKeyValueExpr::new(holder.clone(), put_request_key)
+                .execute()
+                .dbg_expect("Failed to remove put request from holder's metadata");
+        }
+    }
+
+    fn find_residual(buyer: &AccountId, residual_key: &Name) -> Option<Decimal> {
+        FindAccountById::new(buyer.clone())
+            .execute()
+            .dbg_expect("INTERNAL BUG: Account not found")
+            .metadata()
+            .get(residual_key)
+            .map(|residual| {
+                let residual: Fixed = residual
+                    .to_owned()
+                    .try_into()
+                    .dbg_expect("INTERNAL BUG: coupon residual not of the `Fixed` type");
+
+                Decimal::from_fixed(residual)
+                    .dbg_expect("INTERNAL BUG: coupon residual out of range")
+            })
+    }
 
     // TODO: Should all related triggers be unregistered at bond maturation?
     // Or should they be unregistered when asset definition is unregistered?
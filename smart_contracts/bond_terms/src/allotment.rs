@@ -0,0 +1,328 @@
+//! Allotment of oversubscribed bonds at subscription close
+//!
+//! Bonds with an [`Allotment`] aren't sold first-come. Buy orders placed during the
+//! subscription period lock the buyer's funds with the issuer and are queued as a
+//! [`SubscriptionOrder`] in the buyer's metadata under [`subscription_order_key`]. At the close
+//! the issuer's inventory is allotted to the orders tier by tier, pro-rata within the tier that
+//! can't be filled, and the funds of the bonds not allotted are refunded.
+
+use alloc::{borrow::ToOwned as _, format, string::String, vec, vec::Vec};
+use core::{fmt, str::FromStr};
+
+use iroha_data_model::prelude::*;
+
+use crate::{insert_entry, money::Decimal};
+
+/// Prefix of the buyer's account metadata keys holding the queued subscription orders
+pub const SUBSCRIPTION_ORDER_KEY_PREFIX: &str = "subscription_order_";
+
+/// Key of the buyer's account metadata under which the subscription order for the bond is queued
+pub fn subscription_order_key(bond_id: &AssetDefinitionId) -> Name {
+    format!(
+        "{SUBSCRIPTION_ORDER_KEY_PREFIX}{}%%{}",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Unable to parse subscription order key")
+}
+
+/// Bond whose subscription order is queued under the given key of the buyer's account metadata
+pub fn subscription_order_bond_id(key: &Name) -> Option<AssetDefinitionId> {
+    crate::bond_id_from_key(key, SUBSCRIPTION_ORDER_KEY_PREFIX)
+}
+
+/// Rounding of pro-rata allotments to whole lots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllotmentRounding {
+    /// Allotments are rounded down, the lots left over stay with the issuer
+    Down,
+    /// Allotments are rounded down and the lots left over go one each to the orders with the
+    /// largest fractional remainder, the earlier order first on ties
+    LargestRemainder,
+}
+
+impl AllotmentRounding {
+    /// Name under which the rounding is stored, e.g. `largest_remainder`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Down => "down",
+            Self::LargestRemainder => "largest_remainder",
+        }
+    }
+}
+
+impl fmt::Display for AllotmentRounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Allotment rounding name that isn't recognized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownAllotmentRounding;
+
+impl fmt::Display for UnknownAllotmentRounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of `down`, `largest_remainder`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownAllotmentRounding {}
+
+impl FromStr for AllotmentRounding {
+    type Err = UnknownAllotmentRounding;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Down, Self::LargestRemainder]
+            .into_iter()
+            .find(|rounding| rounding.as_str() == s)
+            .ok_or(UnknownAllotmentRounding)
+    }
+}
+
+/// How the issuer's inventory is allotted to the subscription orders at the close
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Allotment {
+    /// Smallest number of bonds that can be ordered or allotted, orders are whole lots
+    pub lot_size: u32,
+    /// Rounding of pro-rata allotments to whole lots
+    pub rounding: AllotmentRounding,
+    /// Countries of the investors served first, tier by tier, investors of other countries
+    /// form the last tier; a single tier of everyone if empty
+    pub priority_tiers: Vec<Vec<String>>,
+}
+
+impl Allotment {
+    /// Check that lots are not empty and don't exceed the bonds offered
+    pub fn is_valid(&self, quantity: u32) -> bool {
+        self.lot_size > 0
+            && self.lot_size <= quantity
+            && self.priority_tiers.iter().all(|tier| !tier.is_empty())
+    }
+
+    /// Priority tier of an investor from the given country, counted from 0
+    pub fn tier_of(&self, country: Option<&str>) -> u32 {
+        let tier = country
+            .and_then(|country| {
+                self.priority_tiers
+                    .iter()
+                    .position(|tier| tier.iter().any(|tier_country| tier_country == country))
+            })
+            .unwrap_or(self.priority_tiers.len());
+
+        u32::try_from(tier).expect("INTERNAL BUG: Too many priority tiers")
+    }
+
+    /// Number of bonds allotted to each of the orders out of the available bonds
+    ///
+    /// Orders are given as their tier and quantity, in the order they were placed. Tiers are
+    /// filled in full while the bonds last; the orders of the tier that can't be filled are
+    /// allotted whole lots in proportion to their quantity.
+    pub fn allot(&self, orders: &[(u32, u32)], available: u32) -> Vec<u32> {
+        let lot_size = self.lot_size.max(1);
+        let mut allotted = vec![0; orders.len()];
+        let mut available_lots = available / lot_size;
+
+        let mut tiers: Vec<u32> = orders.iter().map(|&(tier, _)| tier).collect();
+        tiers.sort_unstable();
+        tiers.dedup();
+
+        for tier in tiers {
+            let tier_orders: Vec<usize> = (0..orders.len())
+                .filter(|&idx| orders[idx].0 == tier)
+                .collect();
+            let demanded_lots: u64 = tier_orders
+                .iter()
+                .map(|&idx| u64::from(orders[idx].1 / lot_size))
+                .sum();
+
+            if demanded_lots <= u64::from(available_lots) {
+                for &idx in &tier_orders {
+                    allotted[idx] = orders[idx].1 / lot_size * lot_size;
+                }
+                available_lots -= u32::try_from(demanded_lots).expect("Bounded by available lots");
+                continue;
+            }
+
+            let lots = self.pro_rata(
+                tier_orders.iter().map(|&idx| orders[idx].1 / lot_size),
+                available_lots,
+                demanded_lots,
+            );
+            for (&idx, lots) in tier_orders.iter().zip(lots) {
+                allotted[idx] = lots * lot_size;
+            }
+            break;
+        }
+
+        allotted
+    }
+
+    /// Lots allotted to each of the demands, in proportion to them, out of the available lots
+    fn pro_rata(
+        &self,
+        demands: impl Iterator<Item = u32>,
+        available_lots: u32,
+        demanded_lots: u64,
+    ) -> Vec<u32> {
+        let (mut lots, remainders): (Vec<u32>, Vec<u64>) = demands
+            .map(|demand| {
+                let share = u64::from(demand) * u64::from(available_lots);
+                (
+                    u32::try_from(share / demanded_lots).expect("Bounded by the demand"),
+                    share % demanded_lots,
+                )
+            })
+            .unzip();
+
+        if self.rounding == AllotmentRounding::LargestRemainder {
+            let left_over = available_lots - lots.iter().sum::<u32>();
+
+            let mut by_remainder: Vec<usize> = (0..lots.len()).collect();
+            // Stable sort keeps the earlier order first on ties
+            by_remainder.sort_by(|&a, &b| remainders[b].cmp(&remainders[a]));
+            for idx in by_remainder.into_iter().take(left_over as usize) {
+                lots[idx] += 1;
+            }
+        }
+
+        lots
+    }
+}
+
+/// Buy order queued with its funds locked until the subscription closes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionOrder {
+    /// Number of bonds ordered
+    pub quantity: u32,
+    /// Priority tier of the buyer, counted from 0
+    pub tier: u32,
+    /// Price of the bonds ordered excluding accrued interest, rounded
+    pub clean_price: Decimal,
+    /// Interest accrued on the bonds ordered, rounded
+    pub accrued_interest: Decimal,
+    /// Purchase fee, charged only if bonds are allotted
+    pub fee: Decimal,
+    /// Time of the first order (in milliseconds since UNIX epoch)
+    pub order_time_ms: u64,
+}
+
+impl SubscriptionOrder {
+    /// Funds locked with the issuer for the order, returns `None` on overflow
+    pub fn locked_amount(&self) -> Option<Decimal> {
+        self.clean_price
+            .checked_add(self.accrued_interest)?
+            .checked_add(self.fee)
+    }
+
+    /// Subscription order queued in the metadata of the buyer, if any
+    pub fn from_buyer_metadata(metadata: &Metadata, bond_id: &AssetDefinitionId) -> Option<Self> {
+        let Value::LimitedMetadata(order) = metadata.get(&subscription_order_key(bond_id))? else {
+            return None;
+        };
+
+        let decimal = |key: &str| -> Option<Decimal> {
+            let amount: Fixed = order.get(key)?.to_owned().try_into().ok()?;
+            Decimal::from_fixed(amount)
+        };
+
+        Some(Self {
+            quantity: order.get("quantity")?.to_owned().try_into().ok()?,
+            tier: order.get("tier")?.to_owned().try_into().ok()?,
+            clean_price: decimal("clean_price")?,
+            accrued_interest: decimal("accrued_interest")?,
+            fee: decimal("fee")?,
+            order_time_ms: order.get("order_time_ms")?.to_owned().try_into().ok()?,
+        })
+    }
+}
+
+impl From<SubscriptionOrder> for Value {
+    fn from(order: SubscriptionOrder) -> Self {
+        let mut metadata = Metadata::new();
+
+        let fixed = |amount: Decimal| -> Value {
+            amount
+                .to_fixed()
+                .expect("INTERNAL BUG: Subscription order amount out of `Fixed` range")
+                .into()
+        };
+
+        insert_entry(&mut metadata, "quantity", order.quantity.into());
+        insert_entry(&mut metadata, "tier", order.tier.into());
+        insert_entry(&mut metadata, "clean_price", fixed(order.clean_price));
+        insert_entry(
+            &mut metadata,
+            "accrued_interest",
+            fixed(order.accrued_interest),
+        );
+        insert_entry(&mut metadata, "fee", fixed(order.fee));
+        insert_entry(&mut metadata, "order_time_ms", order.order_time_ms.into());
+
+        Value::LimitedMetadata(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allotment(lot_size: u32, rounding: AllotmentRounding) -> Allotment {
+        Allotment {
+            lot_size,
+            rounding,
+            priority_tiers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn orders_are_filled_in_full_if_undersubscribed() {
+        let allotment = allotment(10, AllotmentRounding::LargestRemainder);
+
+        assert_eq!(allotment.allot(&[(0, 30), (0, 20)], 100), vec![30, 20]);
+    }
+
+    #[test]
+    fn largest_remainders_get_the_lots_left_over() {
+        let orders = [(0, 50), (0, 30), (0, 20)];
+
+        // 16.5, 9.9 and 6.6 bonds pro-rata
+        assert_eq!(
+            allotment(1, AllotmentRounding::LargestRemainder).allot(&orders, 33),
+            vec![16, 10, 7]
+        );
+        assert_eq!(
+            allotment(1, AllotmentRounding::Down).allot(&orders, 33),
+            vec![16, 9, 6]
+        );
+    }
+
+    #[test]
+    fn earlier_orders_win_ties_for_the_lots_left_over() {
+        let allotment = allotment(1, AllotmentRounding::LargestRemainder);
+
+        assert_eq!(
+            allotment.allot(&[(0, 10), (0, 10), (0, 10)], 20),
+            vec![7, 7, 6]
+        );
+    }
+
+    #[test]
+    fn tiers_are_filled_in_order_and_pro_rata_in_whole_lots() {
+        let allotment = Allotment {
+            priority_tiers: vec![vec!["PW".to_owned()]],
+            ..allotment(10, AllotmentRounding::LargestRemainder)
+        };
+        assert_eq!(allotment.tier_of(Some("PW")), 0);
+        assert_eq!(allotment.tier_of(Some("US")), 1);
+        assert_eq!(allotment.tier_of(None), 1);
+
+        // The 60 bonds left after the first tier are split 5:3 in lots of 10
+        assert_eq!(
+            allotment.allot(&[(1, 50), (0, 40), (1, 30)], 100),
+            vec![40, 40, 20]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Principal of amortizing bonds repaid in scheduled slices before maturity
+
+use alloc::vec::Vec;
+
+use crate::money::{Decimal, RoundingMode};
+
+/// Slice of the principal repaid on a date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrincipalRepayment {
+    /// Date of the repayment (in milliseconds since UNIX epoch)
+    pub date_ms: u64,
+    /// Repaid fraction of the original face value, e.g. `0.25` for a quarter
+    pub fraction: Decimal,
+}
+
+/// Schedule of principal repayments, ordered by date
+///
+/// Whatever isn't repaid by the schedule is repaid at maturity.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmortizationSchedule {
+    pub repayments: Vec<PrincipalRepayment>,
+}
+
+impl AmortizationSchedule {
+    /// Check that the repayments are positive, add up to at most the whole principal
+    /// and fall on increasing dates strictly between registration and maturation
+    pub fn is_valid(&self, registration_time_ms: u64, maturation_date_ms: u64) -> bool {
+        let dates_valid = self
+            .repayments
+            .windows(2)
+            .all(|repayments| repayments[0].date_ms < repayments[1].date_ms)
+            && self.repayments.iter().all(|repayment| {
+                registration_time_ms < repayment.date_ms && repayment.date_ms < maturation_date_ms
+            });
+        let fractions_valid = self
+            .repayments
+            .iter()
+            .all(|repayment| !repayment.fraction.is_negative() && !repayment.fraction.is_zero());
+
+        dates_valid
+            && fractions_valid
+            && self
+                .repaid_fraction(u64::MAX)
+                .is_some_and(|repaid| repaid <= Decimal::ONE)
+    }
+
+    /// Fraction of the original face value repaid at or before the given time
+    ///
+    /// Returns `None` on overflow.
+    pub fn repaid_fraction(&self, time_ms: u64) -> Option<Decimal> {
+        self.repayments
+            .iter()
+            .take_while(|repayment| repayment.date_ms <= time_ms)
+            .try_fold(Decimal::ZERO, |repaid, repayment| {
+                repaid.checked_add(repayment.fraction)
+            })
+    }
+
+    /// Face value of a single bond still outstanding after the repayments made by the given time
+    ///
+    /// Returns `None` on overflow.
+    pub fn outstanding_nominal_value(
+        &self,
+        nominal_value: Decimal,
+        time_ms: u64,
+        mode: RoundingMode,
+    ) -> Option<Decimal> {
+        let outstanding = Decimal::ONE.checked_sub(self.repaid_fraction(time_ms)?)?;
+
+        nominal_value.checked_mul(outstanding, mode)
+    }
+
+    /// Dates of the repayments strictly between the given times
+    pub fn dates_between(&self, start_ms: u64, end_ms: u64) -> impl Iterator<Item = u64> + '_ {
+        self.repayments
+            .iter()
+            .map(|repayment| repayment.date_ms)
+            .filter(move |&date_ms| start_ms < date_ms && date_ms < end_ms)
+    }
+}
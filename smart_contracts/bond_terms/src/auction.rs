@@ -0,0 +1,939 @@
+//! Issuance of bonds by sealed-bid auction
+//!
+//! Bonds with an [`Auction`] are sold to the best bids placed during the subscription period.
+//! A `buy_bonds` order carrying a bid locks the price of the bonds at the bid with the issuer and
+//! is queued as an [`AuctionBid`] in the bidder's metadata under [`auction_bid_key`]. At the close
+//! the bids are ranked and filled down to the stop-out bid, pro-rata among the bids at it, and the
+//! [`AuctionResult`] is stored in the metadata of the bond under [`AUCTION_RESULT_KEY`].
+//!
+//! Orders without a bid join the [`NonCompetitiveTranche`] if the auction has one. They lock
+//! the price of the bonds at par, are filled first and pay the clearing price of the auction.
+
+use alloc::{borrow::ToOwned as _, format, vec, vec::Vec};
+use core::{cmp::Ordering, fmt, str::FromStr};
+
+use iroha_data_model::prelude::*;
+
+use crate::{
+    allotment::{Allotment, AllotmentRounding},
+    inflation::IndexRatio,
+    insert_entry,
+    money::Decimal,
+    BondTerms,
+};
+
+/// Prefix of the bidder's account metadata keys holding the sealed bids
+pub const AUCTION_BID_KEY_PREFIX: &str = "auction_bid_";
+
+/// Key of the bond metadata holding the auction result published at subscription close
+pub const AUCTION_RESULT_KEY: &str = "auction_result";
+
+/// Key of the bidder's account metadata under which the bid for the bond is sealed
+pub fn auction_bid_key(bond_id: &AssetDefinitionId) -> Name {
+    format!(
+        "{AUCTION_BID_KEY_PREFIX}{}%%{}",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Unable to parse auction bid key")
+}
+
+/// Bond whose bid is sealed under the given key of the bidder's account metadata
+pub fn auction_bid_bond_id(key: &Name) -> Option<AssetDefinitionId> {
+    crate::bond_id_from_key(key, AUCTION_BID_KEY_PREFIX)
+}
+
+/// Price paid by the winning bids
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuctionPricing {
+    /// Every winning bid pays the stop-out price (Dutch auction)
+    Uniform,
+    /// Every winning bid pays the price it bid
+    Discriminatory,
+}
+
+impl AuctionPricing {
+    /// Name under which the pricing is stored, e.g. `uniform`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Uniform => "uniform",
+            Self::Discriminatory => "discriminatory",
+        }
+    }
+}
+
+impl fmt::Display for AuctionPricing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Auction pricing name that isn't recognized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownAuctionPricing;
+
+impl fmt::Display for UnknownAuctionPricing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of `uniform`, `discriminatory`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownAuctionPricing {}
+
+impl FromStr for AuctionPricing {
+    type Err = UnknownAuctionPricing;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Uniform, Self::Discriminatory]
+            .into_iter()
+            .find(|pricing| pricing.as_str() == s)
+            .ok_or(UnknownAuctionPricing)
+    }
+}
+
+/// What the bids are quoted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BidBasis {
+    /// Percentage of par, e.g. `98.75`, the highest bids win
+    Price,
+    /// Simple yearly yield to maturation over the bond's day count, e.g. `0.0425`,
+    /// the lowest bids win
+    Yield,
+}
+
+impl BidBasis {
+    /// Name under which the basis is stored, e.g. `yield`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Price => "price",
+            Self::Yield => "yield",
+        }
+    }
+}
+
+impl fmt::Display for BidBasis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Bid basis name that isn't recognized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownBidBasis;
+
+impl fmt::Display for UnknownBidBasis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of `price`, `yield`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownBidBasis {}
+
+impl FromStr for BidBasis {
+    type Err = UnknownBidBasis;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Price, Self::Yield]
+            .into_iter()
+            .find(|basis| basis.as_str() == s)
+            .ok_or(UnknownBidBasis)
+    }
+}
+
+/// How the bonds are auctioned at the close of the subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Auction {
+    /// Price paid by the winning bids
+    pub pricing: AuctionPricing,
+    /// What the bids are quoted in
+    pub basis: BidBasis,
+    /// Smallest number of bonds that can be bid for or allotted, bids are whole lots
+    pub lot_size: u32,
+    /// Rounding of the pro-rata allotment to the bids at the stop-out
+    pub rounding: AllotmentRounding,
+    /// Lowest price or highest yield accepted, any bid if not given
+    pub reserve: Option<Decimal>,
+    /// Tranche for orders without a bid, all orders must bid if not given
+    pub non_competitive: Option<NonCompetitiveTranche>,
+}
+
+/// Bonds set aside for orders without a bid, filled first at the clearing price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonCompetitiveTranche {
+    /// Most bonds a single investor can order
+    pub investor_cap: u32,
+    /// Most bonds of the tranche, orders are filled pro-rata if they exceed it
+    pub total_cap: u32,
+}
+
+impl Auction {
+    /// Check that lots are not empty and don't exceed the bonds offered, that the reserve is
+    /// a valid bid, and that the caps of the non-competitive tranche are whole lots leaving at
+    /// least a lot to the competitive bids
+    pub fn is_valid(&self, quantity: u32) -> bool {
+        self.lot_size > 0
+            && self.lot_size <= quantity
+            && self
+                .reserve
+                .map_or(true, |reserve| self.is_valid_bid(reserve))
+            && self.non_competitive.map_or(true, |tranche| {
+                tranche.investor_cap > 0
+                    && tranche.investor_cap <= tranche.total_cap
+                    && tranche.investor_cap % self.lot_size == 0
+                    && tranche.total_cap % self.lot_size == 0
+                    && tranche
+                        .total_cap
+                        .checked_add(self.lot_size)
+                        .is_some_and(|total| total <= quantity)
+            })
+    }
+
+    /// Bid at which the funds of non-competitive orders are locked: par, or a zero yield
+    pub fn par_bid(&self) -> Decimal {
+        match self.basis {
+            BidBasis::Price => Decimal::from_integer(100).expect("100 fits a decimal"),
+            BidBasis::Yield => Decimal::ZERO,
+        }
+    }
+
+    /// Whether the bid can be placed: prices must be positive and yields not negative
+    pub fn is_valid_bid(&self, bid: Decimal) -> bool {
+        match self.basis {
+            BidBasis::Price => !bid.is_negative() && !bid.is_zero(),
+            BidBasis::Yield => !bid.is_negative(),
+        }
+    }
+
+    /// Whether the bid is at or better than the reserve
+    pub fn meets_reserve(&self, bid: Decimal) -> bool {
+        self.reserve.map_or(true, |reserve| {
+            self.cmp_bids(bid, reserve) != Ordering::Greater
+        })
+    }
+
+    /// Order of two bids, the better one first
+    pub fn cmp_bids(&self, a: Decimal, b: Decimal) -> Ordering {
+        match self.basis {
+            BidBasis::Price => b.cmp(&a),
+            BidBasis::Yield => a.cmp(&b),
+        }
+    }
+
+    /// Number of bonds allotted to each of the bids out of the available bonds
+    ///
+    /// Bids are given as their bid and quantity, in the order they were placed. Bids are filled
+    /// from the best one while the bonds last; bids at the stop-out are allotted whole lots in
+    /// proportion to their quantity. Bids below the reserve are not allotted.
+    pub fn allot(&self, bids: &[(Decimal, u32)], available: u32) -> Vec<u32> {
+        let mut levels: Vec<Decimal> = bids
+            .iter()
+            .map(|&(bid, _)| bid)
+            .filter(|&bid| self.meets_reserve(bid))
+            .collect();
+        levels.sort_unstable_by(|&a, &b| self.cmp_bids(a, b));
+        levels.dedup();
+
+        // Every bid level is a tier of its own, rejected bids demand nothing
+        let orders: Vec<(u32, u32)> = bids
+            .iter()
+            .map(|&(bid, quantity)| {
+                match levels.binary_search_by(|&level| self.cmp_bids(level, bid)) {
+                    Ok(level) => (
+                        u32::try_from(level).expect("INTERNAL BUG: Too many bid levels"),
+                        quantity,
+                    ),
+                    Err(_) => (u32::MAX, 0),
+                }
+            })
+            .collect();
+
+        Allotment {
+            lot_size: self.lot_size,
+            rounding: self.rounding,
+            priority_tiers: Vec::new(),
+        }
+        .allot(&orders, available)
+    }
+
+    /// Number of bonds allotted to each of the non-competitive orders out of the available bonds
+    ///
+    /// Orders are given as their quantity, in the order they were placed. They are filled in
+    /// full up to the total cap of the tranche, and allotted whole lots pro-rata beyond it.
+    pub fn allot_non_competitive(&self, quantities: &[u32], available: u32) -> Vec<u32> {
+        let Some(tranche) = self.non_competitive else {
+            return vec![0; quantities.len()];
+        };
+        let orders: Vec<(u32, u32)> = quantities.iter().map(|&quantity| (0, quantity)).collect();
+
+        Allotment {
+            lot_size: self.lot_size,
+            rounding: self.rounding,
+            priority_tiers: Vec::new(),
+        }
+        .allot(&orders, tranche.total_cap.min(available))
+    }
+
+    /// Worst of the bids allotted any bonds, if any
+    pub fn stop_out(&self, bids: &[(Decimal, u32)], allotted: &[u32]) -> Option<Decimal> {
+        bids.iter()
+            .zip(allotted)
+            .filter(|(_, &allotted)| allotted > 0)
+            .map(|(&(bid, _), _)| bid)
+            .max_by(|&a, &b| self.cmp_bids(a, b))
+    }
+
+    /// Fill the competitive bids and the non-competitive orders out of the offered bonds
+    ///
+    /// Bids and orders are given in the order they were placed. The non-competitive tranche is
+    /// filled first and the competitive bids get the rest. Non-competitive orders lock the price
+    /// at par, so a clearing price above par fills only the lots their funds pay for, and none
+    /// are filled if no competitive bid is; the lots they free are offered to the competitive
+    /// bids again. Clearing prices of inflation-linked bonds are scaled by the index ratio
+    /// at the close, competitive bids whose locked price doesn't pay for the bonds allotted at
+    /// the clearing price are left out and the auction cleared again without them. Returns
+    /// `None` on overflow.
+    pub fn clear(
+        &self,
+        terms: &BondTerms,
+        competitive: &[AuctionBid],
+        non_competitive: &[AuctionBid],
+        offered: u32,
+        index_ratio: Option<IndexRatio>,
+    ) -> Option<Clearing> {
+        let mut ranked: Vec<(Decimal, u32)> = competitive
+            .iter()
+            .map(|bid| Some((bid.bid?, bid.quantity)))
+            .collect::<Option<_>>()?;
+        let mut non_competitive_allotted = self.allot_non_competitive(
+            &non_competitive
+                .iter()
+                .map(|order| order.quantity)
+                .collect::<Vec<_>>(),
+            offered,
+        );
+
+        loop {
+            let non_competitive_sold: u32 = non_competitive_allotted.iter().sum();
+            let allotted = self.allot(&ranked, offered.checked_sub(non_competitive_sold)?);
+            let stop_out = self.stop_out(&ranked, &allotted);
+
+            let clean_prices = competitive
+                .iter()
+                .zip(&allotted)
+                .map(|(bid, &allotted)| match (self.pricing, stop_out) {
+                    (AuctionPricing::Uniform, Some(stop_out)) => terms
+                        .bid_price(allotted, stop_out)
+                        .and_then(|price| terms.index_adjusted(price, index_ratio)),
+                    _ => bid.clean_price.checked_mul_ratio(
+                        allotted.into(),
+                        bid.quantity.into(),
+                        terms.rounding,
+                    ),
+                })
+                .collect::<Option<Vec<_>>>()?;
+
+            let mut is_unfunded = false;
+            for ((bid, (_, quantity)), &clean_price) in
+                competitive.iter().zip(&mut ranked).zip(&clean_prices)
+            {
+                if *quantity > 0 && terms.round(clean_price).amount > bid.clean_price {
+                    *quantity = 0;
+                    is_unfunded = true;
+                }
+            }
+            if is_unfunded {
+                continue;
+            }
+
+            // Non-competitive orders pay the stop-out price, or the average price of the
+            // competitive bids with discriminatory pricing
+            let non_competitive_price = match (self.pricing, stop_out) {
+                (_, None) => None,
+                (AuctionPricing::Uniform, Some(stop_out)) => {
+                    Some(terms.index_adjusted(terms.bid_price(1, stop_out)?, index_ratio)?)
+                }
+                (AuctionPricing::Discriminatory, Some(_)) => {
+                    let paid = clean_prices
+                        .iter()
+                        .try_fold(Decimal::ZERO, |paid, &price| paid.checked_add(price))?;
+                    let sold: u32 = allotted.iter().sum();
+
+                    Some(paid.checked_mul_ratio(1, sold.into(), terms.rounding)?)
+                }
+            };
+
+            let mut is_shrunk = false;
+            for (order, allotted) in non_competitive.iter().zip(&mut non_competitive_allotted) {
+                while *allotted > 0
+                    && !non_competitive_price
+                        .is_some_and(|price| is_covered(terms, price, *allotted, order.clean_price))
+                {
+                    *allotted -= self.lot_size.min(*allotted);
+                    is_shrunk = true;
+                }
+            }
+
+            if !is_shrunk {
+                return Some(Clearing {
+                    allotted,
+                    clean_prices,
+                    stop_out,
+                    non_competitive_allotted,
+                    non_competitive_price,
+                });
+            }
+        }
+    }
+}
+
+/// Whether the price locked for an order pays for the bonds allotted at the given price per bond
+fn is_covered(terms: &BondTerms, price: Decimal, allotted: u32, locked_price: Decimal) -> bool {
+    price
+        .checked_mul_ratio(allotted.into(), 1, terms.rounding)
+        .is_some_and(|clean_price| terms.round(clean_price).amount <= locked_price)
+}
+
+/// Bonds allotted to the bids at the close of an auction and the prices they pay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clearing {
+    /// Number of bonds allotted to each competitive bid
+    pub allotted: Vec<u32>,
+    /// Exact price of the bonds allotted to each competitive bid, excluding accrued interest
+    pub clean_prices: Vec<Decimal>,
+    /// Worst bid allotted any bonds, none if no competitive bid was
+    pub stop_out: Option<Decimal>,
+    /// Number of bonds allotted to each non-competitive order
+    pub non_competitive_allotted: Vec<u32>,
+    /// Exact price of a single bond paid by non-competitive orders, excluding accrued interest
+    pub non_competitive_price: Option<Decimal>,
+}
+
+/// Sealed bid with the price of the bonds at the bid locked until the auction closes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuctionBid {
+    /// Number of bonds bid for
+    pub quantity: u32,
+    /// Price or yield bid, none for non-competitive orders
+    pub bid: Option<Decimal>,
+    /// Price of the bonds bid for at the bid, or at par if non-competitive,
+    /// excluding accrued interest, rounded
+    pub clean_price: Decimal,
+    /// Interest accrued on the bonds bid for by the close, rounded
+    pub accrued_interest: Decimal,
+    /// Purchase fee, charged only if bonds are allotted
+    pub fee: Decimal,
+    /// Time of the first order of the bid (in milliseconds since UNIX epoch)
+    pub bid_time_ms: u64,
+}
+
+impl AuctionBid {
+    /// Funds locked with the issuer for the bid, returns `None` on overflow
+    pub fn locked_amount(&self) -> Option<Decimal> {
+        self.clean_price
+            .checked_add(self.accrued_interest)?
+            .checked_add(self.fee)
+    }
+
+    /// Bid sealed in the metadata of the bidder, if any
+    pub fn from_bidder_metadata(metadata: &Metadata, bond_id: &AssetDefinitionId) -> Option<Self> {
+        let Value::LimitedMetadata(bid) = metadata.get(&auction_bid_key(bond_id))? else {
+            return None;
+        };
+
+        let decimal = |key: &str| -> Option<Decimal> {
+            let amount: Fixed = bid.get(key)?.to_owned().try_into().ok()?;
+            Decimal::from_fixed(amount)
+        };
+
+        Some(Self {
+            quantity: bid.get("quantity")?.to_owned().try_into().ok()?,
+            bid: decimal("bid"),
+            clean_price: decimal("clean_price")?,
+            accrued_interest: decimal("accrued_interest")?,
+            fee: decimal("fee")?,
+            bid_time_ms: bid.get("bid_time_ms")?.to_owned().try_into().ok()?,
+        })
+    }
+}
+
+impl From<AuctionBid> for Value {
+    fn from(bid: AuctionBid) -> Self {
+        let mut metadata = Metadata::new();
+
+        insert_entry(&mut metadata, "quantity", bid.quantity.into());
+        if let Some(price) = bid.bid {
+            insert_entry(&mut metadata, "bid", fixed(price));
+        }
+        insert_entry(&mut metadata, "clean_price", fixed(bid.clean_price));
+        insert_entry(
+            &mut metadata,
+            "accrued_interest",
+            fixed(bid.accrued_interest),
+        );
+        insert_entry(&mut metadata, "fee", fixed(bid.fee));
+        insert_entry(&mut metadata, "bid_time_ms", bid.bid_time_ms.into());
+
+        Value::LimitedMetadata(metadata)
+    }
+}
+
+/// Outcome of the auction, published at subscription close
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuctionResult {
+    /// Time at which the auction closed (in milliseconds since UNIX epoch)
+    pub close_time_ms: u64,
+    /// Price paid by the winning bids
+    pub pricing: AuctionPricing,
+    /// What the bids were quoted in
+    pub basis: BidBasis,
+    /// Number of bonds auctioned, the issuer's inventory at the close
+    pub offered: u32,
+    /// Number of bids
+    pub bids: u32,
+    /// Number of bonds bid for
+    pub bid_quantity: u64,
+    /// Number of bonds allotted
+    pub sold: u32,
+    /// Worst bid allotted any bonds, none if no bonds were sold
+    pub stop_out: Option<Decimal>,
+    /// Price of a single bond at the stop-out, excluding accrued interest
+    pub stop_out_price: Option<Decimal>,
+    /// Average price paid for a single bond, excluding accrued interest
+    pub average_price: Option<Decimal>,
+    /// Number of non-competitive orders
+    pub non_competitive_orders: u32,
+    /// Number of bonds ordered non-competitively
+    pub non_competitive_quantity: u64,
+    /// Number of bonds of the sold ones allotted to non-competitive orders
+    pub non_competitive_sold: u32,
+    /// Clearing price of a single bond paid by non-competitive orders, excluding accrued interest
+    pub non_competitive_price: Option<Decimal>,
+}
+
+impl AuctionResult {
+    /// Auction result stored in the metadata of the bond, if its auction has closed
+    pub fn from_bond_metadata(metadata: &Metadata) -> Option<Self> {
+        let Value::LimitedMetadata(result) = metadata.get(AUCTION_RESULT_KEY)? else {
+            return None;
+        };
+
+        let Value::String(pricing) = result.get("pricing")? else {
+            return None;
+        };
+        let Value::String(basis) = result.get("basis")? else {
+            return None;
+        };
+        let decimal = |key: &str| -> Option<Decimal> {
+            let amount: Fixed = result.get(key)?.to_owned().try_into().ok()?;
+            Decimal::from_fixed(amount)
+        };
+
+        Some(Self {
+            close_time_ms: result.get("close_time_ms")?.to_owned().try_into().ok()?,
+            pricing: pricing.parse().ok()?,
+            basis: basis.parse().ok()?,
+            offered: result.get("offered")?.to_owned().try_into().ok()?,
+            bids: result.get("bids")?.to_owned().try_into().ok()?,
+            bid_quantity: result.get("bid_quantity")?.to_owned().try_into().ok()?,
+            sold: result.get("sold")?.to_owned().try_into().ok()?,
+            stop_out: decimal("stop_out"),
+            stop_out_price: decimal("stop_out_price"),
+            average_price: decimal("average_price"),
+            non_competitive_orders: result
+                .get("non_competitive_orders")?
+                .to_owned()
+                .try_into()
+                .ok()?,
+            non_competitive_quantity: result
+                .get("non_competitive_quantity")?
+                .to_owned()
+                .try_into()
+                .ok()?,
+            non_competitive_sold: result
+                .get("non_competitive_sold")?
+                .to_owned()
+                .try_into()
+                .ok()?,
+            non_competitive_price: decimal("non_competitive_price"),
+        })
+    }
+}
+
+impl From<AuctionResult> for Value {
+    fn from(result: AuctionResult) -> Self {
+        let mut metadata = Metadata::new();
+
+        insert_entry(&mut metadata, "close_time_ms", result.close_time_ms.into());
+        insert_entry(
+            &mut metadata,
+            "pricing",
+            Value::String(result.pricing.as_str().to_owned()),
+        );
+        insert_entry(
+            &mut metadata,
+            "basis",
+            Value::String(result.basis.as_str().to_owned()),
+        );
+        insert_entry(&mut metadata, "offered", result.offered.into());
+        insert_entry(&mut metadata, "bids", result.bids.into());
+        insert_entry(&mut metadata, "bid_quantity", result.bid_quantity.into());
+        insert_entry(&mut metadata, "sold", result.sold.into());
+        if let Some(stop_out) = result.stop_out {
+            insert_entry(&mut metadata, "stop_out", fixed(stop_out));
+        }
+        if let Some(stop_out_price) = result.stop_out_price {
+            insert_entry(&mut metadata, "stop_out_price", fixed(stop_out_price));
+        }
+        if let Some(average_price) = result.average_price {
+            insert_entry(&mut metadata, "average_price", fixed(average_price));
+        }
+        insert_entry(
+            &mut metadata,
+            "non_competitive_orders",
+            result.non_competitive_orders.into(),
+        );
+        insert_entry(
+            &mut metadata,
+            "non_competitive_quantity",
+            result.non_competitive_quantity.into(),
+        );
+        insert_entry(
+            &mut metadata,
+            "non_competitive_sold",
+            result.non_competitive_sold.into(),
+        );
+        if let Some(non_competitive_price) = result.non_competitive_price {
+            insert_entry(
+                &mut metadata,
+                "non_competitive_price",
+                fixed(non_competitive_price),
+            );
+        }
+
+        Value::LimitedMetadata(metadata)
+    }
+}
+
+fn fixed(amount: Decimal) -> Value {
+    amount
+        .to_fixed()
+        .expect("INTERNAL BUG: Auction amount out of `Fixed` range")
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        amortization::AmortizationSchedule,
+        bond_type::BondType,
+        day_count::DayCount,
+        money::RoundingMode,
+        put::PutSchedule,
+        subscription::{SubscriptionPeriod, UnsoldBonds},
+    };
+
+    fn decimal(amount: &str) -> Decimal {
+        amount.parse().expect("Valid decimal")
+    }
+
+    fn auction(pricing: AuctionPricing, reserve: Option<Decimal>) -> Auction {
+        Auction {
+            pricing,
+            basis: BidBasis::Price,
+            lot_size: 10,
+            rounding: AllotmentRounding::LargestRemainder,
+            reserve,
+            non_competitive: Some(NonCompetitiveTranche {
+                investor_cap: 50,
+                total_cap: 50,
+            }),
+        }
+    }
+
+    fn terms(auction: Auction) -> BondTerms {
+        BondTerms {
+            bond_type: BondType::ZeroCoupon,
+            currency: "usd#palau".parse().expect("Valid asset definition id"),
+            quantity: 100,
+            nominal_value: decimal("100"),
+            coupon_rate: Decimal::ZERO,
+            floating_rate: None,
+            inflation_index: None,
+            fixed_fee: Decimal::ZERO,
+            fee_recipient_account_id: "issuer@palau".parse().expect("Valid account id"),
+            registration_time_ms: 0,
+            maturation_date_ms: 365 * 86_400 * 1000,
+            payment_frequency_seconds: 0,
+            day_count: DayCount::Act365F,
+            coupon_schedule: None,
+            currency_precision: 2,
+            rounding: RoundingMode::HalfEven,
+            amortization: AmortizationSchedule {
+                repayments: Vec::new(),
+            },
+            issue_price: None,
+            price_schedule: Vec::new(),
+            call_schedule: Vec::new(),
+            call_notice_period_seconds: 0,
+            put_schedule: PutSchedule {
+                windows: Vec::new(),
+                notice_period_seconds: 0,
+                penalty: None,
+            },
+            subscription: Some(SubscriptionPeriod {
+                start_ms: 0,
+                end_ms: 1000,
+                unsold_bonds: UnsoldBonds::Retain,
+            }),
+            allotment: None,
+            auction: Some(auction),
+        }
+    }
+
+    fn competitive_bid(terms: &BondTerms, quantity: u32, bid: &str) -> AuctionBid {
+        AuctionBid {
+            quantity,
+            bid: Some(decimal(bid)),
+            clean_price: terms
+                .bid_price(quantity, decimal(bid))
+                .expect("Bid price fits"),
+            accrued_interest: Decimal::ZERO,
+            fee: Decimal::ZERO,
+            bid_time_ms: 0,
+        }
+    }
+
+    fn non_competitive_order(terms: &BondTerms, quantity: u32) -> AuctionBid {
+        AuctionBid {
+            bid: None,
+            ..competitive_bid(terms, quantity, "100")
+        }
+    }
+
+    #[test]
+    fn bids_are_filled_from_the_best_and_pro_rata_at_the_stop_out() {
+        let auction = auction(AuctionPricing::Uniform, Some(decimal("98")));
+        let bids = [
+            (decimal("99"), 40),
+            (decimal("100"), 30),
+            (decimal("97"), 50),
+            (decimal("99"), 20),
+        ];
+
+        // 20 bonds left at 99 are split 4:2, the lot left over to the larger remainder
+        let allotted = auction.allot(&bids, 50);
+        assert_eq!(allotted, vec![10, 30, 0, 10]);
+        assert_eq!(auction.stop_out(&bids, &allotted), Some(decimal("99")));
+    }
+
+    #[test]
+    fn lowest_yields_win_yield_bids() {
+        let auction = Auction {
+            basis: BidBasis::Yield,
+            reserve: Some(decimal("0.06")),
+            ..auction(AuctionPricing::Uniform, None)
+        };
+        let bids = [
+            (decimal("0.05"), 50),
+            (decimal("0.04"), 30),
+            (decimal("0.07"), 50),
+        ];
+
+        let allotted = auction.allot(&bids, 60);
+        assert_eq!(allotted, vec![30, 30, 0]);
+        assert_eq!(auction.stop_out(&bids, &allotted), Some(decimal("0.05")));
+    }
+
+    #[test]
+    fn uniform_pricing_charges_every_bid_the_stop_out_price() {
+        let auction = auction(AuctionPricing::Uniform, None);
+        let terms = terms(auction);
+        let competitive = [
+            competitive_bid(&terms, 30, "101"),
+            competitive_bid(&terms, 40, "100"),
+            competitive_bid(&terms, 60, "99.5"),
+        ];
+
+        let clearing = auction
+            .clear(&terms, &competitive, &[], 100, None)
+            .expect("No overflow");
+
+        assert_eq!(clearing.allotted, vec![30, 40, 30]);
+        assert_eq!(clearing.stop_out, Some(decimal("99.5")));
+        assert_eq!(
+            clearing.clean_prices,
+            vec![decimal("2985"), decimal("3980"), decimal("2985")]
+        );
+    }
+
+    #[test]
+    fn discriminatory_pricing_charges_every_bid_its_own_price() {
+        let auction = auction(AuctionPricing::Discriminatory, None);
+        let terms = terms(auction);
+        let competitive = [
+            competitive_bid(&terms, 30, "99.8"),
+            competitive_bid(&terms, 40, "99.5"),
+            competitive_bid(&terms, 60, "99"),
+        ];
+        let non_competitive = [non_competitive_order(&terms, 20)];
+
+        let clearing = auction
+            .clear(&terms, &competitive, &non_competitive, 100, None)
+            .expect("No overflow");
+
+        assert_eq!(clearing.allotted, vec![30, 40, 10]);
+        assert_eq!(clearing.stop_out, Some(decimal("99")));
+        // The partially filled bid pays its share of the price locked for the bonds bid for
+        assert_eq!(
+            clearing.clean_prices,
+            vec![decimal("2994"), decimal("3980"), decimal("990")]
+        );
+        // Non-competitive orders pay the average price of the 80 bonds sold competitively
+        assert_eq!(clearing.non_competitive_allotted, vec![20]);
+        assert_eq!(clearing.non_competitive_price, Some(decimal("99.55")));
+    }
+
+    #[test]
+    fn non_competitive_orders_are_filled_in_full_up_to_the_total_cap() {
+        let auction = auction(AuctionPricing::Uniform, None);
+
+        assert_eq!(auction.allot_non_competitive(&[20, 30], 100), vec![20, 30]);
+        // 15, 25 and 10 bonds pro-rata, the lot left over goes to the earlier of the tie
+        assert_eq!(
+            auction.allot_non_competitive(&[30, 50, 20], 100),
+            vec![20, 20, 10]
+        );
+        assert_eq!(auction.allot_non_competitive(&[50], 30), vec![30]);
+    }
+
+    #[test]
+    fn clearing_at_par_fills_the_tranche_first() {
+        let auction = auction(AuctionPricing::Uniform, None);
+        let terms = terms(auction);
+        let competitive = [
+            competitive_bid(&terms, 40, "100"),
+            competitive_bid(&terms, 40, "99"),
+        ];
+        let non_competitive = [non_competitive_order(&terms, 50)];
+
+        let clearing = auction
+            .clear(&terms, &competitive, &non_competitive, 100, None)
+            .expect("No overflow");
+
+        assert_eq!(clearing.non_competitive_allotted, vec![50]);
+        assert_eq!(clearing.allotted, vec![40, 10]);
+        assert_eq!(clearing.stop_out, Some(decimal("99")));
+        assert_eq!(clearing.non_competitive_price, Some(decimal("99")));
+    }
+
+    #[test]
+    fn lots_freed_above_par_go_to_the_competitive_bids() {
+        let auction = auction(AuctionPricing::Uniform, None);
+        let terms = terms(auction);
+        let competitive = [
+            competitive_bid(&terms, 60, "102"),
+            competitive_bid(&terms, 60, "101"),
+        ];
+        let non_competitive = [non_competitive_order(&terms, 50)];
+
+        let clearing = auction
+            .clear(&terms, &competitive, &non_competitive, 100, None)
+            .expect("No overflow");
+
+        // 50 bonds at 102 cost more than the 5000 locked at par, 40 of them don't
+        assert_eq!(clearing.non_competitive_allotted, vec![40]);
+        assert_eq!(clearing.allotted, vec![60, 0]);
+        assert_eq!(clearing.clean_prices, vec![decimal("6120"), Decimal::ZERO]);
+        assert_eq!(clearing.stop_out, Some(decimal("102")));
+        assert_eq!(clearing.non_competitive_price, Some(decimal("102")));
+    }
+
+    #[test]
+    fn non_competitive_orders_are_not_filled_without_a_competitive_fill() {
+        let auction = auction(AuctionPricing::Uniform, Some(decimal("98")));
+        let terms = terms(auction);
+        let competitive = [competitive_bid(&terms, 50, "97")];
+        let non_competitive = [non_competitive_order(&terms, 50)];
+
+        let clearing = auction
+            .clear(&terms, &competitive, &non_competitive, 100, None)
+            .expect("No overflow");
+
+        assert_eq!(clearing.non_competitive_allotted, vec![0]);
+        assert_eq!(clearing.allotted, vec![0]);
+        assert_eq!(clearing.stop_out, None);
+        assert_eq!(clearing.non_competitive_price, None);
+    }
+
+    #[test]
+    fn uniform_prices_of_inflation_linked_bonds_are_index_adjusted() {
+        let auction = auction(AuctionPricing::Uniform, None);
+        let terms = terms(auction);
+        let index_ratio = IndexRatio {
+            index_value: decimal("110"),
+            ratio: decimal("1.1"),
+        };
+        let index_adjusted = |bid: AuctionBid| AuctionBid {
+            clean_price: terms
+                .index_adjusted(bid.clean_price, Some(index_ratio))
+                .expect("No overflow"),
+            ..bid
+        };
+        let competitive = [
+            index_adjusted(competitive_bid(&terms, 60, "100")),
+            index_adjusted(competitive_bid(&terms, 60, "99")),
+        ];
+        let non_competitive = [index_adjusted(non_competitive_order(&terms, 50))];
+
+        let clearing = auction
+            .clear(
+                &terms,
+                &competitive,
+                &non_competitive,
+                100,
+                Some(index_ratio),
+            )
+            .expect("No overflow");
+
+        // 5500 locked at par and the index ratio of the bid pays for 50 bonds at 110
+        assert_eq!(clearing.non_competitive_allotted, vec![50]);
+        assert_eq!(clearing.allotted, vec![50, 0]);
+        assert_eq!(clearing.clean_prices, vec![decimal("5500"), Decimal::ZERO]);
+        assert_eq!(clearing.stop_out, Some(decimal("100")));
+        assert_eq!(clearing.non_competitive_price, Some(decimal("110")));
+    }
+
+    #[test]
+    fn bids_not_paying_the_clearing_price_are_left_out() {
+        let auction = auction(AuctionPricing::Uniform, None);
+        let terms = terms(auction);
+        let index_ratio = IndexRatio {
+            index_value: decimal("110"),
+            ratio: decimal("1.1"),
+        };
+        let locked_bid = competitive_bid(&terms, 60, "99");
+        let competitive = [
+            // Locked before the index rose
+            competitive_bid(&terms, 60, "100"),
+            AuctionBid {
+                clean_price: terms
+                    .index_adjusted(locked_bid.clean_price, Some(index_ratio))
+                    .expect("No overflow"),
+                ..locked_bid
+            },
+        ];
+
+        let clearing = auction
+            .clear(&terms, &competitive, &[], 100, Some(index_ratio))
+            .expect("No overflow");
+
+        // 6000 locked don't pay for 60 bonds at 108.9, the other bid gets them all
+        assert_eq!(clearing.allotted, vec![0, 60]);
+        assert_eq!(clearing.clean_prices, vec![Decimal::ZERO, decimal("6534")]);
+        assert_eq!(clearing.stop_out, Some(decimal("99")));
+    }
+}
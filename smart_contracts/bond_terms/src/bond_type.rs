@@ -0,0 +1,75 @@
+//! Kinds of bonds differing in how interest is paid
+
+use core::{fmt, str::FromStr};
+
+/// Kind of a bond
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BondType {
+    /// Pays coupons at a fixed `coupon_rate`
+    FixedRate,
+    /// Pays coupons at a reference rate plus a spread, fixed at the start of every period
+    FloatingRate,
+    /// Pays no coupons, it's sold at a discount and repays par at maturity
+    ZeroCoupon,
+    /// Pays coupons at a fixed `coupon_rate` on the principal scaled by an inflation index
+    InflationLinked,
+}
+
+impl BondType {
+    /// Name under which the type is stored, e.g. `zero_coupon`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::FixedRate => "fixed_rate",
+            Self::FloatingRate => "floating_rate",
+            Self::ZeroCoupon => "zero_coupon",
+            Self::InflationLinked => "inflation_linked",
+        }
+    }
+
+    /// Whether bonds of this type pay coupons
+    pub const fn pays_coupons(self) -> bool {
+        !matches!(self, Self::ZeroCoupon)
+    }
+
+    /// Whether bonds of this type pay coupons at the `coupon_rate` of their terms
+    pub const fn has_fixed_coupon_rate(self) -> bool {
+        matches!(self, Self::FixedRate | Self::InflationLinked)
+    }
+}
+
+impl fmt::Display for BondType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Bond type name that isn't recognized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownBondType;
+
+impl fmt::Display for UnknownBondType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            "expected one of `fixed_rate`, `floating_rate`, `zero_coupon`, `inflation_linked`",
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownBondType {}
+
+impl FromStr for BondType {
+    type Err = UnknownBondType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::FixedRate,
+            Self::FloatingRate,
+            Self::ZeroCoupon,
+            Self::InflationLinked,
+        ]
+        .into_iter()
+        .find(|bond_type| bond_type.as_str() == s)
+        .ok_or(UnknownBondType)
+    }
+}
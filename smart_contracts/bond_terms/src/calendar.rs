@@ -0,0 +1,591 @@
+//! Calendar dates, business days and coupon schedules
+//!
+//! Coupon schedules are generated once, when the bond is specified, and stored in the
+//! bond terms as explicit dates so that later changes to a holiday calendar don't move
+//! the coupons of bonds already issued.
+
+use alloc::{collections::BTreeSet, format, vec::Vec};
+use core::{fmt, str::FromStr};
+
+use iroha_data_model::prelude::*;
+
+const MS_PER_DAY: u64 = 86_400_000;
+
+/// Metadata key of the domain under which the holiday calendar with the given name is stored
+pub fn holiday_calendar_key(name: &str) -> Name {
+    format!("holiday_calendar_{name}")
+        .parse()
+        .expect("INTERNAL BUG: Invalid holiday calendar key")
+}
+
+/// Calendar date in the proleptic Gregorian calendar (UTC)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Date {
+    pub(crate) year: i64,
+    pub(crate) month: i64,
+    pub(crate) day: i64,
+}
+
+impl Date {
+    pub(crate) fn from_unix_ms(ms: u64) -> Self {
+        Self::from_days((ms / MS_PER_DAY) as i64)
+    }
+
+    /// Midnight of the date (in milliseconds since UNIX epoch)
+    pub(crate) fn to_unix_ms(self) -> u64 {
+        self.days() as u64 * MS_PER_DAY
+    }
+
+    fn from_days(days: i64) -> Self {
+        // Howard Hinnant's `civil_from_days`
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self { year, month, day }
+    }
+
+    /// Days since UNIX epoch
+    pub(crate) fn days(self) -> i64 {
+        // Howard Hinnant's `days_from_civil`
+        let year = if self.month <= 2 {
+            self.year - 1
+        } else {
+            self.year
+        };
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let mp = (self.month + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + self.day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+        era * 146_097 + doe - 719_468
+    }
+
+    fn is_leap_year(year: i64) -> bool {
+        year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    }
+
+    fn days_in_month(year: i64, month: i64) -> i64 {
+        match month {
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    pub(crate) fn is_last_day_of_month(self) -> bool {
+        self.day == Self::days_in_month(self.year, self.month)
+    }
+
+    pub(crate) fn is_last_day_of_february(self) -> bool {
+        self.month == 2 && self.is_last_day_of_month()
+    }
+
+    /// Same day of the month the given number of months later (or earlier, if negative)
+    ///
+    /// The day is clamped to the length of the target month. With `end_of_month`, the
+    /// last day of a month is moved to the last day of the target month.
+    pub(crate) fn add_months(self, months: i64, end_of_month: bool) -> Self {
+        let month_index = self.year * 12 + (self.month - 1) + months;
+        let year = month_index.div_euclid(12);
+        let month = month_index.rem_euclid(12) + 1;
+        let days_in_month = Self::days_in_month(year, month);
+
+        let day = if end_of_month && self.is_last_day_of_month() {
+            days_in_month
+        } else {
+            self.day.min(days_in_month)
+        };
+
+        Self { year, month, day }
+    }
+
+    fn next_day(self) -> Self {
+        Self::from_days(self.days() + 1)
+    }
+
+    fn previous_day(self) -> Self {
+        Self::from_days(self.days() - 1)
+    }
+
+    fn is_weekend(self) -> bool {
+        // 1970-01-01 was a Thursday, 0 is Sunday
+        matches!((self.days() + 4).rem_euclid(7), 0 | 6)
+    }
+}
+
+/// Holidays on which no payments are made, on top of weekends
+///
+/// Stored on chain in the metadata of a domain, see [`holiday_calendar_key`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HolidayCalendar {
+    holidays: BTreeSet<i64>,
+}
+
+impl HolidayCalendar {
+    /// Calendar with the holidays falling on the given days (in milliseconds since UNIX epoch)
+    pub fn new(holidays_ms: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            holidays: holidays_ms
+                .into_iter()
+                .map(|holiday_ms| Date::from_unix_ms(holiday_ms).days())
+                .collect(),
+        }
+    }
+
+    fn is_business_day(&self, date: Date) -> bool {
+        !date.is_weekend() && !self.holidays.contains(&date.days())
+    }
+
+    fn following(&self, mut date: Date) -> Date {
+        while !self.is_business_day(date) {
+            date = date.next_day();
+        }
+
+        date
+    }
+
+    fn preceding(&self, mut date: Date) -> Date {
+        while !self.is_business_day(date) {
+            date = date.previous_day();
+        }
+
+        date
+    }
+}
+
+impl From<&HolidayCalendar> for Value {
+    fn from(calendar: &HolidayCalendar) -> Self {
+        Value::Vec(
+            calendar
+                .holidays
+                .iter()
+                .map(|&days| Date::from_days(days).to_unix_ms().into())
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<&Value> for HolidayCalendar {
+    type Error = Value;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let Value::Vec(holidays) = value else {
+            return Err(value.clone());
+        };
+
+        holidays
+            .iter()
+            .map(|holiday| u64::try_from(holiday.clone()).map_err(|_| value.clone()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::new)
+    }
+}
+
+/// Rule by which a coupon date falling on a non-business day is moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusinessDayConvention {
+    /// Paid on the date even if it isn't a business day
+    Unadjusted,
+    /// Paid on the next business day
+    Following,
+    /// Paid on the next business day, unless it's in the next month,
+    /// in which case it's paid on the previous business day
+    ModifiedFollowing,
+}
+
+impl BusinessDayConvention {
+    /// Name of the convention, e.g. `modified_following`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Unadjusted => "unadjusted",
+            Self::Following => "following",
+            Self::ModifiedFollowing => "modified_following",
+        }
+    }
+
+    fn adjust(self, date: Date, calendar: &HolidayCalendar) -> Date {
+        match self {
+            Self::Unadjusted => date,
+            Self::Following => calendar.following(date),
+            Self::ModifiedFollowing => {
+                let following = calendar.following(date);
+                if following.month == date.month {
+                    following
+                } else {
+                    calendar.preceding(date)
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for BusinessDayConvention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Business day convention name that isn't recognized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownBusinessDayConvention;
+
+impl fmt::Display for UnknownBusinessDayConvention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of `unadjusted`, `following`, `modified_following`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownBusinessDayConvention {}
+
+impl FromStr for BusinessDayConvention {
+    type Err = UnknownBusinessDayConvention;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Unadjusted, Self::Following, Self::ModifiedFollowing]
+            .into_iter()
+            .find(|convention| convention.as_str() == s)
+            .ok_or(UnknownBusinessDayConvention)
+    }
+}
+
+/// Rule generating a coupon schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleRule {
+    /// Number of months between two coupons, e.g. `6` for semi-annual coupons
+    pub frequency_months: u32,
+    /// Date from which coupon dates are rolled, forwards and backwards
+    pub anchor_date_ms: u64,
+    /// Whether an anchor on the last day of a month keeps coupons on the last day of a month
+    pub end_of_month: bool,
+    /// Adjustment of coupon dates falling on non-business days
+    pub business_day_convention: BusinessDayConvention,
+}
+
+impl ScheduleRule {
+    /// Generate the coupon dates between registration and maturation
+    ///
+    /// The last coupon is due at maturation and is paid together with the principal, on the
+    /// maturation date adjusted like the other payment dates. A coupon that would be paid by
+    /// registration is merged into the next period, and one that would be paid with the final
+    /// coupon into the final period.
+    pub fn generate(
+        &self,
+        registration_time_ms: u64,
+        maturation_date_ms: u64,
+        calendar: &HolidayCalendar,
+    ) -> CouponSchedule {
+        let frequency_months = i64::from(self.frequency_months.max(1));
+        let anchor = Date::from_unix_ms(self.anchor_date_ms);
+        let registration = Date::from_unix_ms(registration_time_ms);
+
+        // Roll back from an anchor after registration to the first coupon
+        let mut k = if anchor > registration {
+            let months =
+                (anchor.year - registration.year) * 12 + (anchor.month - registration.month);
+            -(months / frequency_months + 1)
+        } else {
+            0
+        };
+
+        let maturation = Date::from_unix_ms(maturation_date_ms);
+        let maturity_payment_date_ms = self
+            .business_day_convention
+            .adjust(maturation, calendar)
+            .to_unix_ms();
+
+        let mut coupon_dates_ms = Vec::new();
+        let mut payment_dates_ms = Vec::new();
+        loop {
+            let date = anchor.add_months(k * frequency_months, self.end_of_month);
+            k += 1;
+
+            let date_ms = date.to_unix_ms();
+            if date_ms >= maturation_date_ms {
+                break;
+            }
+            if date_ms <= registration_time_ms {
+                continue;
+            }
+            let payment_date_ms = self
+                .business_day_convention
+                .adjust(date, calendar)
+                .to_unix_ms();
+            if payment_date_ms <= registration_time_ms {
+                continue;
+            }
+            if payment_date_ms >= maturity_payment_date_ms {
+                break;
+            }
+
+            coupon_dates_ms.push(date_ms);
+            payment_dates_ms.push(payment_date_ms);
+        }
+
+        coupon_dates_ms.push(maturation_date_ms);
+        payment_dates_ms.push(maturity_payment_date_ms);
+
+        CouponSchedule {
+            frequency_months: self.frequency_months,
+            coupon_dates_ms,
+            payment_dates_ms,
+        }
+    }
+}
+
+/// Explicit dates on which the coupons of a bond accrue and are paid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CouponSchedule {
+    /// Number of months between two regular coupons
+    pub frequency_months: u32,
+    /// Ends of the accrual periods, the first period starts at registration
+    /// and the last one ends at maturation
+    pub coupon_dates_ms: Vec<u64>,
+    /// Dates on which the coupons are paid, i.e. coupon dates adjusted to business days,
+    /// the last one is the maturity payment date
+    pub payment_dates_ms: Vec<u64>,
+}
+
+impl CouponSchedule {
+    /// Whether the schedule describes coupons of a bond between the given times
+    ///
+    /// Coupons are paid after registration, the final one possibly after maturation
+    /// when that isn't a business day.
+    pub fn is_valid(&self, registration_time_ms: u64, maturation_date_ms: u64) -> bool {
+        let is_increasing = |dates: &[u64]| dates.windows(2).all(|pair| pair[0] < pair[1]);
+
+        self.frequency_months > 0
+            && self.coupon_dates_ms.len() == self.payment_dates_ms.len()
+            && self.coupon_dates_ms.last() == Some(&maturation_date_ms)
+            && self
+                .coupon_dates_ms
+                .first()
+                .is_some_and(|&first| first > registration_time_ms)
+            && self
+                .payment_dates_ms
+                .first()
+                .is_some_and(|&first| first > registration_time_ms)
+            && is_increasing(&self.coupon_dates_ms)
+            && is_increasing(&self.payment_dates_ms)
+    }
+
+    /// Notional regular coupon period ending or starting at the given date
+    pub(crate) fn regular_period(&self, date_ms: u64, backwards: bool) -> (u64, u64) {
+        let date = Date::from_unix_ms(date_ms);
+        let months = i64::from(self.frequency_months);
+
+        if backwards {
+            let start = date.add_months(-months, date.is_last_day_of_month());
+            (start.to_unix_ms(), date_ms)
+        } else {
+            let end = date.add_months(months, date.is_last_day_of_month());
+            (date_ms, end.to_unix_ms())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn date(year: i64, month: i64, day: i64) -> Date {
+        Date { year, month, day }
+    }
+
+    fn date_ms(year: i64, month: i64, day: i64) -> u64 {
+        date(year, month, day).to_unix_ms()
+    }
+
+    #[test]
+    fn business_days_are_not_moved() {
+        let calendar = HolidayCalendar::default();
+
+        for convention in [
+            BusinessDayConvention::Following,
+            BusinessDayConvention::ModifiedFollowing,
+        ] {
+            assert_eq!(
+                convention.adjust(date(2024, 8, 30), &calendar),
+                date(2024, 8, 30)
+            );
+        }
+    }
+
+    #[test]
+    fn following_moves_to_the_next_business_day() {
+        let calendar = HolidayCalendar::new([date_ms(2024, 4, 1)]);
+        let following = BusinessDayConvention::Following;
+
+        // Saturday in the middle of the month
+        assert_eq!(
+            following.adjust(date(2024, 6, 15), &calendar),
+            date(2024, 6, 17)
+        );
+        // Saturday at the end of the month, into the next one
+        assert_eq!(
+            following.adjust(date(2024, 8, 31), &calendar),
+            date(2024, 9, 2)
+        );
+        // Saturday followed by a Sunday and a holiday
+        assert_eq!(
+            following.adjust(date(2024, 3, 30), &calendar),
+            date(2024, 4, 2)
+        );
+    }
+
+    #[test]
+    fn modified_following_stays_within_the_month() {
+        let calendar = HolidayCalendar::new([date_ms(2024, 3, 29)]);
+        let modified_following = BusinessDayConvention::ModifiedFollowing;
+
+        assert_eq!(
+            modified_following.adjust(date(2024, 6, 15), &calendar),
+            date(2024, 6, 17)
+        );
+        assert_eq!(
+            modified_following.adjust(date(2024, 8, 31), &calendar),
+            date(2024, 8, 30)
+        );
+        // The Friday before the weekend is a holiday too
+        assert_eq!(
+            modified_following.adjust(date(2024, 3, 30), &calendar),
+            date(2024, 3, 28)
+        );
+    }
+
+    #[test]
+    fn end_of_month_schedule_is_adjusted_but_accrues_on_the_coupon_dates() {
+        let rule = ScheduleRule {
+            frequency_months: 6,
+            anchor_date_ms: date_ms(2024, 8, 31),
+            end_of_month: true,
+            business_day_convention: BusinessDayConvention::ModifiedFollowing,
+        };
+
+        let schedule = rule.generate(
+            date_ms(2024, 2, 29),
+            date_ms(2025, 8, 31),
+            &HolidayCalendar::default(),
+        );
+
+        assert_eq!(
+            schedule.coupon_dates_ms,
+            vec![
+                date_ms(2024, 8, 31),
+                date_ms(2025, 2, 28),
+                date_ms(2025, 8, 31)
+            ]
+        );
+        assert_eq!(
+            schedule.payment_dates_ms,
+            vec![
+                date_ms(2024, 8, 30),
+                date_ms(2025, 2, 28),
+                date_ms(2025, 8, 29)
+            ]
+        );
+    }
+
+    #[test]
+    fn maturity_on_a_weekend_is_paid_on_the_next_business_day() {
+        let rule = ScheduleRule {
+            frequency_months: 6,
+            anchor_date_ms: date_ms(2024, 3, 15),
+            end_of_month: false,
+            business_day_convention: BusinessDayConvention::Following,
+        };
+        let registration_time_ms = date_ms(2024, 3, 1);
+        // Saturday
+        let maturation_date_ms = date_ms(2025, 3, 15);
+
+        let schedule = rule.generate(
+            registration_time_ms,
+            maturation_date_ms,
+            &HolidayCalendar::default(),
+        );
+
+        assert_eq!(
+            schedule.coupon_dates_ms,
+            vec![
+                date_ms(2024, 3, 15),
+                date_ms(2024, 9, 15),
+                date_ms(2025, 3, 15)
+            ]
+        );
+        assert_eq!(
+            schedule.payment_dates_ms,
+            vec![
+                date_ms(2024, 3, 15),
+                date_ms(2024, 9, 16),
+                date_ms(2025, 3, 17)
+            ]
+        );
+        assert!(schedule.is_valid(registration_time_ms, maturation_date_ms));
+    }
+
+    #[test]
+    fn coupon_paid_with_the_final_coupon_is_merged_into_the_final_period() {
+        let rule = ScheduleRule {
+            frequency_months: 12,
+            anchor_date_ms: date_ms(2024, 3, 15),
+            end_of_month: false,
+            business_day_convention: BusinessDayConvention::Following,
+        };
+
+        // The coupon of Saturday, 15 March 2025 would be paid at maturation on Monday
+        let schedule = rule.generate(
+            date_ms(2024, 3, 1),
+            date_ms(2025, 3, 17),
+            &HolidayCalendar::default(),
+        );
+
+        assert_eq!(
+            schedule.coupon_dates_ms,
+            vec![date_ms(2024, 3, 15), date_ms(2025, 3, 17)]
+        );
+        assert_eq!(
+            schedule.payment_dates_ms,
+            vec![date_ms(2024, 3, 15), date_ms(2025, 3, 17)]
+        );
+    }
+
+    #[test]
+    fn coupon_paid_by_registration_is_merged_into_the_next_period() {
+        let rule = ScheduleRule {
+            frequency_months: 6,
+            anchor_date_ms: date_ms(2024, 8, 31),
+            end_of_month: true,
+            business_day_convention: BusinessDayConvention::ModifiedFollowing,
+        };
+        // Midday of Friday, 30 August 2024, the coupon of the Saturday after would be paid before
+        let registration_time_ms = date_ms(2024, 8, 30) + MS_PER_DAY / 2;
+        let maturation_date_ms = date_ms(2025, 8, 31);
+
+        let schedule = rule.generate(
+            registration_time_ms,
+            maturation_date_ms,
+            &HolidayCalendar::default(),
+        );
+
+        assert_eq!(
+            schedule.coupon_dates_ms,
+            vec![date_ms(2025, 2, 28), date_ms(2025, 8, 31)]
+        );
+        assert_eq!(
+            schedule.payment_dates_ms,
+            vec![date_ms(2025, 2, 28), date_ms(2025, 8, 29)]
+        );
+        assert!(schedule.is_valid(registration_time_ms, maturation_date_ms));
+    }
+}
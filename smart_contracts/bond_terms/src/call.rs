@@ -0,0 +1,72 @@
+//! Early redemption of callable bonds by the issuer
+//!
+//! The call schedule of a bond gives the price at which it can be called from each date on.
+//! When the issuer calls the bond, a [`CallNotice`] is stored in the metadata of the bond
+//! under [`CALL_NOTICE_KEY`] and the bonds are redeemed once the notice period has passed.
+
+use alloc::{borrow::ToOwned as _, string::ToString as _};
+
+use iroha_data_model::prelude::*;
+
+use crate::{insert_entry, price::Price};
+
+/// Key of the bond metadata holding the notice of a call
+pub const CALL_NOTICE_KEY: &str = "call_notice";
+
+/// Issuer's notice that the bond will be redeemed early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallNotice {
+    /// Time at which the bond was called (in milliseconds since UNIX epoch)
+    pub notice_time_ms: u64,
+    /// Time at which the bonds are redeemed (in milliseconds since UNIX epoch)
+    pub redemption_date_ms: u64,
+    /// Price of a single bond at the redemption, excluding accrued interest
+    pub price: Price,
+}
+
+impl CallNotice {
+    /// Call notice stored in the metadata of the bond, if the bond was called
+    pub fn from_bond_metadata(metadata: &Metadata) -> Option<Self> {
+        let Value::LimitedMetadata(notice) = metadata.get(CALL_NOTICE_KEY)? else {
+            return None;
+        };
+
+        let Value::String(price) = notice.get("price")? else {
+            return None;
+        };
+
+        Some(Self {
+            notice_time_ms: notice.get("notice_time_ms")?.to_owned().try_into().ok()?,
+            redemption_date_ms: notice
+                .get("redemption_date_ms")?
+                .to_owned()
+                .try_into()
+                .ok()?,
+            price: price.parse().ok()?,
+        })
+    }
+}
+
+impl From<CallNotice> for Value {
+    fn from(notice: CallNotice) -> Self {
+        let mut metadata = Metadata::new();
+
+        insert_entry(
+            &mut metadata,
+            "notice_time_ms",
+            notice.notice_time_ms.into(),
+        );
+        insert_entry(
+            &mut metadata,
+            "redemption_date_ms",
+            notice.redemption_date_ms.into(),
+        );
+        insert_entry(
+            &mut metadata,
+            "price",
+            Value::String(notice.price.to_string()),
+        );
+
+        Value::LimitedMetadata(metadata)
+    }
+}
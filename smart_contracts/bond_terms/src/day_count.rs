@@ -0,0 +1,268 @@
+//! Day-count conventions used to accrue coupons over a period
+
+use core::{fmt, str::FromStr};
+
+use crate::calendar::Date;
+
+/// Seconds in a day
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Fraction of a year as a ratio of days, kept exact until it's applied to an amount
+///
+/// Under [`DayCount::Seconds365`] it's a ratio of seconds instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YearFraction {
+    pub days: i128,
+    pub basis: i128,
+}
+
+/// Convention for the fraction of a year between two dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayCount {
+    /// Actual days over the actual days of the coupon period times coupons per year
+    ActActIcma,
+    /// Actual days over 365
+    Act365F,
+    /// Actual days over 360
+    Act360,
+    /// 30-day months over 360, with the US (SIA) end-of-month rules
+    Thirty360Us,
+    /// 30-day months over 360, with the Eurobond end-of-month rule
+    ThirtyE360,
+    /// Actual seconds over the seconds of 365 days
+    ///
+    /// Coupons accrued this way before day-count conventions were introduced,
+    /// so it's the convention of bonds stored without one.
+    Seconds365,
+}
+
+impl DayCount {
+    /// Name under which the convention is stored, e.g. `ACT/ACT ICMA`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ActActIcma => "ACT/ACT ICMA",
+            Self::Act365F => "ACT/365F",
+            Self::Act360 => "ACT/360",
+            Self::Thirty360Us => "30/360 US",
+            Self::ThirtyE360 => "30E/360",
+            Self::Seconds365 => "SECONDS/365",
+        }
+    }
+
+    /// Whether the convention counts whole calendar days, i.e. accrues nothing within a day
+    pub const fn counts_days(self) -> bool {
+        !matches!(self, Self::Seconds365)
+    }
+
+    /// Fraction of a year accrued between `start_ms` and `end_ms`
+    ///
+    /// The reference period is the regular coupon period the accrual falls into,
+    /// it's only used by ACT/ACT ICMA together with the number of coupons per year.
+    /// Returns `None` on overflow.
+    pub fn year_fraction(
+        self,
+        start_ms: u64,
+        end_ms: u64,
+        (reference_start_ms, reference_end_ms): (u64, u64),
+        coupons_per_year: u64,
+    ) -> Option<YearFraction> {
+        let start = Date::from_unix_ms(start_ms);
+        let end = Date::from_unix_ms(end_ms);
+
+        let (days, basis) = match self {
+            Self::ActActIcma => {
+                let reference_days = Date::from_unix_ms(reference_end_ms).days()
+                    - Date::from_unix_ms(reference_start_ms).days();
+
+                (
+                    end.days() - start.days(),
+                    reference_days.checked_mul(coupons_per_year.max(1).try_into().ok()?)?,
+                )
+            }
+            Self::Act365F => (end.days() - start.days(), 365),
+            Self::Act360 => (end.days() - start.days(), 360),
+            Self::Thirty360Us => (thirty_360_us(start, end), 360),
+            Self::ThirtyE360 => (thirty_e_360(start, end), 360),
+            Self::Seconds365 => (
+                i64::try_from(end_ms.saturating_sub(start_ms) / 1000).ok()?,
+                i64::try_from(365 * SECONDS_PER_DAY).ok()?,
+            ),
+        };
+
+        if basis <= 0 {
+            return Some(YearFraction { days: 0, basis: 1 });
+        }
+
+        Some(YearFraction {
+            days: days.into(),
+            basis: basis.into(),
+        })
+    }
+}
+
+impl fmt::Display for DayCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Day-count convention name that isn't recognized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownDayCount;
+
+impl fmt::Display for UnknownDayCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of `ACT/ACT ICMA`, `ACT/365F`, `ACT/360`, `30/360 US`, `30E/360`, `SECONDS/365`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownDayCount {}
+
+impl FromStr for DayCount {
+    type Err = UnknownDayCount;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::ActActIcma,
+            Self::Act365F,
+            Self::Act360,
+            Self::Thirty360Us,
+            Self::ThirtyE360,
+            Self::Seconds365,
+        ]
+        .into_iter()
+        .find(|day_count| day_count.as_str() == s)
+        .ok_or(UnknownDayCount)
+    }
+}
+
+fn thirty_360_days(start: Date, end: Date, start_day: i64, end_day: i64) -> i64 {
+    360 * (end.year - start.year) + 30 * (end.month - start.month) + (end_day - start_day)
+}
+
+fn thirty_360_us(start: Date, end: Date) -> i64 {
+    let mut start_day = start.day;
+    let mut end_day = end.day;
+
+    if start.is_last_day_of_february() {
+        if end.is_last_day_of_february() {
+            end_day = 30;
+        }
+        start_day = 30;
+    }
+    if end_day == 31 && start_day >= 30 {
+        end_day = 30;
+    }
+    if start_day == 31 {
+        start_day = 30;
+    }
+
+    thirty_360_days(start, end, start_day, end_day)
+}
+
+fn thirty_e_360(start: Date, end: Date) -> i64 {
+    thirty_360_days(start, end, start.day.min(30), end.day.min(30))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date_ms(year: i64, month: i64, day: i64) -> u64 {
+        Date { year, month, day }.to_unix_ms()
+    }
+
+    fn year_fraction(day_count: DayCount, start_ms: u64, end_ms: u64) -> (i128, i128) {
+        let fraction = day_count
+            .year_fraction(start_ms, end_ms, (start_ms, end_ms), 1)
+            .expect("No overflow");
+
+        (fraction.days, fraction.basis)
+    }
+
+    #[test]
+    fn thirty_360_us_end_of_month_rules() {
+        let thirty_360_us = |start, end| year_fraction(DayCount::Thirty360Us, start, end);
+
+        assert_eq!(
+            thirty_360_us(date_ms(2024, 1, 15), date_ms(2024, 3, 31)),
+            (76, 360)
+        );
+        assert_eq!(
+            thirty_360_us(date_ms(2024, 1, 31), date_ms(2024, 2, 29)),
+            (29, 360)
+        );
+        // The last day of February counts as the 30th
+        assert_eq!(
+            thirty_360_us(date_ms(2024, 2, 29), date_ms(2024, 8, 31)),
+            (180, 360)
+        );
+        assert_eq!(
+            thirty_360_us(date_ms(2024, 2, 29), date_ms(2025, 2, 28)),
+            (360, 360)
+        );
+    }
+
+    #[test]
+    fn thirty_e_360_caps_days_at_the_30th() {
+        let thirty_e_360 = |start, end| year_fraction(DayCount::ThirtyE360, start, end);
+
+        assert_eq!(
+            thirty_e_360(date_ms(2024, 1, 15), date_ms(2024, 3, 31)),
+            (75, 360)
+        );
+        assert_eq!(
+            thirty_e_360(date_ms(2024, 2, 29), date_ms(2024, 8, 31)),
+            (181, 360)
+        );
+    }
+
+    #[test]
+    fn act_act_icma_accrues_over_the_reference_period() {
+        let reference_period = (date_ms(2024, 1, 1), date_ms(2024, 7, 1));
+        let act_act_icma = |start, end| {
+            let fraction = DayCount::ActActIcma
+                .year_fraction(start, end, reference_period, 2)
+                .expect("No overflow");
+            (fraction.days, fraction.basis)
+        };
+
+        // 182 days in the reference period, two coupons a year
+        assert_eq!(
+            act_act_icma(date_ms(2024, 1, 1), date_ms(2024, 7, 1)),
+            (182, 364)
+        );
+        assert_eq!(
+            act_act_icma(date_ms(2024, 1, 1), date_ms(2024, 4, 1)),
+            (91, 364)
+        );
+        // A short first coupon still accrues over the regular period it falls into
+        assert_eq!(
+            act_act_icma(date_ms(2024, 3, 1), date_ms(2024, 7, 1)),
+            (122, 364)
+        );
+    }
+
+    #[test]
+    fn actual_days_over_a_fixed_year() {
+        let (start, end) = (date_ms(2024, 1, 1), date_ms(2025, 1, 1));
+
+        assert_eq!(year_fraction(DayCount::Act365F, start, end), (366, 365));
+        assert_eq!(year_fraction(DayCount::Act360, start, end), (366, 360));
+    }
+
+    #[test]
+    fn only_seconds_365_accrues_within_a_day() {
+        let start = date_ms(2024, 1, 1);
+        let end = start + 12 * SECONDS_PER_DAY * 1000 / 24;
+
+        assert!(!DayCount::Seconds365.counts_days());
+        assert_eq!(
+            year_fraction(DayCount::Seconds365, start, end),
+            (43_200, 31_536_000)
+        );
+        assert!(DayCount::Act365F.counts_days());
+        assert_eq!(year_fraction(DayCount::Act365F, start, end), (0, 365));
+    }
+}
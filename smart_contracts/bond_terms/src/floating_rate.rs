@@ -0,0 +1,140 @@
+//! Coupons of floating-rate notes tracking a reference rate published on chain
+//!
+//! An oracle account holding the `CanPublishReferenceRate` permission publishes the current
+//! value of a reference rate into its own metadata under [`reference_rate_key`]. The rate
+//! of a coupon is fixed at the start of its period and kept in the metadata of the bond
+//! under [`RATE_FIXING_KEY`] until the coupon is paid.
+
+use alloc::{borrow::ToOwned as _, format};
+
+use iroha_data_model::prelude::*;
+
+use crate::{insert_entry, money::Decimal};
+
+/// Key of the bond metadata holding the rate fixed for the current coupon period
+pub const RATE_FIXING_KEY: &str = "rate_fixing";
+
+/// Prefix of the oracle account metadata keys holding the published reference rates
+pub const REFERENCE_RATE_KEY_PREFIX: &str = "reference_rate_";
+
+/// Key of the oracle account metadata under which the given reference rate is published
+pub fn reference_rate_key(reference_rate: &Name) -> Name {
+    format!("{REFERENCE_RATE_KEY_PREFIX}{reference_rate}")
+        .parse()
+        .expect("INTERNAL BUG: Unable to parse reference rate key")
+}
+
+/// Current value of a reference rate as published by the oracle
+///
+/// Returns `None` if the rate isn't published or not a [`Fixed`] within range.
+pub fn published_reference_rate(oracle: &Account, reference_rate: &Name) -> Option<Decimal> {
+    let rate: Fixed = oracle
+        .metadata()
+        .get(&reference_rate_key(reference_rate))?
+        .to_owned()
+        .try_into()
+        .ok()?;
+
+    Decimal::from_fixed(rate)
+}
+
+/// How the coupon rate of a floating-rate note follows its reference rate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FloatingRate {
+    /// Name of the reference rate, e.g. `SOFR`
+    pub reference_rate: Name,
+    /// Account publishing the reference rate
+    pub oracle: AccountId,
+    /// Margin added to the reference rate, e.g. `0.005` for 50 basis points
+    pub spread: Decimal,
+    /// Lowest coupon rate paid
+    pub floor: Option<Decimal>,
+    /// Highest coupon rate paid
+    pub cap: Option<Decimal>,
+}
+
+impl FloatingRate {
+    /// Yearly coupon rate for the given value of the reference rate
+    ///
+    /// Returns `None` on overflow.
+    pub fn coupon_rate(&self, reference_rate: Decimal) -> Option<Decimal> {
+        let mut rate = reference_rate.checked_add(self.spread)?;
+        if let Some(floor) = self.floor {
+            rate = rate.max(floor);
+        }
+        if let Some(cap) = self.cap {
+            rate = rate.min(cap);
+        }
+
+        Some(rate)
+    }
+
+    /// Fix the coupon rate of the period starting at the given time
+    ///
+    /// Returns `None` on overflow.
+    pub fn fix(&self, period_start_ms: u64, reference_rate: Decimal) -> Option<RateFixing> {
+        Some(RateFixing {
+            period_start_ms,
+            reference_rate,
+            coupon_rate: self.coupon_rate(reference_rate)?,
+        })
+    }
+}
+
+/// Rate fixed for a coupon period of a floating-rate note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateFixing {
+    /// Start of the period the rate is fixed for (in milliseconds since UNIX epoch)
+    pub period_start_ms: u64,
+    /// Value of the reference rate at the fixing
+    pub reference_rate: Decimal,
+    /// Coupon rate after the spread, floor and cap are applied
+    pub coupon_rate: Decimal,
+}
+
+impl RateFixing {
+    /// Rate fixing stored in the metadata of the bond, if any
+    pub fn from_bond_metadata(metadata: &Metadata) -> Option<Self> {
+        let Value::LimitedMetadata(fixing) = metadata.get(RATE_FIXING_KEY)? else {
+            return None;
+        };
+
+        let decimal = |key: &str| {
+            let value: Fixed = fixing.get(key)?.to_owned().try_into().ok()?;
+            Decimal::from_fixed(value)
+        };
+
+        Some(Self {
+            period_start_ms: fixing.get("period_start_ms")?.to_owned().try_into().ok()?,
+            reference_rate: decimal("reference_rate")?,
+            coupon_rate: decimal("coupon_rate")?,
+        })
+    }
+}
+
+impl From<RateFixing> for Value {
+    fn from(fixing: RateFixing) -> Self {
+        let mut metadata = Metadata::new();
+
+        let decimal = |value: Decimal| -> Value {
+            value
+                .to_fixed()
+                .expect("INTERNAL BUG: Rate out of `Fixed` range")
+                .into()
+        };
+
+        insert_entry(
+            &mut metadata,
+            "period_start_ms",
+            fixing.period_start_ms.into(),
+        );
+        insert_entry(
+            &mut metadata,
+            "reference_rate",
+            decimal(fixing.reference_rate),
+        );
+        insert_entry(&mut metadata, "coupon_rate", decimal(fixing.coupon_rate));
+
+        Value::LimitedMetadata(metadata)
+    }
+}
@@ -0,0 +1,56 @@
+//! Queries and instructions run on the host by the bond triggers
+//!
+//! Only available with the `trigger` feature, the client and the executor go without them.
+
+use alloc::{borrow::ToOwned as _, format};
+
+use iroha_trigger::{
+    data_model::query::{account::model::FindAccountById, block::model::FindAllBlockHeaders},
+    debug::DebugExpectExt as _,
+    prelude::*,
+};
+
+/// Timestamp of the latest committed block, the time at which the trigger runs
+pub fn block_time_ms() -> u64 {
+    FindAllBlockHeaders::new()
+        .execute()
+        .dbg_expect("INTERNAL BUG: Unable to query block headers")
+        .into_iter()
+        .next()
+        .dbg_expect("INTERNAL BUG: No block committed")
+        .dbg_expect("INTERNAL BUG: Unable to query block headers")
+        .timestamp_ms()
+}
+
+/// Increment the holder's payment record counter under the given key, e.g. `coupon_payment_idx`
+///
+/// Returns the new value, the index of the record about to be written.
+pub fn next_payment_idx(holder: &AccountId, idx_key: &str) -> u32 {
+    let payment_idx_key: Name = idx_key
+        .parse()
+        .dbg_expect("INTERNAL BUG: Unable to parse payment index key");
+
+    let current_idx = FindAccountById::new(holder.clone())
+        .execute()
+        .dbg_expect("INTERNAL BUG: Account not found")
+        .metadata()
+        .get(&payment_idx_key)
+        .map(|idx| {
+            idx.to_owned()
+                .try_into()
+                .dbg_expect(&format!("INTERNAL BUG: `{idx_key}` not of the `u32` type"))
+        })
+        .unwrap_or(0_u32);
+
+    let new_idx = current_idx + 1;
+
+    SetKeyValueExpr::new(
+        holder.clone(),
+        payment_idx_key,
+        Value::Numeric(new_idx.into()),
+    )
+    .execute()
+    .dbg_expect("Failed to set payment index to holder's metadata");
+
+    new_idx
+}
@@ -0,0 +1,95 @@
+//! Principal of inflation-linked bonds scaled by a price index published on chain
+//!
+//! An oracle account holding the `CanPublishInflationIndex` permission publishes the current
+//! value of a price index, e.g. `CPI`, into its own metadata under [`index_key`]. The principal
+//! is scaled by the ratio of the published value to the base value of the index at issuance.
+
+use alloc::{borrow::ToOwned as _, format};
+
+use iroha_data_model::prelude::*;
+
+use crate::money::{Decimal, RoundingMode};
+#[cfg(feature = "trigger")]
+use crate::BondTerms;
+
+/// Prefix of the oracle account metadata keys holding the published index values
+pub const INDEX_KEY_PREFIX: &str = "inflation_index_";
+
+/// Key of the oracle account metadata under which the given index is published
+pub fn index_key(index: &Name) -> Name {
+    format!("{INDEX_KEY_PREFIX}{index}")
+        .parse()
+        .expect("INTERNAL BUG: Unable to parse inflation index key")
+}
+
+/// Current value of a price index as published by the oracle
+///
+/// Returns `None` if the index isn't published or not a positive [`Fixed`] within range.
+pub fn published_index_value(oracle: &Account, index: &Name) -> Option<Decimal> {
+    let value: Fixed = oracle
+        .metadata()
+        .get(&index_key(index))?
+        .to_owned()
+        .try_into()
+        .ok()?;
+
+    Decimal::from_fixed(value).filter(|value| !value.is_negative() && !value.is_zero())
+}
+
+/// Ratio of the value of the index currently published by its oracle to its base value
+///
+/// Coupons, principal and the prices of inflation-linked bonds are all scaled by it.
+#[cfg(feature = "trigger")]
+pub fn find_index_ratio(
+    bond_id: &AssetDefinitionId,
+    terms: &BondTerms,
+    inflation_index: &InflationIndex,
+) -> IndexRatio {
+    use iroha_trigger::{
+        data_model::query::account::model::FindAccountById, debug::DebugExpectExt as _, prelude::*,
+    };
+
+    let oracle = FindAccountById::new(inflation_index.oracle.clone())
+        .execute()
+        .dbg_expect(&format!("{bond_id}: Index oracle not found"));
+    let index_value = published_index_value(&oracle, &inflation_index.index).dbg_expect(&format!(
+        "{bond_id}: Inflation index {} not published",
+        inflation_index.index
+    ));
+
+    inflation_index
+        .ratio(index_value, terms.rounding)
+        .dbg_expect("Index ratio overflow")
+}
+
+/// Price index the principal of an inflation-linked bond follows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InflationIndex {
+    /// Name of the published index, e.g. `CPI`
+    pub index: Name,
+    /// Account publishing the index
+    pub oracle: AccountId,
+    /// Value of the index the nominal value is expressed at, usually the one at issuance
+    pub base_value: Decimal,
+}
+
+impl InflationIndex {
+    /// Index ratio for the given value of the index
+    ///
+    /// Returns `None` on overflow.
+    pub fn ratio(&self, index_value: Decimal, mode: RoundingMode) -> Option<IndexRatio> {
+        Some(IndexRatio {
+            index_value,
+            ratio: index_value.checked_div(self.base_value, mode)?,
+        })
+    }
+}
+
+/// Ratio by which the principal of an inflation-linked bond is scaled at a payment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexRatio {
+    /// Value of the index at the payment
+    pub index_value: Decimal,
+    /// Value of the index divided by its base value
+    pub ratio: Decimal,
+}
@@ -0,0 +1,16 @@
+//! Metadata of investor accounts onboarded for bond trading
+
+/// Account metadata key under which the KYC record of an onboarded investor is stored
+pub const KYC_KEY: &str = "kyc";
+
+/// Fields of the KYC record
+pub mod kyc {
+    /// Party that verified the investor's identity
+    pub const PROVIDER: &str = "provider";
+    /// Reference of the verification with the provider
+    pub const REFERENCE: &str = "reference";
+    /// ISO 3166-1 alpha-2 country code of the investor
+    pub const COUNTRY: &str = "country";
+    /// Time of onboarding (in milliseconds since UNIX epoch)
+    pub const VERIFIED_AT_MS: &str = "verified_at_ms";
+}
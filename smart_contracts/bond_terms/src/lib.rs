@@ -0,0 +1,1655 @@
+//! Typed terms of a bond shared by the client and all of the bond triggers
+//!
+//! Bond terms are stored in the metadata of the bond's asset definition.
+//! This crate is the only place where the metadata keys are spelled out.
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod allotment;
+pub mod amortization;
+pub mod auction;
+pub mod bond_type;
+pub mod calendar;
+pub mod call;
+pub mod day_count;
+pub mod floating_rate;
+#[cfg(feature = "trigger")]
+pub mod host;
+pub mod inflation;
+pub mod investor;
+pub mod money;
+pub mod price;
+pub mod put;
+pub mod subscription;
+pub mod triggers;
+
+use alloc::{
+    borrow::ToOwned as _,
+    format,
+    string::{String, ToString as _},
+    vec::Vec,
+};
+use core::{fmt, iter};
+
+use allotment::Allotment;
+use amortization::{AmortizationSchedule, PrincipalRepayment};
+use auction::{Auction, BidBasis, NonCompetitiveTranche};
+use bond_type::BondType;
+use calendar::CouponSchedule;
+use day_count::{DayCount, SECONDS_PER_DAY};
+use floating_rate::{FloatingRate, RateFixing};
+use inflation::{IndexRatio, InflationIndex};
+use iroha_data_model::{metadata::Limits, prelude::*};
+use money::{Decimal, Rounded, RoundingMode, SCALE};
+use price::{Price, PriceStep};
+use put::{PutPenalty, PutSchedule, PutWindow};
+use subscription::SubscriptionPeriod;
+
+/// Version of the bond terms schema written by [`BondTerms`]
+pub const VERSION: u32 = 1;
+
+const LIMITS: Limits = Limits::new(1024, 4096);
+/// Limits of the records, e.g. call notices or put requests, stored as a single metadata value
+const RECORD_LIMITS: Limits = Limits::new(16, 256);
+/// Average length of a Gregorian year
+const SECONDS_PER_YEAR: u64 = 31_556_952;
+
+/// Field of the bond terms as stored in the asset definition metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Version,
+    BondType,
+    Currency,
+    Quantity,
+    NominalValue,
+    CouponRate,
+    FixedFee,
+    FeeRecipientAccountId,
+    RegistrationTimeMs,
+    MaturationDateMs,
+    PaymentFrequencySeconds,
+    DayCountConvention,
+    CouponFrequencyMonths,
+    CouponDatesMs,
+    PaymentDatesMs,
+    CurrencyPrecision,
+    RoundingMode,
+    IssuePrice,
+    PriceScheduleDatesMs,
+    PriceSchedulePrices,
+    ReferenceRate,
+    RateOracle,
+    Spread,
+    RateFloor,
+    RateCap,
+    RepaymentDatesMs,
+    RepaymentFractions,
+    InflationIndex,
+    IndexOracle,
+    BaseIndexValue,
+    CallScheduleDatesMs,
+    CallSchedulePrices,
+    CallNoticePeriodSeconds,
+    PutWindowStartsMs,
+    PutWindowEndsMs,
+    PutNoticePeriodSeconds,
+    PutPenalty,
+    SubscriptionStartMs,
+    SubscriptionEndMs,
+    UnsoldBonds,
+    AllotmentLotSize,
+    AllotmentRounding,
+    PriorityTiers,
+    AuctionPricing,
+    AuctionBidBasis,
+    AuctionLotSize,
+    AuctionRounding,
+    AuctionReserve,
+    NonCompetitiveInvestorCap,
+    NonCompetitiveTotalCap,
+}
+
+impl Field {
+    /// Metadata key under which the field is stored
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Version => "bond_terms_version",
+            Self::BondType => "bond_type",
+            Self::Currency => "currency",
+            Self::Quantity => "quantity",
+            Self::NominalValue => "nominal_value",
+            Self::CouponRate => "coupon_rate",
+            Self::FixedFee => "fixed_fee",
+            Self::FeeRecipientAccountId => "fee_recipient_account_id",
+            Self::RegistrationTimeMs => "registration_time_ms",
+            Self::MaturationDateMs => "maturation_date_ms",
+            Self::PaymentFrequencySeconds => "payment_frequency_seconds",
+            Self::DayCountConvention => "day_count_convention",
+            Self::CouponFrequencyMonths => "coupon_frequency_months",
+            Self::CouponDatesMs => "coupon_dates_ms",
+            Self::PaymentDatesMs => "payment_dates_ms",
+            Self::CurrencyPrecision => "currency_precision",
+            Self::RoundingMode => "rounding_mode",
+            Self::IssuePrice => "issue_price",
+            Self::PriceScheduleDatesMs => "price_schedule_dates_ms",
+            Self::PriceSchedulePrices => "price_schedule_prices",
+            Self::ReferenceRate => "reference_rate",
+            Self::RateOracle => "rate_oracle",
+            Self::Spread => "spread",
+            Self::RateFloor => "rate_floor",
+            Self::RateCap => "rate_cap",
+            Self::RepaymentDatesMs => "repayment_dates_ms",
+            Self::RepaymentFractions => "repayment_fractions",
+            Self::InflationIndex => "inflation_index",
+            Self::IndexOracle => "index_oracle",
+            Self::BaseIndexValue => "base_index_value",
+            Self::CallScheduleDatesMs => "call_schedule_dates_ms",
+            Self::CallSchedulePrices => "call_schedule_prices",
+            Self::CallNoticePeriodSeconds => "call_notice_period_seconds",
+            Self::PutWindowStartsMs => "put_window_starts_ms",
+            Self::PutWindowEndsMs => "put_window_ends_ms",
+            Self::PutNoticePeriodSeconds => "put_notice_period_seconds",
+            Self::PutPenalty => "put_penalty",
+            Self::SubscriptionStartMs => "subscription_start_ms",
+            Self::SubscriptionEndMs => "subscription_end_ms",
+            Self::UnsoldBonds => "unsold_bonds",
+            Self::AllotmentLotSize => "allotment_lot_size",
+            Self::AllotmentRounding => "allotment_rounding",
+            Self::PriorityTiers => "priority_tiers",
+            Self::AuctionPricing => "auction_pricing",
+            Self::AuctionBidBasis => "auction_bid_basis",
+            Self::AuctionLotSize => "auction_lot_size",
+            Self::AuctionRounding => "auction_rounding",
+            Self::AuctionReserve => "auction_reserve",
+            Self::NonCompetitiveInvestorCap => "non_competitive_investor_cap",
+            Self::NonCompetitiveTotalCap => "non_competitive_total_cap",
+        }
+    }
+
+    /// Type the field value is expected to be of
+    pub const fn expected_type(self) -> &'static str {
+        match self {
+            Self::Version
+            | Self::Quantity
+            | Self::CouponFrequencyMonths
+            | Self::CurrencyPrecision
+            | Self::AllotmentLotSize
+            | Self::AuctionLotSize
+            | Self::NonCompetitiveInvestorCap
+            | Self::NonCompetitiveTotalCap => "u32",
+            Self::Currency => "AssetDefinitionId",
+            Self::FeeRecipientAccountId | Self::RateOracle | Self::IndexOracle => "AccountId",
+            Self::NominalValue
+            | Self::CouponRate
+            | Self::FixedFee
+            | Self::Spread
+            | Self::RateFloor
+            | Self::RateCap
+            | Self::BaseIndexValue
+            | Self::AuctionReserve => "NumericValue::Fixed",
+            Self::RegistrationTimeMs
+            | Self::MaturationDateMs
+            | Self::PaymentFrequencySeconds
+            | Self::CallNoticePeriodSeconds
+            | Self::PutNoticePeriodSeconds
+            | Self::SubscriptionStartMs
+            | Self::SubscriptionEndMs => "u64",
+            Self::BondType => "BondType",
+            Self::DayCountConvention => "DayCount",
+            Self::CouponDatesMs
+            | Self::PaymentDatesMs
+            | Self::PriceScheduleDatesMs
+            | Self::RepaymentDatesMs
+            | Self::CallScheduleDatesMs
+            | Self::PutWindowStartsMs
+            | Self::PutWindowEndsMs => "Vec<u64>",
+            Self::RoundingMode => "RoundingMode",
+            Self::IssuePrice => "Price",
+            Self::PutPenalty => "PutPenalty",
+            Self::UnsoldBonds => "UnsoldBonds",
+            Self::AllotmentRounding | Self::AuctionRounding => "AllotmentRounding",
+            Self::AuctionPricing => "AuctionPricing",
+            Self::AuctionBidBasis => "BidBasis",
+            Self::PriorityTiers => "Vec<String>",
+            Self::PriceSchedulePrices | Self::CallSchedulePrices => "Vec<Price>",
+            Self::ReferenceRate | Self::InflationIndex => "Name",
+            Self::RepaymentFractions => "Vec<NumericValue::Fixed>",
+        }
+    }
+
+    fn name(self) -> Name {
+        self.key()
+            .parse()
+            .expect("INTERNAL BUG: Bond terms key is not a valid name")
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// Reason why bond terms couldn't be read from metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Field is not present in the metadata
+    MissingField(Field),
+    /// Field is present but not of the [`Field::expected_type`]
+    InvalidFieldType(Field),
+    /// Terms were written with a schema version this crate doesn't understand
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "bond missing `{field}`"),
+            Self::InvalidFieldType(field) => {
+                write!(f, "`{field}` not of the `{}` type", field.expected_type())
+            }
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported bond terms version {version} (expected {VERSION})"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Reason why well-formed bond terms can't be used to issue a bond
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidTerms {
+    /// No bonds would be minted
+    ZeroQuantity,
+    /// Bond has a zero or negative face value
+    NonPositiveNominalValue,
+    /// Coupon rate is negative
+    NegativeCouponRate,
+    /// Purchase fee is negative
+    NegativeFixedFee,
+    /// Currency precision is finer than amounts can be represented
+    UnsupportedCurrencyPrecision,
+    /// Coupons would have to be paid continuously
+    ZeroPaymentFrequency,
+    /// Bond would mature before or at the time it's registered
+    MaturationNotAfterRegistration,
+    /// Coupons are paid more often than daily under a convention counting whole days
+    SubDayPaymentFrequency,
+    /// Both a payment frequency and a coupon schedule are given
+    PaymentFrequencyWithCouponSchedule,
+    /// Coupon schedule dates are not ordered or don't fall between registration and maturation
+    InvalidCouponSchedule,
+    /// Zero-coupon bond has a coupon rate, payment frequency or coupon schedule
+    CouponsOnZeroCouponBond,
+    /// Floating-rate note has no reference rate or a fixed coupon rate,
+    /// or another bond has a reference rate
+    InvalidFloatingRate,
+    /// Coupon rate floor is above the cap
+    RateFloorAboveCap,
+    /// Inflation-linked bond has no inflation index or its base value isn't positive,
+    /// or another bond has an inflation index
+    InvalidInflationIndex,
+    /// Principal repayments are not positive, exceed the principal or their dates are not
+    /// increasing between registration and maturation
+    InvalidAmortizationSchedule,
+    /// Issue price or a price of the price schedule is zero or negative
+    NonPositiveIssuePrice,
+    /// Price schedule dates are not increasing or fall after maturation
+    InvalidPriceSchedule,
+    /// Call prices are not positive, call dates are not increasing between registration and
+    /// maturation, or a bond without a call schedule has a call notice period
+    InvalidCallSchedule,
+    /// Put windows overlap or are not settled between registration and maturation, the put
+    /// penalty is out of range, or a bond without put windows has put terms
+    InvalidPutSchedule,
+    /// Subscription period opens before registration, is empty or closes at or after maturation
+    InvalidSubscriptionPeriod,
+    /// Allotment of a bond without a subscription period, or with an empty lot, a lot larger
+    /// than the quantity or an empty priority tier
+    InvalidAllotment,
+    /// Auction of a bond without a subscription period or with an allotment, with an empty lot,
+    /// a lot larger than the quantity, an invalid reserve or non-competitive tranche, or yield
+    /// bids on a coupon bond
+    InvalidAuction,
+    /// Terms don't fit the metadata of the asset definition, e.g. too long a coupon schedule
+    ExceedsMetadataLimits,
+}
+
+impl fmt::Display for InvalidTerms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Self::ZeroQuantity => "`quantity` must be greater than zero",
+            Self::NonPositiveNominalValue => "`nominal_value` must be positive",
+            Self::NegativeCouponRate => "`coupon_rate` must not be negative",
+            Self::NegativeFixedFee => "`fixed_fee` must not be negative",
+            Self::UnsupportedCurrencyPrecision => {
+                "`currency_precision` must not exceed 9 fractional digits"
+            }
+            Self::ZeroPaymentFrequency => "`payment_frequency_seconds` must be greater than zero",
+            Self::MaturationNotAfterRegistration => {
+                "`maturation_date_ms` must be after `registration_time_ms`"
+            }
+            Self::SubDayPaymentFrequency => {
+                "`payment_frequency_seconds` must be at least a day unless accrued `SECONDS/365`"
+            }
+            Self::PaymentFrequencyWithCouponSchedule => {
+                "`payment_frequency_seconds` must be zero for bonds with a coupon schedule"
+            }
+            Self::InvalidCouponSchedule => {
+                "coupon dates must be increasing, after registration and end at maturation"
+            }
+            Self::CouponsOnZeroCouponBond => {
+                "zero-coupon bonds must not have a coupon rate, payment frequency or coupon schedule"
+            }
+            Self::InvalidFloatingRate => {
+                "only floating-rate notes have a reference rate, and they have no `coupon_rate`"
+            }
+            Self::RateFloorAboveCap => "`rate_floor` must not be above `rate_cap`",
+            Self::InvalidInflationIndex => {
+                "only inflation-linked bonds have an inflation index, with a positive base value"
+            }
+            Self::InvalidAmortizationSchedule => {
+                "principal repayments must be positive, add up to at most the principal \
+                 and fall on increasing dates between registration and maturation"
+            }
+            Self::NonPositiveIssuePrice => "issue prices must be positive",
+            Self::InvalidPriceSchedule => {
+                "price schedule dates must be increasing and before maturation"
+            }
+            Self::InvalidCallSchedule => {
+                "call prices must be positive and call dates increasing between registration \
+                 and maturation, only callable bonds have a call notice period"
+            }
+            Self::InvalidPutSchedule => {
+                "put windows must not overlap and be settled between registration and maturation, \
+                 put penalties must be between 0% and 100%, only puttable bonds have put terms"
+            }
+            Self::InvalidSubscriptionPeriod => {
+                "subscription period must open at or after registration and close before maturation"
+            }
+            Self::InvalidAllotment => {
+                "only bonds with a subscription period are allotted, in lots of at least one bond \
+                 and at most the quantity, to priority tiers of at least one country"
+            }
+            Self::InvalidAuction => {
+                "auctioned bonds need a subscription period and no allotment, lots within the \
+                 quantity, yield bids only if zero-coupon, and non-competitive caps in whole lots \
+                 leaving a lot to bid for"
+            }
+            Self::ExceedsMetadataLimits => {
+                "terms must fit the metadata of the bond, schedules are limited to 4096 bytes each"
+            }
+        };
+
+        f.write_str(reason)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidTerms {}
+
+/// Terms of a bond
+#[derive(Debug, Clone, PartialEq)]
+pub struct BondTerms {
+    /// Kind of the bond, determines how interest is paid
+    pub bond_type: BondType,
+    /// Currency in which the bond is bought, redeemed and pays interest
+    pub currency: AssetDefinitionId,
+    /// Number of bonds minted to the issuer at registration
+    pub quantity: u32,
+    /// Face value of a single bond
+    pub nominal_value: Decimal,
+    /// Yearly coupon rate, i.e. `0.1` for 10%, zero for zero-coupon and floating-rate bonds
+    pub coupon_rate: Decimal,
+    /// Reference rate the coupons of a floating-rate note follow
+    pub floating_rate: Option<FloatingRate>,
+    /// Price index the principal of an inflation-linked bond is scaled by
+    pub inflation_index: Option<InflationIndex>,
+    /// Absolute fee charged per purchase
+    pub fixed_fee: Decimal,
+    /// Account receiving the purchase fee
+    pub fee_recipient_account_id: AccountId,
+    /// Time of bond registration (in milliseconds since UNIX epoch)
+    pub registration_time_ms: u64,
+    /// Time at which the bond matures (in milliseconds since UNIX epoch)
+    pub maturation_date_ms: u64,
+    /// Period between two coupon payments, zero with a coupon schedule or without coupons
+    pub payment_frequency_seconds: u64,
+    /// Convention by which coupons accrue over a period
+    pub day_count: DayCount,
+    /// Calendar dates of the coupons, replaces `payment_frequency_seconds` if present
+    pub coupon_schedule: Option<CouponSchedule>,
+    /// Number of fractional digits of the smallest unit of the currency, e.g. `2` for cents
+    pub currency_precision: u32,
+    /// Rounding of amounts to the currency precision
+    pub rounding: RoundingMode,
+    /// Principal repaid before maturity, empty unless the bond amortizes
+    pub amortization: AmortizationSchedule,
+    /// Price at which bonds are sold, at par if not given
+    pub issue_price: Option<Price>,
+    /// Prices replacing the issue price from the given times on, ordered by time
+    pub price_schedule: Vec<PriceStep>,
+    /// Prices at which the issuer can call the bond from the given times on, ordered by time,
+    /// empty unless the bond is callable
+    pub call_schedule: Vec<PriceStep>,
+    /// Time between the call and the redemption of the called bonds
+    pub call_notice_period_seconds: u64,
+    /// Windows in which holders can request early redemption, empty unless the bond is puttable
+    pub put_schedule: PutSchedule,
+    /// Period during which bonds are sold, until maturation if not given
+    pub subscription: Option<SubscriptionPeriod>,
+    /// Allotment of the bonds ordered during the subscription period at its close,
+    /// bonds are sold first-come if not given
+    pub allotment: Option<Allotment>,
+    /// Auction of the bonds bid for during the subscription period at its close,
+    /// bonds are sold at the issue price if not given
+    pub auction: Option<Auction>,
+}
+
+/// Period over which a single coupon accrues
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CouponPeriod {
+    /// Start of the period (in milliseconds since UNIX epoch)
+    pub start_ms: u64,
+    /// End of the period (in milliseconds since UNIX epoch)
+    pub end_ms: u64,
+    /// Regular coupon period the period falls into, differs from it for
+    /// the irregular first and last periods (in milliseconds since UNIX epoch)
+    pub reference_period_ms: (u64, u64),
+}
+
+impl BondTerms {
+    /// Check that the terms describe a bond that can be issued
+    ///
+    /// # Errors
+    ///
+    /// Fails on the first term that doesn't hold
+    pub fn validate(&self) -> Result<(), InvalidTerms> {
+        if self.quantity == 0 {
+            return Err(InvalidTerms::ZeroQuantity);
+        }
+        if self.nominal_value.is_negative() || self.nominal_value.is_zero() {
+            return Err(InvalidTerms::NonPositiveNominalValue);
+        }
+        if self.coupon_rate.is_negative() {
+            return Err(InvalidTerms::NegativeCouponRate);
+        }
+        if self.fixed_fee.is_negative() {
+            return Err(InvalidTerms::NegativeFixedFee);
+        }
+        if self.currency_precision > SCALE {
+            return Err(InvalidTerms::UnsupportedCurrencyPrecision);
+        }
+        if self.maturation_date_ms <= self.registration_time_ms {
+            return Err(InvalidTerms::MaturationNotAfterRegistration);
+        }
+        if self
+            .issue_price
+            .iter()
+            .chain(self.price_schedule.iter().map(|step| &step.price))
+            .any(|price| !price.is_positive())
+        {
+            return Err(InvalidTerms::NonPositiveIssuePrice);
+        }
+        if self
+            .price_schedule
+            .windows(2)
+            .any(|steps| steps[0].from_ms >= steps[1].from_ms)
+            || self
+                .price_schedule
+                .last()
+                .is_some_and(|step| step.from_ms >= self.maturation_date_ms)
+        {
+            return Err(InvalidTerms::InvalidPriceSchedule);
+        }
+        if self.call_schedule.iter().any(|step| {
+            !step.price.is_positive()
+                || step.from_ms <= self.registration_time_ms
+                || step.from_ms >= self.maturation_date_ms
+        }) || self
+            .call_schedule
+            .windows(2)
+            .any(|steps| steps[0].from_ms >= steps[1].from_ms)
+            || (self.call_schedule.is_empty() && self.call_notice_period_seconds != 0)
+        {
+            return Err(InvalidTerms::InvalidCallSchedule);
+        }
+        if !self
+            .put_schedule
+            .is_valid(self.registration_time_ms, self.maturation_date_ms)
+            || (self.put_schedule.windows.is_empty()
+                && (self.put_schedule.notice_period_seconds != 0
+                    || self.put_schedule.penalty.is_some()))
+        {
+            return Err(InvalidTerms::InvalidPutSchedule);
+        }
+        if self.subscription.is_some_and(|subscription| {
+            !subscription.is_valid(self.registration_time_ms, self.maturation_date_ms)
+        }) {
+            return Err(InvalidTerms::InvalidSubscriptionPeriod);
+        }
+        if self.allotment.as_ref().is_some_and(|allotment| {
+            self.subscription.is_none() || !allotment.is_valid(self.quantity)
+        }) {
+            return Err(InvalidTerms::InvalidAllotment);
+        }
+        if self.auction.is_some_and(|auction| {
+            self.subscription.is_none()
+                || self.allotment.is_some()
+                || !auction.is_valid(self.quantity)
+                || (auction.basis == BidBasis::Yield && self.bond_type.pays_coupons())
+        }) {
+            return Err(InvalidTerms::InvalidAuction);
+        }
+        if !self
+            .amortization
+            .is_valid(self.registration_time_ms, self.maturation_date_ms)
+        {
+            return Err(InvalidTerms::InvalidAmortizationSchedule);
+        }
+        let is_floating_rate = self.bond_type == BondType::FloatingRate;
+        if is_floating_rate != self.floating_rate.is_some()
+            || (is_floating_rate && !self.coupon_rate.is_zero())
+        {
+            return Err(InvalidTerms::InvalidFloatingRate);
+        }
+        if let Some(FloatingRate {
+            floor: Some(floor),
+            cap: Some(cap),
+            ..
+        }) = &self.floating_rate
+        {
+            if floor > cap {
+                return Err(InvalidTerms::RateFloorAboveCap);
+            }
+        }
+        let is_inflation_linked = self.bond_type == BondType::InflationLinked;
+        if is_inflation_linked != self.inflation_index.is_some()
+            || self
+                .inflation_index
+                .as_ref()
+                .is_some_and(|index| index.base_value.is_negative() || index.base_value.is_zero())
+        {
+            return Err(InvalidTerms::InvalidInflationIndex);
+        }
+        // Terms are stored in the metadata of the bond
+        Metadata::try_from(self.clone())?;
+        if !self.bond_type.pays_coupons() {
+            if !self.coupon_rate.is_zero()
+                || self.payment_frequency_seconds != 0
+                || self.coupon_schedule.is_some()
+            {
+                return Err(InvalidTerms::CouponsOnZeroCouponBond);
+            }
+
+            return Ok(());
+        }
+        match &self.coupon_schedule {
+            None if self.payment_frequency_seconds == 0 => {
+                return Err(InvalidTerms::ZeroPaymentFrequency);
+            }
+            None if self.day_count.counts_days()
+                && self.payment_frequency_seconds < SECONDS_PER_DAY =>
+            {
+                return Err(InvalidTerms::SubDayPaymentFrequency);
+            }
+            None => {}
+            Some(_) if self.payment_frequency_seconds != 0 => {
+                return Err(InvalidTerms::PaymentFrequencyWithCouponSchedule);
+            }
+            Some(schedule) => {
+                if !schedule.is_valid(self.registration_time_ms, self.maturation_date_ms) {
+                    return Err(InvalidTerms::InvalidCouponSchedule);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Last coupon period that ended at or before the given time
+    ///
+    /// Only for bonds without a coupon schedule, whose periods are laid out every
+    /// `payment_frequency_seconds` from the registration time with the last one ending
+    /// at maturation. Returns `None` if no period has ended by then, or the time is
+    /// past the end of the last period.
+    pub fn coupon_period_ended_by(&self, time_ms: u64) -> Option<CouponPeriod> {
+        if !self.bond_type.pays_coupons() || self.coupon_schedule.is_some() {
+            return None;
+        }
+
+        let payment_frequency_ms = self.payment_frequency_seconds.checked_mul(1000)?;
+        let periods = time_ms.checked_sub(self.registration_time_ms)? / payment_frequency_ms;
+
+        let start_ms = self.registration_time_ms + (periods.checked_sub(1)? * payment_frequency_ms);
+        if start_ms >= self.maturation_date_ms {
+            return None;
+        }
+        let regular_end_ms = start_ms.checked_add(payment_frequency_ms)?;
+
+        Some(CouponPeriod {
+            start_ms,
+            end_ms: regular_end_ms.min(self.maturation_date_ms),
+            reference_period_ms: (start_ms, regular_end_ms),
+        })
+    }
+
+    /// Period of the `n`-th coupon of the coupon schedule, counted from 1
+    ///
+    /// Returns `None` if the bond has no coupon schedule or no such coupon.
+    pub fn scheduled_coupon_period(&self, n: usize) -> Option<CouponPeriod> {
+        let schedule = self.coupon_schedule.as_ref()?;
+        let end_ms = *schedule.coupon_dates_ms.get(n.checked_sub(1)?)?;
+
+        if n == 1 {
+            Some(CouponPeriod {
+                start_ms: self.registration_time_ms,
+                end_ms,
+                reference_period_ms: schedule.regular_period(end_ms, true),
+            })
+        } else {
+            let start_ms = schedule.coupon_dates_ms[n - 2];
+
+            Some(CouponPeriod {
+                start_ms,
+                end_ms,
+                reference_period_ms: schedule.regular_period(start_ms, false),
+            })
+        }
+    }
+
+    /// Last coupon period, which ends at maturation
+    ///
+    /// Its coupon is paid together with the principal at maturation, not by the coupon
+    /// triggers. Returns `None` if the bond pays no coupons.
+    pub fn final_coupon_period(&self) -> Option<CouponPeriod> {
+        match &self.coupon_schedule {
+            Some(schedule) => self.scheduled_coupon_period(schedule.coupon_dates_ms.len()),
+            None => self.coupon_period_at(self.maturation_date_ms.checked_sub(1)?),
+        }
+    }
+
+    /// Time at which the principal and the final coupon are paid
+    ///
+    /// That's the maturation date, adjusted to a business day for bonds with a coupon schedule.
+    pub fn maturity_payment_date_ms(&self) -> u64 {
+        self.coupon_schedule
+            .as_ref()
+            .and_then(|schedule| schedule.payment_dates_ms.last().copied())
+            .unwrap_or(self.maturation_date_ms)
+    }
+
+    /// Coupon period whose coupon is paid to the holders at the given time
+    ///
+    /// That's the period the time falls into or, for bonds with a coupon schedule, the period
+    /// whose payment date hasn't come yet. Returns `None` before registration and after the
+    /// last coupon is paid.
+    pub fn coupon_period_at(&self, time_ms: u64) -> Option<CouponPeriod> {
+        if !self.bond_type.pays_coupons() || time_ms < self.registration_time_ms {
+            return None;
+        }
+
+        if let Some(schedule) = &self.coupon_schedule {
+            let n = schedule
+                .payment_dates_ms
+                .iter()
+                .position(|&payment_date_ms| payment_date_ms > time_ms)?;
+
+            return self.scheduled_coupon_period(n + 1);
+        }
+
+        let payment_frequency_ms = self.payment_frequency_seconds.checked_mul(1000)?;
+        let periods = (time_ms - self.registration_time_ms) / payment_frequency_ms;
+
+        let start_ms = self.registration_time_ms + (periods * payment_frequency_ms);
+        if start_ms >= self.maturation_date_ms {
+            return None;
+        }
+        let regular_end_ms = start_ms.checked_add(payment_frequency_ms)?;
+
+        Some(CouponPeriod {
+            start_ms,
+            end_ms: regular_end_ms.min(self.maturation_date_ms),
+            reference_period_ms: (start_ms, regular_end_ms),
+        })
+    }
+
+    /// Exact interest accrued on the given number of bonds since the start of the current
+    /// coupon period until the given time
+    ///
+    /// Returns `None` on overflow.
+    pub fn accrued_interest(
+        &self,
+        quantity: u32,
+        time_ms: u64,
+        coupon_rate: Decimal,
+    ) -> Option<Decimal> {
+        let Some(period) = self.coupon_period_at(time_ms) else {
+            return Some(Decimal::ZERO);
+        };
+
+        self.coupon_amount(
+            quantity,
+            CouponPeriod {
+                end_ms: time_ms.clamp(period.start_ms, period.end_ms),
+                ..period
+            },
+            coupon_rate,
+        )
+    }
+
+    /// Yearly coupon rate of the current coupon period
+    ///
+    /// Floating-rate notes take it from the rate fixing of the period, so it's `None`
+    /// until the rate is fixed.
+    pub fn current_coupon_rate(&self, fixing: Option<&RateFixing>) -> Option<Decimal> {
+        match self.bond_type {
+            BondType::FloatingRate => fixing.map(|fixing| fixing.coupon_rate),
+            BondType::FixedRate | BondType::ZeroCoupon | BondType::InflationLinked => {
+                Some(self.coupon_rate)
+            }
+        }
+    }
+
+    /// Number of regular coupons paid in a year
+    pub fn coupons_per_year(&self) -> u64 {
+        match &self.coupon_schedule {
+            Some(schedule) => 12 / u64::from(schedule.frequency_months.max(1)),
+            None => {
+                (SECONDS_PER_YEAR + self.payment_frequency_seconds / 2)
+                    / self.payment_frequency_seconds.max(1)
+            }
+        }
+        .max(1)
+    }
+
+    /// Price of a single bond at the given time
+    ///
+    /// That's the price of the latest price schedule step started by then, or the issue price.
+    pub fn price_at(&self, time_ms: u64) -> Price {
+        self.price_schedule
+            .iter()
+            .rev()
+            .find(|step| step.from_ms <= time_ms)
+            .map(|step| step.price)
+            .or(self.issue_price)
+            .unwrap_or(Price::Absolute(self.nominal_value))
+    }
+
+    /// Exact price of the given number of bonds bought at the given time, excluding
+    /// accrued interest
+    ///
+    /// Returns `None` on overflow.
+    pub fn clean_price(&self, quantity: u32, time_ms: u64) -> Option<Decimal> {
+        self.price_at(time_ms)
+            .per_bond(self.outstanding_nominal_value(time_ms)?, self.rounding)?
+            .checked_mul_ratio(quantity.into(), 1, self.rounding)
+    }
+
+    /// Exact price of the given number of bonds at an auction bid, excluding accrued interest
+    ///
+    /// Bonds are priced at the close of the subscription, when the auction settles. Price bids
+    /// are a percentage of par, yield bids discount the principal repaid at maturation by the
+    /// simple yield over the day count. Returns `None` on overflow or if the bond isn't auctioned.
+    pub fn bid_price(&self, quantity: u32, bid: Decimal) -> Option<Decimal> {
+        let auction = self.auction?;
+        let settlement_ms = self.subscription?.end_ms;
+        let nominal_value = self.outstanding_nominal_value(settlement_ms)?;
+
+        let price = match auction.basis {
+            BidBasis::Price => Price::PercentOfPar(bid).per_bond(nominal_value, self.rounding)?,
+            BidBasis::Yield => {
+                // ACT/ACT ICMA accrues over the year before maturation
+                let year_fraction = self.day_count.year_fraction(
+                    settlement_ms,
+                    self.maturation_date_ms,
+                    (
+                        self.maturation_date_ms
+                            .saturating_sub(SECONDS_PER_YEAR * 1000),
+                        self.maturation_date_ms,
+                    ),
+                    1,
+                )?;
+                let discount = Decimal::ONE.checked_add(bid.checked_mul_ratio(
+                    year_fraction.days,
+                    year_fraction.basis,
+                    self.rounding,
+                )?)?;
+
+                nominal_value.checked_div(discount, self.rounding)?
+            }
+        };
+
+        price.checked_mul_ratio(quantity.into(), 1, self.rounding)
+    }
+
+    /// Whether bonds can be bought from the issuer at the given time
+    ///
+    /// That's during the subscription period or, for bonds without one, before maturation.
+    pub fn is_subscription_open(&self, time_ms: u64) -> bool {
+        match &self.subscription {
+            Some(subscription) => subscription.is_open(time_ms),
+            None => time_ms < self.maturation_date_ms,
+        }
+    }
+
+    /// Whether the issuer can redeem the bond before maturity
+    pub fn is_callable(&self) -> bool {
+        !self.call_schedule.is_empty()
+    }
+
+    /// Price of a single bond called for redemption at the given time
+    ///
+    /// That's the price of the latest call schedule step started by then, `None` while
+    /// the bond is protected from calls.
+    pub fn call_price_at(&self, redemption_date_ms: u64) -> Option<Price> {
+        self.call_schedule
+            .iter()
+            .rev()
+            .find(|step| step.from_ms <= redemption_date_ms)
+            .map(|step| step.price)
+    }
+
+    /// Time at which bonds called at the given time are redeemed
+    pub fn call_redemption_date_ms(&self, notice_time_ms: u64) -> u64 {
+        notice_time_ms.saturating_add(self.call_notice_period_seconds.saturating_mul(1000))
+    }
+
+    /// Whether holders can request redemption before maturity
+    pub fn is_puttable(&self) -> bool {
+        !self.put_schedule.windows.is_empty()
+    }
+
+    /// Exact price of the given number of bonds put in the `n`-th put window, counted from 1,
+    /// excluding accrued interest
+    ///
+    /// That's the principal outstanding at the settlement less the put penalty, but never
+    /// below zero. Coupons forfeited as penalty are at the given yearly rate. Returns `None`
+    /// if there is no such window or on overflow.
+    pub fn put_price(&self, quantity: u32, n: usize, coupon_rate: Decimal) -> Option<Decimal> {
+        let principal =
+            self.outstanding_principal(quantity, self.put_schedule.settlement_date_ms(n)?)?;
+
+        let penalty = match self.put_schedule.penalty {
+            None => Decimal::ZERO,
+            Some(PutPenalty::Percent(percent)) => principal
+                .checked_mul(percent, self.rounding)?
+                .checked_mul_ratio(1, 100, self.rounding)?,
+            Some(PutPenalty::ForfeitedCoupons(coupons)) => principal
+                .checked_mul(coupon_rate, self.rounding)?
+                .checked_mul_ratio(
+                    coupons.into(),
+                    self.coupons_per_year().into(),
+                    self.rounding,
+                )?,
+        };
+
+        Some(principal.checked_sub(penalty)?.max(Decimal::ZERO))
+    }
+
+    /// Face value of a single bond still outstanding at the given time
+    ///
+    /// That's the nominal value less the principal repaid by then. Returns `None` on overflow.
+    pub fn outstanding_nominal_value(&self, time_ms: u64) -> Option<Decimal> {
+        self.amortization
+            .outstanding_nominal_value(self.nominal_value, time_ms, self.rounding)
+    }
+
+    /// Face value of the given number of bonds still outstanding at the given time
+    ///
+    /// Returns `None` on overflow.
+    pub fn outstanding_principal(&self, quantity: u32, time_ms: u64) -> Option<Decimal> {
+        self.outstanding_nominal_value(time_ms)?.checked_mul_ratio(
+            quantity.into(),
+            1,
+            self.rounding,
+        )
+    }
+
+    /// Exact principal repaid by the `n`-th repayment of the amortization schedule,
+    /// counted from 1, on the given number of bonds
+    ///
+    /// Returns `None` if there is no such repayment or on overflow.
+    pub fn principal_repayment(&self, quantity: u32, n: usize) -> Option<Decimal> {
+        let repayment = self.amortization.repayments.get(n.checked_sub(1)?)?;
+
+        self.nominal_value
+            .checked_mul(repayment.fraction, self.rounding)?
+            .checked_mul_ratio(quantity.into(), 1, self.rounding)
+    }
+
+    /// Exact coupon paid for the period at the given yearly rate on the given number of bonds
+    ///
+    /// Interest accrues on the outstanding principal, so the period is split at the principal
+    /// repayments falling into it. Returns `None` on overflow.
+    pub fn coupon_amount(
+        &self,
+        quantity: u32,
+        period: CouponPeriod,
+        coupon_rate: Decimal,
+    ) -> Option<Decimal> {
+        let segment_ends_ms = self
+            .amortization
+            .dates_between(period.start_ms, period.end_ms)
+            .chain(iter::once(period.end_ms));
+
+        let mut amount = Decimal::ZERO;
+        let mut segment_start_ms = period.start_ms;
+        for segment_end_ms in segment_ends_ms {
+            let year_fraction = self.day_count.year_fraction(
+                segment_start_ms,
+                segment_end_ms,
+                period.reference_period_ms,
+                self.coupons_per_year(),
+            )?;
+
+            let segment_amount = self
+                .outstanding_principal(quantity, segment_start_ms)?
+                .checked_mul(coupon_rate, self.rounding)?
+                .checked_mul_ratio(year_fraction.days, year_fraction.basis, self.rounding)?;
+            amount = amount.checked_add(segment_amount)?;
+            segment_start_ms = segment_end_ms;
+        }
+
+        Some(amount)
+    }
+
+    /// Scale the amount by the index ratio of an inflation-linked bond
+    ///
+    /// Other bonds pass no ratio and keep the amount. Returns `None` on overflow.
+    pub fn index_adjusted(
+        &self,
+        amount: Decimal,
+        index_ratio: Option<IndexRatio>,
+    ) -> Option<Decimal> {
+        match index_ratio {
+            None => Some(amount),
+            Some(index_ratio) => amount.checked_mul(index_ratio.ratio, self.rounding),
+        }
+    }
+
+    /// Round the amount to the precision of the currency
+    pub fn round(&self, amount: Decimal) -> Rounded {
+        amount.round(self.currency_precision, self.rounding)
+    }
+}
+
+impl TryFrom<&Metadata> for BondTerms {
+    type Error = Error;
+
+    fn try_from(metadata: &Metadata) -> Result<Self, Self::Error> {
+        let version: u32 = get(metadata, Field::Version)?;
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        // Bonds issued before bond types were introduced paid fixed-rate coupons
+        let bond_type = get_optional::<String>(metadata, Field::BondType)?
+            .map(|bond_type| bond_type.parse())
+            .transpose()
+            .map_err(|_| Error::InvalidFieldType(Field::BondType))?
+            .unwrap_or(BondType::FixedRate);
+        let pays_coupons = bond_type.pays_coupons();
+        let floating_rate = match bond_type {
+            BondType::FloatingRate => Some(FloatingRate {
+                reference_rate: get::<String>(metadata, Field::ReferenceRate)?
+                    .parse()
+                    .map_err(|_| Error::InvalidFieldType(Field::ReferenceRate))?,
+                oracle: get(metadata, Field::RateOracle)?,
+                spread: get_decimal(metadata, Field::Spread)?,
+                floor: get_optional_decimal(metadata, Field::RateFloor)?,
+                cap: get_optional_decimal(metadata, Field::RateCap)?,
+            }),
+            BondType::FixedRate | BondType::ZeroCoupon | BondType::InflationLinked => None,
+        };
+        let inflation_index = match bond_type {
+            BondType::InflationLinked => Some(InflationIndex {
+                index: get::<String>(metadata, Field::InflationIndex)?
+                    .parse()
+                    .map_err(|_| Error::InvalidFieldType(Field::InflationIndex))?,
+                oracle: get(metadata, Field::IndexOracle)?,
+                base_value: get_decimal(metadata, Field::BaseIndexValue)?,
+            }),
+            BondType::FixedRate | BondType::FloatingRate | BondType::ZeroCoupon => None,
+        };
+
+        Ok(Self {
+            bond_type,
+            currency: get(metadata, Field::Currency)?,
+            quantity: get(metadata, Field::Quantity)?,
+            nominal_value: get_decimal(metadata, Field::NominalValue)?,
+            coupon_rate: match get_optional_decimal(metadata, Field::CouponRate)? {
+                Some(coupon_rate) => coupon_rate,
+                None if !bond_type.has_fixed_coupon_rate() => Decimal::ZERO,
+                None => return Err(Error::MissingField(Field::CouponRate)),
+            },
+            floating_rate,
+            inflation_index,
+            fixed_fee: get_decimal(metadata, Field::FixedFee)?,
+            fee_recipient_account_id: get(metadata, Field::FeeRecipientAccountId)?,
+            registration_time_ms: get(metadata, Field::RegistrationTimeMs)?,
+            maturation_date_ms: get(metadata, Field::MaturationDateMs)?,
+            payment_frequency_seconds: match get_optional(metadata, Field::PaymentFrequencySeconds)?
+            {
+                None if !pays_coupons => 0,
+                payment_frequency_seconds => payment_frequency_seconds
+                    .ok_or(Error::MissingField(Field::PaymentFrequencySeconds))?,
+            },
+            // Bonds issued before conventions were introduced accrued by the second
+            day_count: get_optional::<String>(metadata, Field::DayCountConvention)?
+                .map(|day_count| day_count.parse())
+                .transpose()
+                .map_err(|_| Error::InvalidFieldType(Field::DayCountConvention))?
+                .unwrap_or(DayCount::Seconds365),
+            coupon_schedule: match get_optional(metadata, Field::CouponFrequencyMonths)? {
+                None => None,
+                Some(frequency_months) => Some(CouponSchedule {
+                    frequency_months,
+                    coupon_dates_ms: get_dates(metadata, Field::CouponDatesMs)?,
+                    payment_dates_ms: get_dates(metadata, Field::PaymentDatesMs)?,
+                }),
+            },
+            // Bonds issued before amounts were rounded paid at the precision of `Fixed`
+            currency_precision: get_optional(metadata, Field::CurrencyPrecision)?.unwrap_or(SCALE),
+            rounding: get_optional::<String>(metadata, Field::RoundingMode)?
+                .map(|rounding| rounding.parse())
+                .transpose()
+                .map_err(|_| Error::InvalidFieldType(Field::RoundingMode))?
+                .unwrap_or(RoundingMode::HalfEven),
+            amortization: AmortizationSchedule {
+                repayments: match metadata.get(Field::RepaymentDatesMs.key()) {
+                    None => Vec::new(),
+                    Some(_) => zip_exact(
+                        get_dates(metadata, Field::RepaymentDatesMs)?,
+                        get_decimals(metadata, Field::RepaymentFractions)?,
+                        Field::RepaymentFractions,
+                    )?
+                    .map(|(date_ms, fraction)| PrincipalRepayment { date_ms, fraction })
+                    .collect(),
+                },
+            },
+            issue_price: get_optional::<String>(metadata, Field::IssuePrice)?
+                .map(|price| price.parse())
+                .transpose()
+                .map_err(|_| Error::InvalidFieldType(Field::IssuePrice))?,
+            price_schedule: match metadata.get(Field::PriceScheduleDatesMs.key()) {
+                None => Vec::new(),
+                Some(_) => zip_exact(
+                    get_dates(metadata, Field::PriceScheduleDatesMs)?,
+                    get_prices(metadata, Field::PriceSchedulePrices)?,
+                    Field::PriceSchedulePrices,
+                )?
+                .map(|(from_ms, price)| PriceStep { from_ms, price })
+                .collect(),
+            },
+            call_schedule: match metadata.get(Field::CallScheduleDatesMs.key()) {
+                None => Vec::new(),
+                Some(_) => zip_exact(
+                    get_dates(metadata, Field::CallScheduleDatesMs)?,
+                    get_prices(metadata, Field::CallSchedulePrices)?,
+                    Field::CallSchedulePrices,
+                )?
+                .map(|(from_ms, price)| PriceStep { from_ms, price })
+                .collect(),
+            },
+            call_notice_period_seconds: get_optional(metadata, Field::CallNoticePeriodSeconds)?
+                .unwrap_or(0),
+            put_schedule: PutSchedule {
+                windows: match metadata.get(Field::PutWindowStartsMs.key()) {
+                    None => Vec::new(),
+                    Some(_) => zip_exact(
+                        get_dates(metadata, Field::PutWindowStartsMs)?,
+                        get_dates(metadata, Field::PutWindowEndsMs)?,
+                        Field::PutWindowEndsMs,
+                    )?
+                    .map(|(start_ms, end_ms)| PutWindow { start_ms, end_ms })
+                    .collect(),
+                },
+                notice_period_seconds: get_optional(metadata, Field::PutNoticePeriodSeconds)?
+                    .unwrap_or(0),
+                penalty: get_optional::<String>(metadata, Field::PutPenalty)?
+                    .map(|penalty| penalty.parse())
+                    .transpose()
+                    .map_err(|_| Error::InvalidFieldType(Field::PutPenalty))?,
+            },
+            subscription: match get_optional(metadata, Field::SubscriptionStartMs)? {
+                None => None,
+                Some(start_ms) => Some(SubscriptionPeriod {
+                    start_ms,
+                    end_ms: get(metadata, Field::SubscriptionEndMs)?,
+                    unsold_bonds: get::<String>(metadata, Field::UnsoldBonds)?
+                        .parse()
+                        .map_err(|_| Error::InvalidFieldType(Field::UnsoldBonds))?,
+                }),
+            },
+            allotment: match get_optional(metadata, Field::AllotmentLotSize)? {
+                None => None,
+                Some(lot_size) => Some(Allotment {
+                    lot_size,
+                    rounding: get::<String>(metadata, Field::AllotmentRounding)?
+                        .parse()
+                        .map_err(|_| Error::InvalidFieldType(Field::AllotmentRounding))?,
+                    priority_tiers: match metadata.get(Field::PriorityTiers.key()) {
+                        None => Vec::new(),
+                        Some(_) => get_tiers(metadata, Field::PriorityTiers)?,
+                    },
+                }),
+            },
+            auction: match get_optional::<String>(metadata, Field::AuctionPricing)? {
+                None => None,
+                Some(pricing) => Some(Auction {
+                    pricing: pricing
+                        .parse()
+                        .map_err(|_| Error::InvalidFieldType(Field::AuctionPricing))?,
+                    basis: get::<String>(metadata, Field::AuctionBidBasis)?
+                        .parse()
+                        .map_err(|_| Error::InvalidFieldType(Field::AuctionBidBasis))?,
+                    lot_size: get(metadata, Field::AuctionLotSize)?,
+                    rounding: get::<String>(metadata, Field::AuctionRounding)?
+                        .parse()
+                        .map_err(|_| Error::InvalidFieldType(Field::AuctionRounding))?,
+                    reserve: get_optional_decimal(metadata, Field::AuctionReserve)?,
+                    non_competitive: match get_optional(metadata, Field::NonCompetitiveTotalCap)? {
+                        None => None,
+                        Some(total_cap) => Some(NonCompetitiveTranche {
+                            investor_cap: get(metadata, Field::NonCompetitiveInvestorCap)?,
+                            total_cap,
+                        }),
+                    },
+                }),
+            },
+        })
+    }
+}
+
+impl TryFrom<BondTerms> for Metadata {
+    type Error = InvalidTerms;
+
+    fn try_from(terms: BondTerms) -> Result<Self, Self::Error> {
+        let mut metadata = Metadata::new();
+
+        insert(&mut metadata, Field::Version, VERSION.into())?;
+        insert(
+            &mut metadata,
+            Field::BondType,
+            Value::String(terms.bond_type.as_str().to_owned()),
+        )?;
+        insert(&mut metadata, Field::Currency, terms.currency.into())?;
+        insert(&mut metadata, Field::Quantity, terms.quantity.into())?;
+        insert_decimal(&mut metadata, Field::NominalValue, terms.nominal_value)?;
+        if terms.bond_type.has_fixed_coupon_rate() {
+            insert_decimal(&mut metadata, Field::CouponRate, terms.coupon_rate)?;
+        }
+        if let Some(floating_rate) = terms.floating_rate {
+            insert(
+                &mut metadata,
+                Field::ReferenceRate,
+                Value::String(floating_rate.reference_rate.to_string()),
+            )?;
+            insert(
+                &mut metadata,
+                Field::RateOracle,
+                floating_rate.oracle.into(),
+            )?;
+            insert_decimal(&mut metadata, Field::Spread, floating_rate.spread)?;
+            if let Some(floor) = floating_rate.floor {
+                insert_decimal(&mut metadata, Field::RateFloor, floor)?;
+            }
+            if let Some(cap) = floating_rate.cap {
+                insert_decimal(&mut metadata, Field::RateCap, cap)?;
+            }
+        }
+        if let Some(inflation_index) = terms.inflation_index {
+            insert(
+                &mut metadata,
+                Field::InflationIndex,
+                Value::String(inflation_index.index.to_string()),
+            )?;
+            insert(
+                &mut metadata,
+                Field::IndexOracle,
+                inflation_index.oracle.into(),
+            )?;
+            insert_decimal(
+                &mut metadata,
+                Field::BaseIndexValue,
+                inflation_index.base_value,
+            )?;
+        }
+        if terms.bond_type.pays_coupons() {
+            insert(
+                &mut metadata,
+                Field::PaymentFrequencySeconds,
+                terms.payment_frequency_seconds.into(),
+            )?;
+        }
+        insert_decimal(&mut metadata, Field::FixedFee, terms.fixed_fee)?;
+        insert(
+            &mut metadata,
+            Field::FeeRecipientAccountId,
+            terms.fee_recipient_account_id.into(),
+        )?;
+        insert(
+            &mut metadata,
+            Field::RegistrationTimeMs,
+            terms.registration_time_ms.into(),
+        )?;
+        insert(
+            &mut metadata,
+            Field::MaturationDateMs,
+            terms.maturation_date_ms.into(),
+        )?;
+        insert(
+            &mut metadata,
+            Field::DayCountConvention,
+            Value::String(terms.day_count.as_str().to_owned()),
+        )?;
+        insert(
+            &mut metadata,
+            Field::CurrencyPrecision,
+            terms.currency_precision.into(),
+        )?;
+        insert(
+            &mut metadata,
+            Field::RoundingMode,
+            Value::String(terms.rounding.as_str().to_owned()),
+        )?;
+        if !terms.amortization.repayments.is_empty() {
+            insert(
+                &mut metadata,
+                Field::RepaymentDatesMs,
+                dates_value(
+                    terms
+                        .amortization
+                        .repayments
+                        .iter()
+                        .map(|repayment| repayment.date_ms)
+                        .collect(),
+                ),
+            )?;
+            insert(
+                &mut metadata,
+                Field::RepaymentFractions,
+                Value::Vec(
+                    terms
+                        .amortization
+                        .repayments
+                        .iter()
+                        .map(|repayment| {
+                            repayment
+                                .fraction
+                                .to_fixed()
+                                .expect("INTERNAL BUG: Bond terms amount out of `Fixed` range")
+                                .into()
+                        })
+                        .collect(),
+                ),
+            )?;
+        }
+        if let Some(issue_price) = terms.issue_price {
+            insert(
+                &mut metadata,
+                Field::IssuePrice,
+                Value::String(issue_price.to_string()),
+            )?;
+        }
+        if !terms.price_schedule.is_empty() {
+            insert(
+                &mut metadata,
+                Field::PriceScheduleDatesMs,
+                dates_value(
+                    terms
+                        .price_schedule
+                        .iter()
+                        .map(|step| step.from_ms)
+                        .collect(),
+                ),
+            )?;
+            insert(
+                &mut metadata,
+                Field::PriceSchedulePrices,
+                Value::Vec(
+                    terms
+                        .price_schedule
+                        .iter()
+                        .map(|step| Value::String(step.price.to_string()))
+                        .collect(),
+                ),
+            )?;
+        }
+        if !terms.call_schedule.is_empty() {
+            insert(
+                &mut metadata,
+                Field::CallScheduleDatesMs,
+                dates_value(
+                    terms
+                        .call_schedule
+                        .iter()
+                        .map(|step| step.from_ms)
+                        .collect(),
+                ),
+            )?;
+            insert(
+                &mut metadata,
+                Field::CallSchedulePrices,
+                Value::Vec(
+                    terms
+                        .call_schedule
+                        .iter()
+                        .map(|step| Value::String(step.price.to_string()))
+                        .collect(),
+                ),
+            )?;
+            insert(
+                &mut metadata,
+                Field::CallNoticePeriodSeconds,
+                terms.call_notice_period_seconds.into(),
+            )?;
+        }
+        if !terms.put_schedule.windows.is_empty() {
+            insert(
+                &mut metadata,
+                Field::PutWindowStartsMs,
+                dates_value(
+                    terms
+                        .put_schedule
+                        .windows
+                        .iter()
+                        .map(|window| window.start_ms)
+                        .collect(),
+                ),
+            )?;
+            insert(
+                &mut metadata,
+                Field::PutWindowEndsMs,
+                dates_value(
+                    terms
+                        .put_schedule
+                        .windows
+                        .iter()
+                        .map(|window| window.end_ms)
+                        .collect(),
+                ),
+            )?;
+            insert(
+                &mut metadata,
+                Field::PutNoticePeriodSeconds,
+                terms.put_schedule.notice_period_seconds.into(),
+            )?;
+        }
+        if let Some(subscription) = terms.subscription {
+            insert(
+                &mut metadata,
+                Field::SubscriptionStartMs,
+                subscription.start_ms.into(),
+            )?;
+            insert(
+                &mut metadata,
+                Field::SubscriptionEndMs,
+                subscription.end_ms.into(),
+            )?;
+            insert(
+                &mut metadata,
+                Field::UnsoldBonds,
+                Value::String(subscription.unsold_bonds.as_str().to_owned()),
+            )?;
+        }
+        if let Some(allotment) = terms.allotment {
+            insert(
+                &mut metadata,
+                Field::AllotmentLotSize,
+                allotment.lot_size.into(),
+            )?;
+            insert(
+                &mut metadata,
+                Field::AllotmentRounding,
+                Value::String(allotment.rounding.as_str().to_owned()),
+            )?;
+            if !allotment.priority_tiers.is_empty() {
+                insert(
+                    &mut metadata,
+                    Field::PriorityTiers,
+                    Value::Vec(
+                        allotment
+                            .priority_tiers
+                            .iter()
+                            .map(|tier| Value::String(tier.join(",")))
+                            .collect(),
+                    ),
+                )?;
+            }
+        }
+        if let Some(auction) = terms.auction {
+            insert(
+                &mut metadata,
+                Field::AuctionPricing,
+                Value::String(auction.pricing.as_str().to_owned()),
+            )?;
+            insert(
+                &mut metadata,
+                Field::AuctionBidBasis,
+                Value::String(auction.basis.as_str().to_owned()),
+            )?;
+            insert(
+                &mut metadata,
+                Field::AuctionLotSize,
+                auction.lot_size.into(),
+            )?;
+            insert(
+                &mut metadata,
+                Field::AuctionRounding,
+                Value::String(auction.rounding.as_str().to_owned()),
+            )?;
+            if let Some(reserve) = auction.reserve {
+                insert_decimal(&mut metadata, Field::AuctionReserve, reserve)?;
+            }
+            if let Some(tranche) = auction.non_competitive {
+                insert(
+                    &mut metadata,
+                    Field::NonCompetitiveInvestorCap,
+                    tranche.investor_cap.into(),
+                )?;
+                insert(
+                    &mut metadata,
+                    Field::NonCompetitiveTotalCap,
+                    tranche.total_cap.into(),
+                )?;
+            }
+        }
+        if let Some(penalty) = terms.put_schedule.penalty {
+            insert(
+                &mut metadata,
+                Field::PutPenalty,
+                Value::String(penalty.to_string()),
+            )?;
+        }
+        if let Some(schedule) = terms.coupon_schedule {
+            insert(
+                &mut metadata,
+                Field::CouponFrequencyMonths,
+                schedule.frequency_months.into(),
+            )?;
+            insert(
+                &mut metadata,
+                Field::CouponDatesMs,
+                dates_value(schedule.coupon_dates_ms),
+            )?;
+            insert(
+                &mut metadata,
+                Field::PaymentDatesMs,
+                dates_value(schedule.payment_dates_ms),
+            )?;
+        }
+
+        Ok(metadata)
+    }
+}
+
+/// Bond of the account metadata key made of the prefix and the bond id,
+/// e.g. `put_request_t-bond%%palau`
+pub(crate) fn bond_id_from_key(key: &Name, prefix: &str) -> Option<AssetDefinitionId> {
+    let (name, domain) = key.as_ref().strip_prefix(prefix)?.split_once("%%")?;
+
+    format!("{name}#{domain}").parse().ok()
+}
+
+/// Insert an entry into the metadata of a record, e.g. a call notice
+pub(crate) fn insert_entry(metadata: &mut Metadata, key: &str, value: Value) {
+    metadata
+        .insert_with_limits(
+            key.parse().expect("INTERNAL BUG: Invalid record key"),
+            value,
+            RECORD_LIMITS,
+        )
+        .expect("INTERNAL BUG: Record exceeds metadata limits");
+}
+
+fn get<T: TryFrom<Value>>(metadata: &Metadata, field: Field) -> Result<T, Error> {
+    metadata
+        .get(field.key())
+        .ok_or(Error::MissingField(field))?
+        .to_owned()
+        .try_into()
+        .map_err(|_| Error::InvalidFieldType(field))
+}
+
+fn get_optional<T: TryFrom<Value>>(metadata: &Metadata, field: Field) -> Result<Option<T>, Error> {
+    metadata
+        .get(field.key())
+        .map(|value| {
+            value
+                .to_owned()
+                .try_into()
+                .map_err(|_| Error::InvalidFieldType(field))
+        })
+        .transpose()
+}
+
+/// Decimals are stored as [`Fixed`] so that they can be read without this crate
+fn get_decimal(metadata: &Metadata, field: Field) -> Result<Decimal, Error> {
+    Decimal::from_fixed(get(metadata, field)?).ok_or(Error::InvalidFieldType(field))
+}
+
+fn get_optional_decimal(metadata: &Metadata, field: Field) -> Result<Option<Decimal>, Error> {
+    get_optional(metadata, field)?
+        .map(|value| Decimal::from_fixed(value).ok_or(Error::InvalidFieldType(field)))
+        .transpose()
+}
+
+fn get_dates(metadata: &Metadata, field: Field) -> Result<Vec<u64>, Error> {
+    let Value::Vec(dates) = metadata
+        .get(field.key())
+        .ok_or(Error::MissingField(field))?
+    else {
+        return Err(Error::InvalidFieldType(field));
+    };
+
+    dates
+        .iter()
+        .map(|date| {
+            date.to_owned()
+                .try_into()
+                .map_err(|_| Error::InvalidFieldType(field))
+        })
+        .collect()
+}
+
+fn get_decimals(metadata: &Metadata, field: Field) -> Result<Vec<Decimal>, Error> {
+    let Value::Vec(decimals) = metadata
+        .get(field.key())
+        .ok_or(Error::MissingField(field))?
+    else {
+        return Err(Error::InvalidFieldType(field));
+    };
+
+    decimals
+        .iter()
+        .map(|decimal| {
+            let decimal: Fixed = decimal
+                .to_owned()
+                .try_into()
+                .map_err(|_| Error::InvalidFieldType(field))?;
+
+            Decimal::from_fixed(decimal).ok_or(Error::InvalidFieldType(field))
+        })
+        .collect()
+}
+
+fn get_prices(metadata: &Metadata, field: Field) -> Result<Vec<Price>, Error> {
+    let Value::Vec(prices) = metadata
+        .get(field.key())
+        .ok_or(Error::MissingField(field))?
+    else {
+        return Err(Error::InvalidFieldType(field));
+    };
+
+    prices
+        .iter()
+        .map(|price| match price {
+            Value::String(price) => price.parse().map_err(|_| Error::InvalidFieldType(field)),
+            _ => Err(Error::InvalidFieldType(field)),
+        })
+        .collect()
+}
+
+/// Priority tiers are stored as comma-separated country codes, e.g. `PW,FM`
+fn get_tiers(metadata: &Metadata, field: Field) -> Result<Vec<Vec<String>>, Error> {
+    let Value::Vec(tiers) = metadata
+        .get(field.key())
+        .ok_or(Error::MissingField(field))?
+    else {
+        return Err(Error::InvalidFieldType(field));
+    };
+
+    tiers
+        .iter()
+        .map(|tier| match tier {
+            Value::String(tier) => Ok(tier.split(',').map(ToOwned::to_owned).collect()),
+            _ => Err(Error::InvalidFieldType(field)),
+        })
+        .collect()
+}
+
+/// Pair up the entries of two lists stored under separate keys
+///
+/// The lists must be of the same length, otherwise `field`, the second one, is invalid
+fn zip_exact<A, B>(
+    left: Vec<A>,
+    right: Vec<B>,
+    field: Field,
+) -> Result<impl Iterator<Item = (A, B)>, Error> {
+    if left.len() != right.len() {
+        return Err(Error::InvalidFieldType(field));
+    }
+
+    Ok(left.into_iter().zip(right))
+}
+
+fn dates_value(dates_ms: Vec<u64>) -> Value {
+    Value::Vec(dates_ms.into_iter().map(Into::into).collect())
+}
+
+fn insert_decimal(
+    metadata: &mut Metadata,
+    field: Field,
+    value: Decimal,
+) -> Result<(), InvalidTerms> {
+    let value = value
+        .to_fixed()
+        .expect("INTERNAL BUG: Bond terms amount out of `Fixed` range");
+
+    insert(metadata, field, value.into())
+}
+
+fn insert(metadata: &mut Metadata, field: Field, value: Value) -> Result<(), InvalidTerms> {
+    metadata
+        .insert_with_limits(field.name(), value, LIMITS)
+        .map(|_| ())
+        .map_err(|_| InvalidTerms::ExceedsMetadataLimits)
+}
@@ -0,0 +1,351 @@
+//! Deterministic decimal arithmetic for prices, fees and coupons
+//!
+//! Amounts are exact decimals with [`SCALE`] fractional digits, the precision of [`Fixed`]
+//! assets. Every operation that can't be exact rounds by an explicit [`RoundingMode`], and
+//! amounts are rounded to the precision of the currency only when they are transferred,
+//! keeping the residual so that it can be recorded or carried over instead of being lost.
+
+use alloc::{format, string::ToString as _};
+use core::{fmt, str::FromStr};
+
+use iroha_data_model::prelude::*;
+
+/// Number of fractional digits of a [`Decimal`], same as of [`Fixed`]
+pub const SCALE: u32 = 9;
+
+const UNIT: i128 = 10_i128.pow(SCALE);
+
+/// Key of the holder's account metadata under which the rounding residual of the bond's
+/// coupons and principal repayments is carried over to the next payment
+pub fn residual_key(bond_id: &AssetDefinitionId) -> Name {
+    format!(
+        "coupon_residual_{}%%{}",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Unable to parse coupon residual key")
+}
+
+/// Rule by which a decimal is rounded to fewer fractional digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// To the nearest value, ties to the even neighbour (banker's rounding)
+    HalfEven,
+    /// Towards zero, i.e. truncated
+    Down,
+}
+
+impl RoundingMode {
+    /// Name of the rounding mode, e.g. `half_even`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::HalfEven => "half_even",
+            Self::Down => "down",
+        }
+    }
+
+    /// Divide rounding the quotient by this mode
+    fn div(self, numerator: i128, denominator: i128) -> Option<i128> {
+        let quotient = numerator.checked_div(denominator)?;
+        let remainder = numerator % denominator;
+
+        match self {
+            Self::Down => Some(quotient),
+            Self::HalfEven => {
+                let twice_remainder = remainder.checked_abs()?.checked_mul(2)?;
+                let abs_denominator = denominator.checked_abs()?;
+                let away_from_zero = twice_remainder > abs_denominator
+                    || (twice_remainder == abs_denominator && quotient % 2 != 0);
+
+                if !away_from_zero {
+                    return Some(quotient);
+                }
+                if (numerator < 0) == (denominator < 0) {
+                    quotient.checked_add(1)
+                } else {
+                    quotient.checked_sub(1)
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Rounding mode name that isn't recognized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownRoundingMode;
+
+impl fmt::Display for UnknownRoundingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of `half_even`, `down`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownRoundingMode {}
+
+impl FromStr for RoundingMode {
+    type Err = UnknownRoundingMode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::HalfEven, Self::Down]
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .ok_or(UnknownRoundingMode)
+    }
+}
+
+/// Exact decimal number with [`SCALE`] fractional digits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Decimal(i128);
+
+impl Decimal {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(UNIT);
+
+    /// Whole number
+    pub fn from_integer(value: impl Into<i128>) -> Option<Self> {
+        value.into().checked_mul(UNIT).map(Self)
+    }
+
+    pub fn from_fixed(value: Fixed) -> Option<Self> {
+        value.to_string().parse().ok()
+    }
+
+    /// Convert to a [`Fixed`], returns `None` if out of its range
+    pub fn to_fixed(self) -> Option<Fixed> {
+        self.to_string().parse().ok()
+    }
+
+    pub const fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    /// Multiply, rounding the product to [`SCALE`] digits by the given mode
+    pub fn checked_mul(self, rhs: Self, mode: RoundingMode) -> Option<Self> {
+        mode.div(self.0.checked_mul(rhs.0)?, UNIT).map(Self)
+    }
+
+    /// Divide, rounding the quotient to [`SCALE`] digits by the given mode
+    pub fn checked_div(self, rhs: Self, mode: RoundingMode) -> Option<Self> {
+        if rhs.0 == 0 {
+            return None;
+        }
+
+        mode.div(self.0.checked_mul(UNIT)?, rhs.0).map(Self)
+    }
+
+    /// Multiply by `numerator / denominator` rounding only once, by the given mode
+    pub fn checked_mul_ratio(
+        self,
+        numerator: i128,
+        denominator: i128,
+        mode: RoundingMode,
+    ) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+
+        mode.div(self.0.checked_mul(numerator)?, denominator)
+            .map(Self)
+    }
+
+    /// Round to the given number of fractional digits
+    ///
+    /// Returns the rounded amount and the residual, i.e. the exact amount minus the rounded one.
+    pub fn round(self, precision: u32, mode: RoundingMode) -> Rounded {
+        let Some(step) = SCALE
+            .checked_sub(precision)
+            .and_then(|digits| 10_i128.checked_pow(digits))
+        else {
+            return Rounded {
+                amount: self,
+                residual: Self::ZERO,
+            };
+        };
+
+        let amount = mode
+            .div(self.0, step)
+            .and_then(|steps| steps.checked_mul(step))
+            .map_or(self, Self);
+
+        Rounded {
+            amount,
+            residual: Self(self.0 - amount.0),
+        }
+    }
+}
+
+/// Amount rounded to the precision of a currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rounded {
+    /// Amount that is transferred
+    pub amount: Decimal,
+    /// Exact amount minus the transferred one, negative if rounded up
+    pub residual: Decimal,
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let integer = self.0.unsigned_abs() / UNIT.unsigned_abs();
+        let fraction = self.0.unsigned_abs() % UNIT.unsigned_abs();
+
+        if fraction == 0 {
+            return write!(f, "{sign}{integer}");
+        }
+
+        let mut digits = SCALE as usize;
+        let mut fraction = fraction;
+        while fraction % 10 == 0 {
+            fraction /= 10;
+            digits -= 1;
+        }
+
+        write!(f, "{sign}{integer}.{fraction:0digits$}")
+    }
+}
+
+/// String that isn't a decimal with at most [`SCALE`] fractional digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseDecimalError;
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected a decimal with at most {SCALE} fractional digits"
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDecimalError {}
+
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, s),
+        };
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+
+        let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if integer.is_empty()
+            || !is_digits(integer)
+            || !is_digits(fraction)
+            || fraction.len() > SCALE as usize
+        {
+            return Err(ParseDecimalError);
+        }
+
+        let integer: i128 = integer.parse().map_err(|_| ParseDecimalError)?;
+        let fraction: i128 = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse::<i128>().map_err(|_| ParseDecimalError)?
+                * 10_i128.pow(SCALE - fraction.len() as u32)
+        };
+
+        let value = integer
+            .checked_mul(UNIT)
+            .and_then(|integer| integer.checked_add(fraction))
+            .ok_or(ParseDecimalError)?;
+
+        Ok(Self(if negative { -value } else { value }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn decimal(amount: &str) -> Decimal {
+        amount.parse().expect("Valid decimal")
+    }
+
+    fn round(amount: &str, mode: RoundingMode) -> (Decimal, Decimal) {
+        let rounded = decimal(amount).round(2, mode);
+        (rounded.amount, rounded.residual)
+    }
+
+    #[test]
+    fn half_even_rounds_ties_to_the_even_neighbour() {
+        let half_even = |amount| round(amount, RoundingMode::HalfEven);
+
+        assert_eq!(half_even("2.345"), (decimal("2.34"), decimal("0.005")));
+        assert_eq!(half_even("2.355"), (decimal("2.36"), decimal("-0.005")));
+        assert_eq!(half_even("2.3451"), (decimal("2.35"), decimal("-0.0049")));
+        assert_eq!(half_even("-2.345"), (decimal("-2.34"), decimal("-0.005")));
+        assert_eq!(half_even("-2.355"), (decimal("-2.36"), decimal("0.005")));
+    }
+
+    #[test]
+    fn down_truncates_towards_zero() {
+        let down = |amount| round(amount, RoundingMode::Down);
+
+        assert_eq!(down("2.349"), (decimal("2.34"), decimal("0.009")));
+        assert_eq!(down("-2.349"), (decimal("-2.34"), decimal("-0.009")));
+        assert_eq!(down("2.34"), (decimal("2.34"), Decimal::ZERO));
+    }
+
+    #[test]
+    fn ratio_is_rounded_once_at_scale() {
+        let one = Decimal::ONE;
+
+        assert_eq!(
+            one.checked_mul_ratio(2, 3, RoundingMode::HalfEven),
+            Some(decimal("0.666666667"))
+        );
+        assert_eq!(
+            one.checked_mul_ratio(2, 3, RoundingMode::Down),
+            Some(decimal("0.666666666"))
+        );
+        assert_eq!(one.checked_mul_ratio(2, 0, RoundingMode::Down), None);
+    }
+
+    #[test]
+    fn carried_residual_keeps_payments_summing_to_the_exact_amount() {
+        let coupon = decimal("10")
+            .checked_mul_ratio(1, 3, RoundingMode::HalfEven)
+            .expect("No overflow");
+        assert_eq!(coupon, decimal("3.333333333"));
+
+        let mut residual = Decimal::ZERO;
+        let mut paid = Vec::new();
+        for _ in 0..3 {
+            let payment = coupon
+                .checked_add(residual)
+                .expect("No overflow")
+                .round(2, RoundingMode::HalfEven);
+            paid.push(payment.amount);
+            residual = payment.residual;
+        }
+
+        assert_eq!(paid, [decimal("3.33"), decimal("3.34"), decimal("3.33")]);
+        // Only what's lost rounding the coupon to the scale is left over
+        assert_eq!(residual, decimal("-0.000000001"));
+    }
+}
@@ -0,0 +1,79 @@
+//! Price at which bonds are sold to buyers
+
+use core::{fmt, str::FromStr};
+
+use crate::money::{Decimal, RoundingMode};
+
+/// Price of a single bond, excluding accrued interest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Price {
+    /// Amount in the bond currency, e.g. `985.5`
+    Absolute(Decimal),
+    /// Percentage of the face value, e.g. `98.55%`
+    PercentOfPar(Decimal),
+}
+
+impl Price {
+    /// Amount paid for a single bond of the given face value
+    ///
+    /// Returns `None` on overflow.
+    pub fn per_bond(self, nominal_value: Decimal, mode: RoundingMode) -> Option<Decimal> {
+        match self {
+            Self::Absolute(price) => Some(price),
+            Self::PercentOfPar(percent) => nominal_value
+                .checked_mul(percent, mode)?
+                .checked_mul_ratio(1, 100, mode),
+        }
+    }
+
+    /// Whether the price is greater than zero
+    pub fn is_positive(self) -> bool {
+        let (Self::Absolute(price) | Self::PercentOfPar(price)) = self;
+        !price.is_negative() && !price.is_zero()
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Absolute(price) => write!(f, "{price}"),
+            Self::PercentOfPar(percent) => write!(f, "{percent}%"),
+        }
+    }
+}
+
+/// String that isn't a decimal optionally followed by `%`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsePriceError;
+
+impl fmt::Display for ParsePriceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected an amount, e.g. `985.5`, or a percentage of par, e.g. `98.55%`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParsePriceError {}
+
+impl FromStr for Price {
+    type Err = ParsePriceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_suffix('%') {
+            Some(percent) => percent
+                .trim_end()
+                .parse()
+                .map(Self::PercentOfPar)
+                .map_err(|_| ParsePriceError),
+            None => s.parse().map(Self::Absolute).map_err(|_| ParsePriceError),
+        }
+    }
+}
+
+/// Price that applies to purchases from the given time on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceStep {
+    /// Start of the step (in milliseconds since UNIX epoch)
+    pub from_ms: u64,
+    pub price: Price,
+}
@@ -0,0 +1,190 @@
+//! Early redemption of puttable bonds requested by the holders
+//!
+//! Holders request a put with a `redeem_bonds` order while a put window is open. Requests are
+//! queued in the holder's metadata under [`put_request_key`] and settled at the end of the
+//! window plus the notice period, at the outstanding principal less the put penalty.
+
+use alloc::{borrow::ToOwned as _, format, vec::Vec};
+use core::{fmt, str::FromStr};
+
+use iroha_data_model::prelude::*;
+
+use crate::{insert_entry, money::Decimal};
+
+/// Prefix of the holder's account metadata keys holding the queued put requests
+pub const PUT_REQUEST_KEY_PREFIX: &str = "put_request_";
+
+/// Key of the holder's account metadata under which the put request for the bond is queued
+pub fn put_request_key(bond_id: &AssetDefinitionId) -> Name {
+    format!(
+        "{PUT_REQUEST_KEY_PREFIX}{}%%{}",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Unable to parse put request key")
+}
+
+/// Bond whose put request is queued under the given key of the holder's account metadata
+pub fn put_request_bond_id(key: &Name) -> Option<AssetDefinitionId> {
+    crate::bond_id_from_key(key, PUT_REQUEST_KEY_PREFIX)
+}
+
+/// Period during which holders can request a put
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PutWindow {
+    /// Opening of the window (in milliseconds since UNIX epoch)
+    pub start_ms: u64,
+    /// Closing of the window, inclusive (in milliseconds since UNIX epoch)
+    pub end_ms: u64,
+}
+
+/// Deduction from the principal repaid to holders who put their bonds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PutPenalty {
+    /// Percentage of the outstanding face value, e.g. `2%`
+    Percent(Decimal),
+    /// Number of coupons at the current coupon rate, e.g. `1 coupon`
+    ForfeitedCoupons(u32),
+}
+
+impl fmt::Display for PutPenalty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Percent(percent) => write!(f, "{percent}%"),
+            Self::ForfeitedCoupons(1) => f.write_str("1 coupon"),
+            Self::ForfeitedCoupons(coupons) => write!(f, "{coupons} coupons"),
+        }
+    }
+}
+
+/// String that is neither a percentage nor a number of coupons
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsePutPenaltyError;
+
+impl fmt::Display for ParsePutPenaltyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected a percentage, e.g. `2%`, or forfeited coupons, e.g. `1 coupon`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParsePutPenaltyError {}
+
+impl FromStr for PutPenalty {
+    type Err = ParsePutPenaltyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(percent) = s.strip_suffix('%') {
+            return percent
+                .trim_end()
+                .parse()
+                .map(Self::Percent)
+                .map_err(|_| ParsePutPenaltyError);
+        }
+
+        s.strip_suffix("coupons")
+            .or_else(|| s.strip_suffix("coupon"))
+            .ok_or(ParsePutPenaltyError)?
+            .trim_end()
+            .parse()
+            .map(Self::ForfeitedCoupons)
+            .map_err(|_| ParsePutPenaltyError)
+    }
+}
+
+/// Windows, notice period and penalty of the put, empty unless the bond is puttable
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PutSchedule {
+    /// Windows during which puts can be requested, ordered by time
+    pub windows: Vec<PutWindow>,
+    /// Time between the closing of a window and the settlement of its requests
+    pub notice_period_seconds: u64,
+    /// Deduction from the principal repaid, none if not given
+    pub penalty: Option<PutPenalty>,
+}
+
+impl PutSchedule {
+    /// Check that the windows don't overlap and are settled between registration and
+    /// maturation, and that the penalty isn't negative
+    pub fn is_valid(&self, registration_time_ms: u64, maturation_date_ms: u64) -> bool {
+        let windows_valid = self
+            .windows
+            .windows(2)
+            .all(|windows| windows[0].end_ms < windows[1].start_ms)
+            && self.windows.iter().enumerate().all(|(idx, window)| {
+                registration_time_ms < window.start_ms
+                    && window.start_ms <= window.end_ms
+                    && self
+                        .settlement_date_ms(idx + 1)
+                        .is_some_and(|settlement_ms| settlement_ms < maturation_date_ms)
+            });
+        let penalty_valid = match self.penalty {
+            Some(PutPenalty::Percent(percent)) => {
+                !percent.is_negative() && percent <= Decimal::from_integer(100).unwrap()
+            }
+            Some(PutPenalty::ForfeitedCoupons(_)) | None => true,
+        };
+
+        windows_valid && penalty_valid
+    }
+
+    /// Number of the window open at the given time, counted from 1
+    pub fn window_at(&self, time_ms: u64) -> Option<usize> {
+        self.windows
+            .iter()
+            .position(|window| window.start_ms <= time_ms && time_ms <= window.end_ms)
+            .map(|idx| idx + 1)
+    }
+
+    /// Time at which the requests of the `n`-th window are settled, counted from 1
+    pub fn settlement_date_ms(&self, n: usize) -> Option<u64> {
+        let window = self.windows.get(n.checked_sub(1)?)?;
+
+        window
+            .end_ms
+            .checked_add(self.notice_period_seconds.checked_mul(1000)?)
+    }
+}
+
+/// Put queued by a holder until the settlement of its window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PutRequest {
+    /// Number of the window the request was made in, counted from 1
+    pub window: u32,
+    /// Number of bonds to redeem
+    pub quantity: u32,
+    /// Time of the latest order adding to the request (in milliseconds since UNIX epoch)
+    pub request_time_ms: u64,
+}
+
+impl PutRequest {
+    /// Put request queued in the metadata of the holder, if any
+    pub fn from_holder_metadata(metadata: &Metadata, bond_id: &AssetDefinitionId) -> Option<Self> {
+        let Value::LimitedMetadata(request) = metadata.get(&put_request_key(bond_id))? else {
+            return None;
+        };
+
+        Some(Self {
+            window: request.get("window")?.to_owned().try_into().ok()?,
+            quantity: request.get("quantity")?.to_owned().try_into().ok()?,
+            request_time_ms: request.get("request_time_ms")?.to_owned().try_into().ok()?,
+        })
+    }
+}
+
+impl From<PutRequest> for Value {
+    fn from(request: PutRequest) -> Self {
+        let mut metadata = Metadata::new();
+
+        insert_entry(&mut metadata, "window", request.window.into());
+        insert_entry(&mut metadata, "quantity", request.quantity.into());
+        insert_entry(
+            &mut metadata,
+            "request_time_ms",
+            request.request_time_ms.into(),
+        );
+
+        Value::LimitedMetadata(metadata)
+    }
+}
@@ -0,0 +1,155 @@
+//! Primary offering of a bond during its subscription period
+//!
+//! Bonds are only sold from the issuer's inventory while the subscription period is open.
+//! At its close the unsold bonds are burnt or retained by the issuer, and an [`Allocation`]
+//! is stored in the metadata of the bond under [`ALLOCATION_KEY`].
+
+use alloc::borrow::ToOwned as _;
+use core::{fmt, str::FromStr};
+
+use iroha_data_model::prelude::*;
+
+use crate::insert_entry;
+
+/// Key of the bond metadata holding the allocation published at subscription close
+pub const ALLOCATION_KEY: &str = "allocation";
+
+/// What happens to the bonds left in the issuer's inventory at subscription close
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsoldBonds {
+    /// Unsold bonds are burnt, the issued quantity shrinks to the bonds sold
+    Burn,
+    /// Unsold bonds stay with the issuer
+    Retain,
+}
+
+impl UnsoldBonds {
+    /// Name under which the treatment is stored, e.g. `burn`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Burn => "burn",
+            Self::Retain => "retain",
+        }
+    }
+}
+
+impl fmt::Display for UnsoldBonds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Treatment of unsold bonds that isn't recognized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownUnsoldBonds;
+
+impl fmt::Display for UnknownUnsoldBonds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of `burn`, `retain`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownUnsoldBonds {}
+
+impl FromStr for UnsoldBonds {
+    type Err = UnknownUnsoldBonds;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Burn, Self::Retain]
+            .into_iter()
+            .find(|unsold_bonds| unsold_bonds.as_str() == s)
+            .ok_or(UnknownUnsoldBonds)
+    }
+}
+
+/// Period during which bonds are sold from the issuer's inventory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionPeriod {
+    /// Opening of the subscription (in milliseconds since UNIX epoch)
+    pub start_ms: u64,
+    /// Close of the subscription, exclusive (in milliseconds since UNIX epoch)
+    pub end_ms: u64,
+    /// What happens to the bonds unsold at the close
+    pub unsold_bonds: UnsoldBonds,
+}
+
+impl SubscriptionPeriod {
+    /// Whether bonds can be bought at the given time
+    pub fn is_open(&self, time_ms: u64) -> bool {
+        self.start_ms <= time_ms && time_ms < self.end_ms
+    }
+
+    /// Check that the subscription opens at or after registration and closes before maturation
+    pub fn is_valid(&self, registration_time_ms: u64, maturation_date_ms: u64) -> bool {
+        registration_time_ms <= self.start_ms
+            && self.start_ms < self.end_ms
+            && self.end_ms < maturation_date_ms
+    }
+}
+
+/// Outcome of the primary offering, published at subscription close
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Allocation {
+    /// Time at which the subscription closed (in milliseconds since UNIX epoch)
+    pub close_time_ms: u64,
+    /// Number of bonds offered, the quantity minted at registration
+    pub offered: u32,
+    /// Number of bonds held by investors at the close
+    pub sold: u32,
+    /// Number of bonds left in the issuer's inventory at the close
+    pub unsold: u32,
+    /// Number of investors holding bonds at the close
+    pub investors: u32,
+    /// What happened to the unsold bonds
+    pub unsold_bonds: UnsoldBonds,
+}
+
+impl Allocation {
+    /// Allocation stored in the metadata of the bond, if its subscription has closed
+    pub fn from_bond_metadata(metadata: &Metadata) -> Option<Self> {
+        let Value::LimitedMetadata(allocation) = metadata.get(ALLOCATION_KEY)? else {
+            return None;
+        };
+
+        let Value::String(unsold_bonds) = allocation.get("unsold_bonds")? else {
+            return None;
+        };
+
+        Some(Self {
+            close_time_ms: allocation
+                .get("close_time_ms")?
+                .to_owned()
+                .try_into()
+                .ok()?,
+            offered: allocation.get("offered")?.to_owned().try_into().ok()?,
+            sold: allocation.get("sold")?.to_owned().try_into().ok()?,
+            unsold: allocation.get("unsold")?.to_owned().try_into().ok()?,
+            investors: allocation.get("investors")?.to_owned().try_into().ok()?,
+            unsold_bonds: unsold_bonds.parse().ok()?,
+        })
+    }
+}
+
+impl From<Allocation> for Value {
+    fn from(allocation: Allocation) -> Self {
+        let mut metadata = Metadata::new();
+
+        insert_entry(
+            &mut metadata,
+            "close_time_ms",
+            allocation.close_time_ms.into(),
+        );
+        insert_entry(&mut metadata, "offered", allocation.offered.into());
+        insert_entry(&mut metadata, "sold", allocation.sold.into());
+        insert_entry(&mut metadata, "unsold", allocation.unsold.into());
+        insert_entry(&mut metadata, "investors", allocation.investors.into());
+        insert_entry(
+            &mut metadata,
+            "unsold_bonds",
+            Value::String(allocation.unsold_bonds.as_str().to_owned()),
+        );
+
+        Value::LimitedMetadata(metadata)
+    }
+}
@@ -0,0 +1,200 @@
+//! Per-bond time triggers registered by `register_bond`
+
+use alloc::format;
+use core::time::Duration;
+
+use iroha_data_model::prelude::*;
+
+use crate::{subscription::SubscriptionPeriod, BondTerms};
+
+/// Id of the trigger paying the coupons of the given bond
+pub fn interest_payments_trigger_id(bond_id: &AssetDefinitionId) -> TriggerId {
+    format!(
+        "{}%%{}%%interest_payments",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Invalid interest payments trigger id")
+}
+
+/// Id of the trigger paying the `n`-th coupon of the coupon schedule of the given bond
+pub fn coupon_payment_trigger_id(bond_id: &AssetDefinitionId, n: usize) -> TriggerId {
+    format!(
+        "{}%%{}%%interest_payments%%{n}",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Invalid coupon payment trigger id")
+}
+
+/// Id of the trigger repaying the principal of the given bond
+pub fn bond_maturation_trigger_id(bond_id: &AssetDefinitionId) -> TriggerId {
+    format!(
+        "{}%%{}%%bond_maturation",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Invalid bond maturation trigger id")
+}
+
+/// Id of the trigger making the `n`-th principal repayment of the amortization schedule
+/// of the given bond
+pub fn principal_repayment_trigger_id(bond_id: &AssetDefinitionId, n: usize) -> TriggerId {
+    format!(
+        "{}%%{}%%principal_repayment%%{n}",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Invalid principal repayment trigger id")
+}
+
+/// Id of the trigger redeeming the bonds of the given bond called by the issuer
+pub fn bond_call_trigger_id(bond_id: &AssetDefinitionId) -> TriggerId {
+    format!("{}%%{}%%bond_call", bond_id.name(), bond_id.domain_id())
+        .parse()
+        .expect("INTERNAL BUG: Invalid bond call trigger id")
+}
+
+/// Id of the trigger settling the puts requested in the `n`-th put window of the given bond
+pub fn put_settlement_trigger_id(bond_id: &AssetDefinitionId, n: usize) -> TriggerId {
+    format!(
+        "{}%%{}%%put_settlement%%{n}",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Invalid put settlement trigger id")
+}
+
+/// Id of the trigger closing the subscription of the given bond
+pub fn subscription_close_trigger_id(bond_id: &AssetDefinitionId) -> TriggerId {
+    format!(
+        "{}%%{}%%subscription_close",
+        bond_id.name(),
+        bond_id.domain_id()
+    )
+    .parse()
+    .expect("INTERNAL BUG: Invalid subscription close trigger id")
+}
+
+/// Bond of the per-bond trigger with the given id, `None` if it isn't one
+///
+/// Per-bond triggers are named after the bond followed by their kind, and by their number
+/// if the bond has one of them per date, e.g. `t-bond%%palau%%principal_repayment%%2`.
+pub fn bond_trigger_bond_id(trigger_id: &TriggerId) -> Option<AssetDefinitionId> {
+    let mut parts = trigger_id.name().as_ref().split("%%");
+    let (name, domain, kind) = (parts.next()?, parts.next()?, parts.next()?);
+    let n = parts.next().map(str::parse::<usize>);
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let is_bond_trigger = match (kind, n) {
+        ("interest_payments" | "subscription_close" | "bond_call" | "bond_maturation", None) => {
+            true
+        }
+        ("interest_payments" | "principal_repayment" | "put_settlement", Some(Ok(n))) => n > 0,
+        _ => false,
+    };
+    if !is_bond_trigger {
+        return None;
+    }
+
+    format!("{name}#{domain}").parse().ok()
+}
+
+/// Schedule on which the coupons of a bond without a coupon schedule are paid
+pub fn interest_payments_filter(terms: &BondTerms) -> TriggeringFilterBox {
+    let registration_time = Duration::from_millis(terms.registration_time_ms);
+    let payment_frequency = Duration::from_secs(terms.payment_frequency_seconds);
+
+    // TODO: This is simplified in RC22
+    TriggeringFilterBox::from(TimeEventFilter::new(ExecutionTime::Schedule(
+        TimeSchedule::starting_at(registration_time).with_period(payment_frequency),
+    )))
+}
+
+/// Schedule on which a coupon of the coupon schedule is paid,
+/// the trigger must be registered with `Repeats::Exactly(1)`
+pub fn coupon_payment_filter(payment_date_ms: u64) -> TriggeringFilterBox {
+    one_shot_filter(payment_date_ms)
+}
+
+/// Schedule on which a principal repayment of the amortization schedule is made,
+/// the trigger must be registered with `Repeats::Exactly(1)`
+pub fn principal_repayment_filter(repayment_date_ms: u64) -> TriggeringFilterBox {
+    one_shot_filter(repayment_date_ms)
+}
+
+/// Schedule on which the matured bond is repaid, the trigger must be registered with
+/// `Repeats::Exactly(1)`
+pub fn bond_maturation_filter(terms: &BondTerms) -> TriggeringFilterBox {
+    one_shot_filter(terms.maturity_payment_date_ms())
+}
+
+/// Schedule on which called bonds are redeemed, the trigger must be registered with
+/// `Repeats::Exactly(1)`
+pub fn bond_call_filter(redemption_date_ms: u64) -> TriggeringFilterBox {
+    one_shot_filter(redemption_date_ms)
+}
+
+/// Schedule on which the puts requested in a put window are settled, the trigger must be
+/// registered with `Repeats::Exactly(1)`
+pub fn put_settlement_filter(settlement_date_ms: u64) -> TriggeringFilterBox {
+    one_shot_filter(settlement_date_ms)
+}
+
+/// Schedule on which the subscription closes, the trigger must be registered with
+/// `Repeats::Exactly(1)`
+pub fn subscription_close_filter(subscription: &SubscriptionPeriod) -> TriggeringFilterBox {
+    one_shot_filter(subscription.end_ms)
+}
+
+/// Schedule firing once at the given time (in milliseconds since UNIX epoch)
+fn one_shot_filter(date_ms: u64) -> TriggeringFilterBox {
+    // TODO: This is simplified in RC22
+    TriggeringFilterBox::from(TimeEventFilter::new(ExecutionTime::Schedule(
+        TimeSchedule::starting_at(Duration::from_millis(date_ms)),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_bond_trigger_ids_name_their_bond() {
+        let bond_id: AssetDefinitionId = "t-bond#palau".parse().expect("Valid bond id");
+
+        for trigger_id in [
+            interest_payments_trigger_id(&bond_id),
+            coupon_payment_trigger_id(&bond_id, 1),
+            bond_maturation_trigger_id(&bond_id),
+            principal_repayment_trigger_id(&bond_id, 2),
+            bond_call_trigger_id(&bond_id),
+            put_settlement_trigger_id(&bond_id, 3),
+            subscription_close_trigger_id(&bond_id),
+        ] {
+            assert_eq!(bond_trigger_bond_id(&trigger_id), Some(bond_id.clone()));
+        }
+    }
+
+    #[test]
+    fn other_trigger_ids_name_no_bond() {
+        for trigger_id in [
+            "register_bond",
+            "t-bond%%palau%%minter",
+            "t-bond%%palau%%bond_maturation%%1",
+            "t-bond%%palau%%principal_repayment",
+            "t-bond%%palau%%put_settlement%%0",
+            "t-bond%%palau%%interest_payments%%1%%2",
+        ] {
+            let trigger_id: TriggerId = trigger_id.parse().expect("Valid trigger id");
+            assert_eq!(bond_trigger_bond_id(&trigger_id), None);
+        }
+    }
+}
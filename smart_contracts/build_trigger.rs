@@ -0,0 +1,20 @@
+// Build script helper shared by the triggers registering other triggers, pulled in with `include!`
+use std::{io::Write as _, path::Path};
+
+/// Compile the trigger crate next to the including one into `OUT_DIR/<trigger>.wasm`
+fn build_trigger(trigger: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let trigger_dir = Path::new("..").join(trigger);
+    println!("cargo::rerun-if-changed={}", trigger_dir.display());
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let wasm = iroha_wasm_builder::Builder::new(&trigger_dir)
+        // TODO: Available in RC22
+        //.show_output()
+        .build()?
+        .optimize()?
+        .into_bytes()?;
+
+    let mut file = std::fs::File::create(Path::new(&out_dir).join(format!("{trigger}.wasm")))?;
+    file.write_all(&wasm)?;
+    Ok(())
+}
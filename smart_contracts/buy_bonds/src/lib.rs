@@ -9,6 +9,16 @@ use core::num::NonZeroU32;
 
 use alloc::{borrow::ToOwned as _, format};
 
+use bond_terms::{
+    allotment::{subscription_order_key, Allotment, SubscriptionOrder},
+    auction::{auction_bid_key, AuctionBid},
+    floating_rate::RateFixing,
+    host::{block_time_ms, next_payment_idx},
+    inflation::{find_index_ratio, IndexRatio},
+    investor::{kyc, KYC_KEY},
+    money::Decimal,
+    BondTerms,
+};
 use dlmalloc::GlobalDlmalloc;
 use iroha_trigger::{
     data_model::prelude::*,
@@ -32,6 +42,8 @@ struct BuyBondsOrder {
     bond: AssetDefinition,
     /// How many bonds to buy
     quantity: NonZeroU32,
+    /// Price or yield bid for auctioned bonds
+    bid: Option<Decimal>,
 }
 
 impl BuyBondsOrder {
@@ -48,6 +60,13 @@ impl BuyBondsOrder {
             .to_owned()
             .try_into()
             .dbg_expect("`bond_quantity` not of the `u32` type");
+        let bid = metadata.get("bid").map(|bid| {
+            let bid: Fixed = bid
+                .to_owned()
+                .try_into()
+                .dbg_expect("`bid` not of the `Fixed` type");
+            Decimal::from_fixed(bid).dbg_expect("`bid` out of range")
+        });
 
         let bond = FindAssetDefinitionById::new(bond_id.clone())
             .execute()
@@ -58,6 +77,7 @@ impl BuyBondsOrder {
             buyer,
             bond,
             quantity: NonZeroU32::new(quantity).dbg_expect("Bond quantity is zero"),
+            bid,
         }
     }
 
@@ -85,73 +105,320 @@ impl BuyBondsOrder {
         }
     }
 
-    fn find_buy_bond_payment_idx(buyer: &AccountId) -> u32 {
-        let buy_bond_payment_idx_key: Name = "buy_bond_payment_idx"
-            .parse()
-            .dbg_expect("INTERNAL BUG: Unable to parse buy bond payment index key");
+    /// Interest accrued on the ordered bonds by the close of the subscription
+    ///
+    /// Bonds are issued at the close, the interest accrued by then is paid along.
+    fn accrued_interest_at_close(
+        &self,
+        terms: &BondTerms,
+        coupon_rate: Decimal,
+        index_ratio: Option<IndexRatio>,
+    ) -> Decimal {
+        let close_ms = terms
+            .subscription
+            .dbg_expect("INTERNAL BUG: Bond sold at the close has no subscription period")
+            .end_ms;
 
-        let current_idx = FindAccountById::new(buyer.clone())
-            .execute()
-            .dbg_expect("INTERNAL BUG: Account not found")
-            .metadata()
-            .get(&buy_bond_payment_idx_key)
-            .map(|idx| {
-                idx.to_owned()
-                    .try_into()
-                    .dbg_expect("INTERNAL BUG: `buy_bond_payment_idx` not of the `u32` type")
-            })
-            .unwrap_or(0_u32);
-
-        let new_idx = current_idx + 1;
-
-        SetKeyValueExpr::new(buyer.clone(), buy_bond_payment_idx_key, Value::Numeric(new_idx.into()))
+        terms
+            .round(
+                terms
+                    .accrued_interest(self.quantity.get(), close_ms, coupon_rate)
+                    .and_then(|accrued| terms.index_adjusted(accrued, index_ratio))
+                    .dbg_expect("Accrued interest overflow"),
+            )
+            .amount
+    }
+
+    /// Lock the funds of the order with the issuer until the bonds are allotted at subscription close
+    fn place_subscription_order(
+        self,
+        allotment: &Allotment,
+        currency: AssetDefinitionId,
+        now_ms: u64,
+        clean_price: Decimal,
+        accrued_interest: Decimal,
+        fee: Decimal,
+    ) {
+        let bond_id = self.bond.id();
+        if self.quantity.get() % allotment.lot_size != 0 {
+            error!(&format!(
+                "{bond_id}: Quantity must be a multiple of the lot size of {} bonds",
+                allotment.lot_size
+            ));
+            return;
+        }
+
+        let locked_amount = clean_price
+            .checked_add(accrued_interest)
+            .and_then(|amount| amount.checked_add(fee))
+            .and_then(Decimal::to_fixed)
+            .dbg_expect("Subscription order amount overflow");
+        let bond_buyer_money = AssetId::new(currency, self.buyer.clone());
+        if !Self::check_account_asset_amount(&bond_buyer_money, locked_amount.into()) {
+            return;
+        }
+
+        let buyer = FindAccountById::new(self.buyer.clone())
             .execute()
-            .dbg_expect("Failed to set buy bond payment index to buyer's metadata");
+            .dbg_expect("INTERNAL BUG: Account not found");
+        let order = match SubscriptionOrder::from_buyer_metadata(buyer.metadata(), bond_id) {
+            // Orders add up, the first one keeps its place in the queue
+            Some(order) => SubscriptionOrder {
+                quantity: order
+                    .quantity
+                    .checked_add(self.quantity.get())
+                    .dbg_expect("Subscription order quantity overflow"),
+                clean_price: order
+                    .clean_price
+                    .checked_add(clean_price)
+                    .dbg_expect("Subscription order amount overflow"),
+                accrued_interest: order
+                    .accrued_interest
+                    .checked_add(accrued_interest)
+                    .dbg_expect("Subscription order amount overflow"),
+                fee: order
+                    .fee
+                    .checked_add(fee)
+                    .dbg_expect("Subscription order amount overflow"),
+                ..order
+            },
+            None => {
+                let country = match buyer.metadata().get(KYC_KEY) {
+                    Some(Value::LimitedMetadata(record)) => match record.get(kyc::COUNTRY) {
+                        Some(Value::String(country)) => Some(country.as_str()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
 
-        new_idx
+                SubscriptionOrder {
+                    quantity: self.quantity.get(),
+                    tier: allotment.tier_of(country),
+                    clean_price,
+                    accrued_interest,
+                    fee,
+                    order_time_ms: now_ms,
+                }
+            }
+        };
+
+        TransferExpr::new(bond_buyer_money, locked_amount, self.issuer)
+            .execute()
+            .dbg_expect("Locking money failed");
+        SetKeyValueExpr::new(self.buyer, subscription_order_key(bond_id), order)
+            .execute()
+            .dbg_expect("Failed to set subscription order to buyer's metadata");
     }
 
-    fn execute(self) {
-        let bond_currency: AssetDefinitionId = self
-            .bond
-            .metadata()
-            .get("currency")
-            .dbg_expect("Currency not found")
-            .to_owned()
-            .try_into()
-            .dbg_expect("`currency` not of the `AssetDefinitionId` type");
+    /// Seal the bid and lock the price of the bonds at the bid with the issuer until the
+    /// auction closes, orders without a bid join the non-competitive tranche at par
+    fn place_auction_bid(
+        self,
+        terms: &BondTerms,
+        bid: Option<Decimal>,
+        now_ms: u64,
+        coupon_rate: Decimal,
+        index_ratio: Option<IndexRatio>,
+        fee: Decimal,
+    ) {
+        let bond_id = self.bond.id();
+        let auction = terms
+            .auction
+            .dbg_expect("INTERNAL BUG: Bond is not auctioned");
+        if self.quantity.get() % auction.lot_size != 0 {
+            error!(&format!(
+                "{bond_id}: Quantity must be a multiple of the lot size of {} bonds",
+                auction.lot_size
+            ));
+            return;
+        }
+        match (bid, auction.non_competitive) {
+            (Some(bid), _) if !auction.is_valid_bid(bid) || !auction.meets_reserve(bid) => {
+                error!(&format!("{bond_id}: Bid of {bid} not accepted"));
+                return;
+            }
+            (None, None) => {
+                error!(&format!("{bond_id}: Auctioned bonds must be bid for"));
+                return;
+            }
+            _ => {}
+        }
 
-        let bond_nominal_value: Fixed = self
-            .bond
-            .metadata()
-            .get("nominal_value")
-            .dbg_expect("Nominal value not found")
-            .to_owned()
-            .try_into()
-            .dbg_expect("`nominal_value` not of the `NumericValue::Fixed` type");
+        let buyer = FindAccountById::new(self.buyer.clone())
+            .execute()
+            .dbg_expect("INTERNAL BUG: Account not found");
+        let sealed_bid = AuctionBid::from_bidder_metadata(buyer.metadata(), bond_id);
+        if let Some(sealed_bid) = sealed_bid.filter(|sealed_bid| sealed_bid.bid != bid) {
+            match sealed_bid.bid {
+                Some(sealed_bid) => error!(&format!(
+                    "{bond_id}: Only one bid per bidder, already bid {sealed_bid}"
+                )),
+                None => error!(&format!(
+                    "{bond_id}: Only one bid per bidder, already ordered non-competitively"
+                )),
+            }
+            return;
+        }
+        if let (None, Some(tranche)) = (bid, auction.non_competitive) {
+            let ordered = sealed_bid.map_or(0, |sealed_bid| sealed_bid.quantity);
+            if ordered.saturating_add(self.quantity.get()) > tranche.investor_cap {
+                error!(&format!(
+                    "{bond_id}: Non-competitive orders are capped at {} bonds per investor, \
+                     {ordered} already ordered",
+                    tranche.investor_cap
+                ));
+                return;
+            }
+        }
 
-        // note: fixed fee is an absolute value, i.e 0.1$. Clarify if it should be a percentage of the bond nominal value
-        let bond_fee: Fixed = self
-            .bond
-            .metadata()
-            .get("fixed_fee")
-            .dbg_expect("Fixed fee not found")
-            .to_owned()
-            .try_into()
-            .dbg_expect("`fixed_fee` not of the `NumericValue::Fixed` type");
+        let clean_price = terms
+            .round(
+                terms
+                    .bid_price(self.quantity.get(), bid.unwrap_or(auction.par_bid()))
+                    .and_then(|price| terms.index_adjusted(price, index_ratio))
+                    .dbg_expect("Bond total price overflow"),
+            )
+            .amount;
+        let accrued_interest = self.accrued_interest_at_close(terms, coupon_rate, index_ratio);
 
-        let bond_fee_recipient: AccountId = self
-            .bond
-            .metadata()
-            .get("fee_recipient_account_id")
-            .dbg_expect("Fee recipient account id not found")
-            .to_owned()
-            .try_into()
-            .dbg_expect("`fee_recipient_account_id` not of the `AccountId` type");
+        let locked_amount = clean_price
+            .checked_add(accrued_interest)
+            .and_then(|amount| amount.checked_add(fee))
+            .and_then(Decimal::to_fixed)
+            .dbg_expect("Auction bid amount overflow");
+        let bond_buyer_money = AssetId::new(terms.currency.clone(), self.buyer.clone());
+        if !Self::check_account_asset_amount(&bond_buyer_money, locked_amount.into()) {
+            return;
+        }
+
+        let sealed_bid = match sealed_bid {
+            // Orders at the same bid, or non-competitive ones, add up,
+            // the first one keeps its place in the queue
+            Some(sealed_bid) => AuctionBid {
+                quantity: sealed_bid
+                    .quantity
+                    .checked_add(self.quantity.get())
+                    .dbg_expect("Auction bid quantity overflow"),
+                clean_price: sealed_bid
+                    .clean_price
+                    .checked_add(clean_price)
+                    .dbg_expect("Auction bid amount overflow"),
+                accrued_interest: sealed_bid
+                    .accrued_interest
+                    .checked_add(accrued_interest)
+                    .dbg_expect("Auction bid amount overflow"),
+                fee: sealed_bid
+                    .fee
+                    .checked_add(fee)
+                    .dbg_expect("Auction bid amount overflow"),
+                ..sealed_bid
+            },
+            None => AuctionBid {
+                quantity: self.quantity.get(),
+                bid,
+                clean_price,
+                accrued_interest,
+                fee,
+                bid_time_ms: now_ms,
+            },
+        };
+
+        TransferExpr::new(bond_buyer_money, locked_amount, self.issuer)
+            .execute()
+            .dbg_expect("Locking money failed");
+        SetKeyValueExpr::new(self.buyer, auction_bid_key(bond_id), sealed_bid)
+            .execute()
+            .dbg_expect("Failed to set auction bid to buyer's metadata");
+    }
+
+    fn execute(self) {
+        let terms = BondTerms::try_from(self.bond.metadata())
+            .dbg_expect(&format!("{}: Invalid bond terms", self.bond.id()));
+        // note: fixed fee is an absolute value, i.e 0.1$. Clarify if it should be a percentage of the bond nominal value
+        let bond_fee = terms.round(terms.fixed_fee);
+        let now_ms = block_time_ms();
+        if !terms.is_subscription_open(now_ms) {
+            error!(&format!("{}: Subscription not open at {now_ms} ms", self.bond.id()));
+            return;
+        }
+        // Prices of inflation-linked bonds follow the index, like their coupons and principal
+        let index_ratio = terms
+            .inflation_index
+            .as_ref()
+            .map(|inflation_index| find_index_ratio(self.bond.id(), &terms, inflation_index));
+        let clean_price = terms.round(
+            terms
+                .clean_price(self.quantity.get(), now_ms)
+                .and_then(|price| terms.index_adjusted(price, index_ratio))
+                .dbg_expect("Bond total price overflow"),
+        );
+        // Buyer compensates the seller for the interest accrued since the last coupon date,
+        // since the whole coupon is paid to whoever holds the bond on the payment date
+        let coupon_rate = terms
+            .current_coupon_rate(RateFixing::from_bond_metadata(self.bond.metadata()).as_ref())
+            .dbg_expect(&format!("{}: Coupon rate not fixed", self.bond.id()));
+        match (terms.auction, self.bid) {
+            (Some(_), bid) => {
+                self.place_auction_bid(
+                    &terms,
+                    bid,
+                    now_ms,
+                    coupon_rate,
+                    index_ratio,
+                    bond_fee.amount,
+                );
+                return;
+            }
+            (None, Some(_)) => {
+                error!(&format!("{}: Bond is not auctioned", self.bond.id()));
+                return;
+            }
+            (None, None) => {}
+        }
+        if let Some(allotment) = &terms.allotment {
+            let accrued_interest =
+                self.accrued_interest_at_close(&terms, coupon_rate, index_ratio);
 
-        let bonds_total_price = Fixed::try_from(self.quantity.get() as f64)
-            .and_then(|qty| qty.checked_mul(bond_nominal_value))
+            // Oversubscribed bonds are allotted at the close, residuals are settled then
+            self.place_subscription_order(
+                allotment,
+                terms.currency.clone(),
+                now_ms,
+                clean_price.amount,
+                accrued_interest,
+                bond_fee.amount,
+            );
+            return;
+        }
+        let accrued_interest = terms.round(
+            terms
+                .accrued_interest(self.quantity.get(), now_ms, coupon_rate)
+                .and_then(|accrued| terms.index_adjusted(accrued, index_ratio))
+                .dbg_expect("Accrued interest overflow"),
+        );
+        let residual = clean_price
+            .residual
+            .checked_add(accrued_interest.residual)
+            .and_then(|residual| residual.checked_add(bond_fee.residual))
+            .and_then(Decimal::to_fixed)
+            .dbg_expect("Bond price residual overflow");
+        let bonds_total_price = clean_price
+            .amount
+            .checked_add(accrued_interest.amount)
+            .and_then(Decimal::to_fixed)
             .dbg_expect("Bond total price overflow");
+        let clean_price = clean_price
+            .amount
+            .to_fixed()
+            .dbg_expect("Bond total price overflow");
+        let accrued_interest = accrued_interest
+            .amount
+            .to_fixed()
+            .dbg_expect("Accrued interest overflow");
+        let bond_fee = bond_fee.amount.to_fixed().dbg_expect("Bond fee overflow");
+
+        let bond_currency = terms.currency;
+        let bond_fee_recipient = terms.fee_recipient_account_id;
 
         let bond_buyer_money = AssetId::new(bond_currency.clone(), self.buyer.clone());
         let bond_issuer_bonds = AssetId::new(self.bond.id().clone(), self.issuer.clone());
@@ -163,7 +430,7 @@ impl BuyBondsOrder {
             return;
         }
 
-        let buy_bond_payment_idx = Self::find_buy_bond_payment_idx(&self.buyer);
+        let buy_bond_payment_idx = next_payment_idx(&self.buyer, "buy_bond_payment_idx");
         let transfer_metadata_id: Name = format!(
             "buy_bond_payment_{}%%{}%%idx%%{}",
             self.bond.id().name().to_owned(),
@@ -176,6 +443,12 @@ impl BuyBondsOrder {
         transfer_metadata
             .insert_with_limits("amount".parse().unwrap(), bonds_total_price.into(), LIMITS)
             .unwrap();
+        transfer_metadata
+            .insert_with_limits("clean_price".parse().unwrap(), clean_price.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("accrued_interest".parse().unwrap(), accrued_interest.into(), LIMITS)
+            .unwrap();
         transfer_metadata
             .insert_with_limits("quantity".parse().unwrap(), self.quantity.get().into(), LIMITS)
             .unwrap();
@@ -185,6 +458,25 @@ impl BuyBondsOrder {
         transfer_metadata
             .insert_with_limits("bond_asset_id".parse().unwrap(), self.bond.id().clone().into(), LIMITS)
             .unwrap();
+        transfer_metadata
+            .insert_with_limits("residual".parse().unwrap(), residual.into(), LIMITS)
+            .unwrap();
+        if let Some(index_ratio) = index_ratio {
+            transfer_metadata
+                .insert_with_limits(
+                    "index_value".parse().unwrap(),
+                    index_ratio.index_value.to_fixed().dbg_expect("Index value overflow").into(),
+                    LIMITS,
+                )
+                .unwrap();
+            transfer_metadata
+                .insert_with_limits(
+                    "index_ratio".parse().unwrap(),
+                    index_ratio.ratio.to_fixed().dbg_expect("Index ratio overflow").into(),
+                    LIMITS,
+                )
+                .unwrap();
+        }
 
         TransferExpr::new(bond_buyer_money.clone(), bonds_total_price, self.issuer)
             .execute()
@@ -0,0 +1,11 @@
+//! Compile the trigger redeeming called bonds
+include!("../build_trigger.rs");
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo::rerun-if-changed=../bond_terms");
+    println!("cargo::rerun-if-changed=../build_trigger.rs");
+
+    build_trigger("bond_maturation")?;
+
+    Ok(())
+}
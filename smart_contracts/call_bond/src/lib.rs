@@ -0,0 +1,226 @@
+//! Trigger calling bonds for early redemption by the issuer
+#![no_std]
+
+extern crate alloc;
+#[cfg(not(test))]
+extern crate panic_halt;
+
+use alloc::{borrow::ToOwned as _, format, string::ToString as _};
+use core::fmt;
+
+use bond_terms::{
+    call::{CallNotice, CALL_NOTICE_KEY},
+    host::block_time_ms,
+    triggers::{bond_call_filter, bond_call_trigger_id},
+    BondTerms,
+};
+use dlmalloc::GlobalDlmalloc;
+use iroha_trigger::{
+    data_model::prelude::*,
+    debug::{dbg_panic, DebugExpectExt as _},
+    log::{error, info, trace},
+    prelude::*,
+};
+
+#[global_allocator]
+static ALLOC: GlobalDlmalloc = GlobalDlmalloc;
+
+const LIMITS: MetadataLimits = MetadataLimits::new(256, 256);
+
+/// Reason why a bond was not called
+enum Rejection {
+    /// Bond is not a registered asset definition
+    BondNotFound,
+    /// Bond terms couldn't be read from the asset definition metadata
+    MalformedTerms(bond_terms::Error),
+    /// Bond has no call schedule
+    NotCallable,
+    /// Bond was already called
+    AlreadyCalled(CallNotice),
+    /// Notice period would end at or after maturity
+    NoticePeriodPastMaturity(u64),
+    /// Call schedule doesn't allow calls at the end of the notice period yet
+    CallProtected(u64),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BondNotFound => f.write_str("bond not found"),
+            Self::MalformedTerms(err) => write!(f, "malformed bond terms: {err}"),
+            Self::NotCallable => f.write_str("bond is not callable"),
+            Self::AlreadyCalled(notice) => write!(
+                f,
+                "bond already called for redemption at {} ms",
+                notice.redemption_date_ms
+            ),
+            Self::NoticePeriodPastMaturity(redemption_date_ms) => write!(
+                f,
+                "redemption at {redemption_date_ms} ms would be at or after maturity"
+            ),
+            Self::CallProtected(redemption_date_ms) => write!(
+                f,
+                "bond can't be called for redemption at {redemption_date_ms} ms"
+            ),
+        }
+    }
+}
+
+struct CallBond {
+    /// Authority of the trigger, the issuer of the bond
+    issuer: AccountId,
+    /// Which bond to call
+    bond_id: AssetDefinitionId,
+}
+
+impl CallBond {
+    fn from_metadata(metadata: &Value, issuer: AccountId) -> Self {
+        let bond_id: AssetDefinitionId = metadata
+            .to_owned()
+            .try_into()
+            .dbg_expect("`bond` not of the `AssetDefinitionId` type");
+
+        Self { issuer, bond_id }
+    }
+
+    /// Checks that the bond can be called now:
+    ///
+    /// * The bond is registered with well-formed terms and a call schedule
+    /// * The bond hasn't been called already
+    /// * The notice period ends before maturity, at a time the call schedule allows calls
+    fn validate(&self, notice_time_ms: u64) -> Result<CallNotice, Rejection> {
+        let bond = FindAssetDefinitionById::new(self.bond_id.clone())
+            .execute()
+            .map_err(|_| Rejection::BondNotFound)?;
+        let terms = BondTerms::try_from(bond.metadata()).map_err(Rejection::MalformedTerms)?;
+
+        if !terms.is_callable() {
+            return Err(Rejection::NotCallable);
+        }
+        if let Some(notice) = CallNotice::from_bond_metadata(bond.metadata()) {
+            return Err(Rejection::AlreadyCalled(notice));
+        }
+
+        let redemption_date_ms = terms.call_redemption_date_ms(notice_time_ms);
+        if redemption_date_ms >= terms.maturation_date_ms {
+            return Err(Rejection::NoticePeriodPastMaturity(redemption_date_ms));
+        }
+        let price = terms
+            .call_price_at(redemption_date_ms)
+            .ok_or(Rejection::CallProtected(redemption_date_ms))?;
+
+        Ok(CallNotice {
+            notice_time_ms,
+            redemption_date_ms,
+            price,
+        })
+    }
+
+    /// Records the rejection in the metadata of the `call_bond` trigger
+    fn reject(&self, call_bond_trigger_id: TriggerId, rejection: &Rejection) {
+        let bond_id = &self.bond_id;
+        error!(&format!("{bond_id}: Bond call rejected ({rejection})"));
+
+        let rejection_metadata_id: Name =
+            format!("rejected_call_{}%%{}", bond_id.name(), bond_id.domain_id())
+                .parse()
+                .dbg_expect("INTERNAL BUG: Unable to parse rejection metadata id");
+
+        let mut rejection_metadata = Metadata::new();
+        rejection_metadata
+            .insert_with_limits("bond".parse().unwrap(), bond_id.clone().into(), LIMITS)
+            .unwrap();
+        rejection_metadata
+            .insert_with_limits(
+                "reason".parse().unwrap(),
+                Value::String(rejection.to_string()),
+                LIMITS,
+            )
+            .unwrap();
+
+        SetKeyValueExpr::new(
+            call_bond_trigger_id,
+            rejection_metadata_id,
+            rejection_metadata,
+        )
+        .execute()
+        .dbg_expect("Failed to set bond call rejection to trigger's metadata");
+    }
+
+    /// Registers the trigger redeeming the bonds once the notice period has passed,
+    /// it runs the bond maturation contract
+    fn register_bond_call_trigger(&self, notice: &CallNotice) {
+        const WASM: &[u8] =
+            core::include_bytes!(concat!(core::env!("OUT_DIR"), "/bond_maturation.wasm"));
+
+        let bond_call_trigger_id = bond_call_trigger_id(&self.bond_id);
+        let bond_call_trigger = Trigger::new(
+            bond_call_trigger_id.clone(),
+            Action::new(
+                WasmSmartContract::from_compiled(WASM.to_vec()),
+                Repeats::Exactly(1),
+                self.issuer.clone(),
+                bond_call_filter(notice.redemption_date_ms),
+            ),
+        );
+
+        info!(&format!(
+            "{bond_call_trigger_id}: Registering bond call trigger for {} ms",
+            notice.redemption_date_ms
+        ));
+        RegisterExpr::new(bond_call_trigger).execute().unwrap();
+    }
+
+    fn execute(self, call_bond_trigger_id: TriggerId) {
+        let notice = match self.validate(block_time_ms()) {
+            Ok(notice) => notice,
+            Err(rejection) => {
+                self.reject(call_bond_trigger_id, &rejection);
+                return;
+            }
+        };
+
+        info!(&format!(
+            "{}: Bond called for redemption at {} ms at {}",
+            self.bond_id, notice.redemption_date_ms, notice.price
+        ));
+        SetKeyValueExpr::new(
+            self.bond_id.clone(),
+            CALL_NOTICE_KEY.parse::<Name>().unwrap(),
+            notice,
+        )
+        .execute()
+        .dbg_expect("Failed to set call notice to bond's metadata");
+
+        self.register_bond_call_trigger(&notice);
+    }
+}
+
+#[iroha_trigger::main]
+fn main(id: TriggerId, issuer: AccountId, event: Event) {
+    let call_bond_key = "bond".parse().unwrap();
+
+    // FIXME: Replace with by call trigger with args after migrating to RC22
+    let Event::Data(DataEvent::Trigger(TriggerEvent::MetadataInserted(event))) = event else {
+        dbg_panic(
+            "INTERNAL BUG: Triggering event is not TriggerEvent::MetadataInserted.
+            To avoid this error, register the trigger using a more strict filter",
+        );
+    };
+    if id != *event.target_id() {
+        dbg_panic(
+            "INTERNAL BUG: Triggered by metadata insert event of another trigger.
+            To avoid this error, register the trigger using a more strict filter",
+        );
+    }
+    if *event.key() != call_bond_key {
+        // TODO: Can we filter more precisely to avoid invoking trigger?
+        trace!("Triggered by trigger metadata insert event with another key");
+        return;
+    }
+
+    CallBond::from_metadata(event.value(), issuer).execute(id.clone());
+    RemoveKeyValueExpr::new(id, call_bond_key)
+        .execute()
+        .unwrap();
+}
@@ -6,8 +6,34 @@ extern crate alloc;
 #[cfg(not(test))]
 extern crate panic_halt;
 
-use iroha_executor::{default::default_permission_token_schema, prelude::*, smart_contract};
+use alloc::borrow::ToOwned as _;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use bond_terms::{
+    allotment::{subscription_order_bond_id, SUBSCRIPTION_ORDER_KEY_PREFIX},
+    auction::{auction_bid_bond_id, AUCTION_BID_KEY_PREFIX},
+    floating_rate::REFERENCE_RATE_KEY_PREFIX,
+    inflation::INDEX_KEY_PREFIX,
+    investor::KYC_KEY,
+    put::{put_request_bond_id, PUT_REQUEST_KEY_PREFIX},
+    triggers::bond_trigger_bond_id,
+    Field,
+};
 use dlmalloc::GlobalDlmalloc;
+use iroha_executor::{
+    default::{
+        default_permission_token_schema,
+        tokens::{
+            account::{CanRemoveKeyValueInUserAccount, CanSetKeyValueInUserAccount},
+            asset::CanTransferUserAsset,
+        },
+    },
+    permission::Token as _,
+    prelude::*,
+    smart_contract,
+};
+
+pub mod token;
 
 #[global_allocator]
 static ALLOC: GlobalDlmalloc = GlobalDlmalloc;
@@ -18,23 +44,544 @@ static ALLOC: GlobalDlmalloc = GlobalDlmalloc;
 ///
 /// The defaults are not guaranteed to be stable.
 #[derive(Clone, Constructor, Debug, ValidateEntrypoints, ExpressionEvaluator, Validate, Visit)]
+#[visit(custom(
+    visit_transaction,
+    visit_wasm,
+    visit_register_asset_definition,
+    visit_unregister_asset,
+    visit_mint_asset,
+    visit_burn_asset,
+    visit_transfer_asset,
+    visit_register_account,
+    visit_grant_account_permission,
+    visit_set_account_key_value,
+    visit_remove_account_key_value,
+    visit_register_trigger,
+    visit_set_trigger_key_value,
+    visit_set_asset_definition_key_value,
+    visit_remove_asset_definition_key_value,
+    visit_unregister_asset_definition
+))]
 pub struct Executor {
     verdict: Result,
     block_height: u64,
     host: smart_contract::Host,
 }
 
+/// Set while the instructions of a transaction submitted by a client are visited
+///
+/// Instructions executed by triggers are validated one by one through the
+/// `validate_instruction` entrypoint and never pass through [`visit_transaction`].
+/// This is how the executor tells the bond triggers apart from their authority
+/// acting on its own. It only holds as long as no other trigger can be registered,
+/// see [`visit_register_trigger`].
+static IN_CLIENT_TRANSACTION: AtomicBool = AtomicBool::new(false);
+
+/// Whether the instruction directly manipulates the bond outside of the bond triggers
+fn is_direct_bond_manipulation(
+    executor: &Executor,
+    asset_definition_id: &AssetDefinitionId,
+) -> bool {
+    executor.block_height() != 0
+        && IN_CLIENT_TRANSACTION.load(Ordering::Relaxed)
+        && is_bond(asset_definition_id)
+}
+
+/// Authority the bond triggers are executed as, i.e. that of the `register_bond` trigger
+fn bond_triggers_authority() -> Option<AccountId> {
+    FindTriggerById::new("register_bond".parse().unwrap())
+        .execute()
+        .ok()
+        .map(|trigger| trigger.action().authority().clone())
+}
+
+/// Whether the asset definition carries bond terms
+fn is_bond(asset_definition_id: &AssetDefinitionId) -> bool {
+    FindAssetDefinitionById::new(asset_definition_id.clone())
+        .execute()
+        .map_or(false, |asset_definition| {
+            asset_definition
+                .metadata()
+                .get(Field::Version.key())
+                .is_some()
+        })
+}
+
+/// Whether the authority may unregister or call the asset definition if it's a bond
+fn can_administer(authority: &AccountId, asset_definition_id: &AssetDefinitionId) -> bool {
+    !is_bond(asset_definition_id)
+        || token::CanAdministerBond {
+            asset_definition_id: asset_definition_id.clone(),
+        }
+        .is_owned_by(authority)
+}
+
+/// Bond of the order placed in the account's metadata for `buy_bonds` or `redeem_bonds`
+fn ordered_bond(order: &Value) -> Option<AssetDefinitionId> {
+    let Value::LimitedMetadata(order) = order else {
+        return None;
+    };
+
+    order.get("bond")?.to_owned().try_into().ok()
+}
+
+/// Investor account a permission token needed by the bond triggers' authority is about
+fn investor_of(token: &PermissionToken) -> Option<AccountId> {
+    if let Ok(token) = CanTransferUserAsset::try_from(token) {
+        return Some(token.asset_id.account_id().clone());
+    }
+    if let Ok(token) = CanSetKeyValueInUserAccount::try_from(token) {
+        return Some(token.account_id);
+    }
+    if let Ok(token) = CanRemoveKeyValueInUserAccount::try_from(token) {
+        return Some(token.account_id);
+    }
+
+    None
+}
+
+/// Whether the account carries a KYC record or is yet to be registered
+///
+/// Instructions of a transaction are validated before any of them is executed,
+/// so the account registered by an onboarding transaction doesn't exist yet.
+fn is_onboarded_or_new(account_id: &AccountId) -> bool {
+    FindAccountById::new(account_id.clone())
+        .execute()
+        .map_or(true, |account| account.metadata().get(KYC_KEY).is_some())
+}
+
+fn visit_transaction(
+    executor: &mut Executor,
+    authority: &AccountId,
+    transaction: &SignedTransaction,
+) {
+    IN_CLIENT_TRANSACTION.store(true, Ordering::Relaxed);
+    iroha_executor::default::visit_transaction(executor, authority, transaction);
+    IN_CLIENT_TRANSACTION.store(false, Ordering::Relaxed);
+}
+
+fn visit_wasm(executor: &mut Executor, _authority: &AccountId, _wasm: &WasmSmartContract) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    // Instructions of a WASM transaction are validated like those of a trigger
+    deny!(
+        executor,
+        "WASM transactions are not accepted, submit instructions instead"
+    );
+}
+
+fn visit_register_asset_definition(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: Register<AssetDefinition>,
+) {
+    if executor.block_height() != 0
+        && IN_CLIENT_TRANSACTION.load(Ordering::Relaxed)
+        && isi.object.metadata().get(Field::Version.key()).is_some()
+    {
+        deny!(
+            executor,
+            "Bonds can only be registered through the `register_bond` trigger"
+        );
+    }
+
+    iroha_executor::default::asset_definition::visit_register_asset_definition(
+        executor, authority, isi,
+    );
+}
+
+fn visit_unregister_asset(executor: &mut Executor, authority: &AccountId, isi: Unregister<Asset>) {
+    if is_direct_bond_manipulation(executor, isi.object_id.definition_id()) {
+        deny!(
+            executor,
+            "Bonds can only be unregistered by the bond triggers"
+        );
+    }
+
+    iroha_executor::default::asset::visit_unregister_asset(executor, authority, isi);
+}
+
+fn visit_mint_asset(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: Mint<Asset, NumericValue>,
+) {
+    if is_direct_bond_manipulation(executor, isi.destination_id.definition_id()) {
+        deny!(executor, "Bonds can only be minted by the bond triggers");
+    }
+
+    iroha_executor::default::asset::visit_mint_asset(executor, authority, isi);
+}
+
+fn visit_burn_asset(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: Burn<Asset, NumericValue>,
+) {
+    if is_direct_bond_manipulation(executor, isi.destination_id.definition_id()) {
+        deny!(executor, "Bonds can only be burned by the bond triggers");
+    }
+
+    iroha_executor::default::asset::visit_burn_asset(executor, authority, isi);
+}
+
+fn visit_transfer_asset(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: Transfer<Asset, NumericValue, Account>,
+) {
+    if is_direct_bond_manipulation(executor, isi.source_id.definition_id()) {
+        deny!(
+            executor,
+            "Bonds can only be transferred by the bond triggers, place a `buy_bonds` or `redeem_bonds` order instead"
+        );
+    }
+
+    iroha_executor::default::asset::visit_transfer_asset(executor, authority, isi);
+}
+
+fn visit_register_account(executor: &mut Executor, authority: &AccountId, isi: Register<Account>) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    if isi.object.metadata().get(KYC_KEY).is_some() {
+        if token::CanOnboardInvestor.is_owned_by(authority) {
+            pass!(executor);
+        }
+
+        deny!(
+            executor,
+            "Can't register investors without `CanOnboardInvestor` permission"
+        );
+    }
+
+    iroha_executor::default::account::visit_register_account(executor, authority, isi);
+}
+
+fn visit_grant_account_permission(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: Grant<PermissionToken>,
+) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    // The onboarder grants itself what the bond triggers need over the investor's account
+    if isi.destination_id == *authority && token::CanOnboardInvestor.is_owned_by(authority) {
+        if let Some(investor) = investor_of(&isi.object) {
+            if is_onboarded_or_new(&investor) {
+                pass!(executor);
+            }
+        }
+    }
+
+    iroha_executor::default::permission_token::visit_grant_account_permission(
+        executor, authority, isi,
+    );
+}
+
+fn visit_set_account_key_value(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: SetKeyValue<Account>,
+) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    if isi.key.as_ref() == KYC_KEY && !token::CanOnboardInvestor.is_owned_by(authority) {
+        deny!(
+            executor,
+            "Can't edit KYC records without `CanOnboardInvestor` permission"
+        );
+    }
+    if isi.key.as_ref().starts_with(REFERENCE_RATE_KEY_PREFIX)
+        && !token::CanPublishReferenceRate.is_owned_by(authority)
+    {
+        deny!(
+            executor,
+            "Can't publish reference rates without `CanPublishReferenceRate` permission"
+        );
+    }
+    if isi.key.as_ref().starts_with(INDEX_KEY_PREFIX)
+        && !token::CanPublishInflationIndex.is_owned_by(authority)
+    {
+        deny!(
+            executor,
+            "Can't publish inflation indices without `CanPublishInflationIndex` permission"
+        );
+    }
+    // Put requests are queued and settled by the bond triggers only
+    if isi.key.as_ref().starts_with(PUT_REQUEST_KEY_PREFIX)
+        && !put_request_bond_id(&isi.key).is_some_and(|bond_id| can_administer(authority, &bond_id))
+    {
+        deny!(
+            executor,
+            "Can't edit put requests without `CanAdministerBond` permission"
+        );
+    }
+    // Subscription orders lock funds with the issuer, only the bond triggers can edit them
+    if isi.key.as_ref().starts_with(SUBSCRIPTION_ORDER_KEY_PREFIX)
+        && !subscription_order_bond_id(&isi.key)
+            .is_some_and(|bond_id| can_administer(authority, &bond_id))
+    {
+        deny!(
+            executor,
+            "Can't edit subscription orders without `CanAdministerBond` permission"
+        );
+    }
+    // Sealed bids lock funds with the issuer, only the bond triggers can edit them
+    if isi.key.as_ref().starts_with(AUCTION_BID_KEY_PREFIX)
+        && !auction_bid_bond_id(&isi.key).is_some_and(|bond_id| can_administer(authority, &bond_id))
+    {
+        deny!(
+            executor,
+            "Can't edit auction bids without `CanAdministerBond` permission"
+        );
+    }
+
+    if let Some(asset_definition_id) = ordered_bond(&isi.value) {
+        let permitted = match isi.key.as_ref() {
+            "buy_bonds" => token::CanBuyBond {
+                asset_definition_id,
+            }
+            .is_owned_by(authority),
+            "redeem_bonds" => token::CanRedeemBond {
+                asset_definition_id,
+            }
+            .is_owned_by(authority),
+            _ => true,
+        };
+        if !permitted {
+            deny!(
+                executor,
+                "Can't place bond orders without `CanBuyBond`/`CanRedeemBond` permission"
+            );
+        }
+    }
+
+    iroha_executor::default::account::visit_set_account_key_value(executor, authority, isi);
+}
+
+fn visit_remove_account_key_value(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: RemoveKeyValue<Account>,
+) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    if isi.key.as_ref() == KYC_KEY && !token::CanOnboardInvestor.is_owned_by(authority) {
+        deny!(
+            executor,
+            "Can't edit KYC records without `CanOnboardInvestor` permission"
+        );
+    }
+    if isi.key.as_ref().starts_with(REFERENCE_RATE_KEY_PREFIX)
+        && !token::CanPublishReferenceRate.is_owned_by(authority)
+    {
+        deny!(
+            executor,
+            "Can't publish reference rates without `CanPublishReferenceRate` permission"
+        );
+    }
+    if isi.key.as_ref().starts_with(INDEX_KEY_PREFIX)
+        && !token::CanPublishInflationIndex.is_owned_by(authority)
+    {
+        deny!(
+            executor,
+            "Can't publish inflation indices without `CanPublishInflationIndex` permission"
+        );
+    }
+    // Put requests are queued and settled by the bond triggers only
+    if isi.key.as_ref().starts_with(PUT_REQUEST_KEY_PREFIX)
+        && !put_request_bond_id(&isi.key).is_some_and(|bond_id| can_administer(authority, &bond_id))
+    {
+        deny!(
+            executor,
+            "Can't edit put requests without `CanAdministerBond` permission"
+        );
+    }
+    // Subscription orders lock funds with the issuer, only the bond triggers can edit them
+    if isi.key.as_ref().starts_with(SUBSCRIPTION_ORDER_KEY_PREFIX)
+        && !subscription_order_bond_id(&isi.key)
+            .is_some_and(|bond_id| can_administer(authority, &bond_id))
+    {
+        deny!(
+            executor,
+            "Can't edit subscription orders without `CanAdministerBond` permission"
+        );
+    }
+    // Sealed bids lock funds with the issuer, only the bond triggers can edit them
+    if isi.key.as_ref().starts_with(AUCTION_BID_KEY_PREFIX)
+        && !auction_bid_bond_id(&isi.key).is_some_and(|bond_id| can_administer(authority, &bond_id))
+    {
+        deny!(
+            executor,
+            "Can't edit auction bids without `CanAdministerBond` permission"
+        );
+    }
+
+    iroha_executor::default::account::visit_remove_account_key_value(executor, authority, isi);
+}
+
+fn visit_register_trigger(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: Register<Trigger<TriggeringFilterBox, Executable>>,
+) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    // Instructions of any trigger pass the bond guards, so triggers can only be registered
+    // by the bond triggers, as their own per-bond triggers executed as themselves
+    if IN_CLIENT_TRANSACTION.load(Ordering::Relaxed) {
+        deny!(
+            executor,
+            "Triggers can only be registered in genesis or by the bond triggers"
+        );
+    }
+    let Some(bond_triggers_authority) = bond_triggers_authority() else {
+        deny!(
+            executor,
+            "Triggers can't be registered without the `register_bond` trigger"
+        );
+    };
+    if *authority != bond_triggers_authority
+        || *isi.object.action().authority() != bond_triggers_authority
+    {
+        deny!(
+            executor,
+            "Triggers can only be registered by the bond triggers' authority"
+        );
+    }
+    if !bond_trigger_bond_id(isi.object.id()).is_some_and(|bond_id| is_bond(&bond_id)) {
+        deny!(
+            executor,
+            "Only the per-bond triggers of registered bonds can be registered"
+        );
+    }
+
+    iroha_executor::default::trigger::visit_register_trigger(executor, authority, isi);
+}
+
+fn visit_set_trigger_key_value(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: SetKeyValue<Trigger<TriggeringFilterBox, Executable>>,
+) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    if isi.object_id.name().as_ref() == "register_bond"
+        && isi.key.as_ref() == "bond"
+        && !token::CanIssueBond.is_owned_by(authority)
+    {
+        deny!(
+            executor,
+            "Can't issue bonds without `CanIssueBond` permission"
+        );
+    }
+    if isi.object_id.name().as_ref() == "call_bond" && isi.key.as_ref() == "bond" {
+        let called_bond = AssetDefinitionId::try_from(isi.value.clone()).ok();
+        if !called_bond.is_some_and(|bond_id| can_administer(authority, &bond_id)) {
+            deny!(
+                executor,
+                "Can't call bonds without `CanAdministerBond` permission"
+            );
+        }
+    }
+
+    iroha_executor::default::trigger::visit_set_trigger_key_value(executor, authority, isi);
+}
+
+fn visit_set_asset_definition_key_value(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: SetKeyValue<AssetDefinition>,
+) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    if is_direct_bond_manipulation(executor, &isi.object_id) {
+        deny!(
+            executor,
+            "Bond terms can only be edited by the bond triggers"
+        );
+    }
+
+    iroha_executor::default::asset_definition::visit_set_asset_definition_key_value(
+        executor, authority, isi,
+    );
+}
+
+fn visit_remove_asset_definition_key_value(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: RemoveKeyValue<AssetDefinition>,
+) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    if is_direct_bond_manipulation(executor, &isi.object_id) {
+        deny!(
+            executor,
+            "Bond terms can only be edited by the bond triggers"
+        );
+    }
+
+    iroha_executor::default::asset_definition::visit_remove_asset_definition_key_value(
+        executor, authority, isi,
+    );
+}
+
+fn visit_unregister_asset_definition(
+    executor: &mut Executor,
+    authority: &AccountId,
+    isi: Unregister<AssetDefinition>,
+) {
+    if executor.block_height() == 0 {
+        pass!(executor);
+    }
+
+    if !can_administer(authority, &isi.object_id) {
+        deny!(
+            executor,
+            "Can't unregister bond without `CanAdministerBond` permission"
+        );
+    }
+
+    iroha_executor::default::asset_definition::visit_unregister_asset_definition(
+        executor, authority, isi,
+    );
+}
+
 /// Migrate previous executor to the current version.
 /// Called by Iroha once just before upgrading executor.
 #[entrypoint]
 pub fn migrate(_block_height: u64) -> MigrationResult {
-    let schema = default_permission_token_schema();
-    let (token_ids, schema_str) = schema.serialize();
+    let mut schema = default_permission_token_schema();
+    schema.insert::<token::CanIssueBond>();
+    schema.insert::<token::CanOnboardInvestor>();
+    schema.insert::<token::CanPublishReferenceRate>();
+    schema.insert::<token::CanPublishInflationIndex>();
+    schema.insert::<token::CanBuyBond>();
+    schema.insert::<token::CanRedeemBond>();
+    schema.insert::<token::CanAdministerBond>();
 
+    let (token_ids, schema_str) = schema.serialize();
     iroha_executor::set_permission_token_schema(
         &iroha_executor::data_model::permission::PermissionTokenSchema::new(token_ids, schema_str),
     );
 
     Ok(())
 }
-
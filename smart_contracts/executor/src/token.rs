@@ -0,0 +1,46 @@
+//! Permission tokens for bond operations
+
+use iroha_executor::{permission, prelude::*};
+use iroha_schema::IntoSchema;
+use serde::{Deserialize, Serialize};
+
+/// Allows submitting new bonds to the `register_bond` trigger
+#[derive(Token, ValidateGrantRevoke, Clone, Copy, Debug, Serialize, Deserialize, IntoSchema)]
+#[validate(permission::OnlyGenesis)]
+pub struct CanIssueBond;
+
+/// Allows registering investor accounts together with the grants the bond triggers need
+#[derive(Token, ValidateGrantRevoke, Clone, Copy, Debug, Serialize, Deserialize, IntoSchema)]
+#[validate(permission::OnlyGenesis)]
+pub struct CanOnboardInvestor;
+
+/// Allows publishing reference rates floating-rate notes are fixed to
+#[derive(Token, ValidateGrantRevoke, Clone, Copy, Debug, Serialize, Deserialize, IntoSchema)]
+#[validate(permission::OnlyGenesis)]
+pub struct CanPublishReferenceRate;
+
+/// Allows publishing inflation indices the principal of inflation-linked bonds is scaled by
+#[derive(Token, ValidateGrantRevoke, Clone, Copy, Debug, Serialize, Deserialize, IntoSchema)]
+#[validate(permission::OnlyGenesis)]
+pub struct CanPublishInflationIndex;
+
+/// Allows placing buy orders for the bond
+#[derive(Token, ValidateGrantRevoke, Clone, Debug, Serialize, Deserialize, IntoSchema)]
+#[validate(permission::asset_definition::Owner)]
+pub struct CanBuyBond {
+    pub asset_definition_id: AssetDefinitionId,
+}
+
+/// Allows placing redeem orders for the bond
+#[derive(Token, ValidateGrantRevoke, Clone, Debug, Serialize, Deserialize, IntoSchema)]
+#[validate(permission::asset_definition::Owner)]
+pub struct CanRedeemBond {
+    pub asset_definition_id: AssetDefinitionId,
+}
+
+/// Allows unregistering the bond
+#[derive(Token, ValidateGrantRevoke, Clone, Debug, Serialize, Deserialize, IntoSchema)]
+#[validate(permission::asset_definition::Owner)]
+pub struct CanAdministerBond {
+    pub asset_definition_id: AssetDefinitionId,
+}
@@ -5,25 +5,38 @@ extern crate alloc;
 #[cfg(not(test))]
 extern crate panic_halt;
 
-use alloc::{borrow::ToOwned as _, format};
+use alloc::{borrow::ToOwned as _, format, string::ToString as _};
+use bond_terms::{
+    floating_rate::{published_reference_rate, FloatingRate, RateFixing, RATE_FIXING_KEY},
+    host::next_payment_idx,
+    inflation::find_index_ratio,
+    money::{residual_key, Decimal},
+    BondTerms,
+};
 use dlmalloc::GlobalDlmalloc;
 use iroha_trigger::data_model::query::account::model::FindAccountById;
-use iroha_trigger::log::{info, trace};
+use iroha_trigger::log::{error, info, trace};
 use iroha_trigger::{data_model::prelude::*, debug::dbg_panic};
 
 #[global_allocator]
 static ALLOC: GlobalDlmalloc = GlobalDlmalloc;
 
 const LIMITS: MetadataLimits = MetadataLimits::new(256, 256);
-const ONE_YEAR_IN_SECONDS: u64 = 31_536_000;
 
 #[iroha_trigger::main]
 fn main(id: TriggerId, issuer: AccountId, event: Event) {
-    let bond_id: AssetDefinitionId = id
+    // Triggers of bonds with a coupon schedule pay a single coupon, whose number follows the suffix
+    let (bond_id, coupon_number) = id
         .name()
         .as_ref()
-        .strip_suffix("%%interest_payments")
-        .dbg_expect("INTERNAL BUG: Trigger name must end with `%%interest_payments`")
+        .split_once("%%interest_payments")
+        .dbg_expect("INTERNAL BUG: Trigger name must contain `%%interest_payments`");
+    let coupon_number: Option<usize> = coupon_number.strip_prefix("%%").map(|number| {
+        number
+            .parse()
+            .dbg_expect("INTERNAL BUG: Unable to parse coupon number from trigger name suffix")
+    });
+    let bond_id: AssetDefinitionId = bond_id
         .replace("%%", "#")
         .parse()
         .dbg_expect(
@@ -31,12 +44,17 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
             Prefix trigger name with the id of the bond it's registered for",
         );
 
-    if !matches!(event, Event::Time(_)) {
+    let Event::Time(event) = event else {
         dbg_panic(
             "INTERNAL BUG: Triggering event is not TimeEvent.
             To avoid this error, register the trigger using the correct filter",
         );
-    }
+    };
+    let interval = event.interval();
+    let now_ms: u64 = (*interval.since() + *interval.length())
+        .as_millis()
+        .try_into()
+        .dbg_expect("INTERNAL BUG: Block time overflow");
 
     let bond = FindAssetDefinitionById::new(bond_id.clone())
         .execute()
@@ -45,48 +63,55 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
         .execute()
         .dbg_expect(&format!("{bond_id}: Bond not found"));
 
-    // WARN: Coupon rate can be changed by the issuer after the bond is issued (not required, for now)
-    let yearly_coupon_rate: Fixed = bond
-        .metadata()
-        .get(&"coupon_rate".parse::<Name>().unwrap())
-        .dbg_expect("INTERNAL BUG: bond missing `coupon_rate`")
-        .to_owned()
-        .try_into()
-        .dbg_expect("`coupon_rate` not of the `NumericValue::Fixed` type");
+    let terms = BondTerms::try_from(bond.metadata())
+        .dbg_expect(&format!("{bond_id}: Invalid bond terms"));
 
-    let payment_frequency_seconds: u64 = bond
-        .metadata()
-        .get(&"payment_frequency_seconds".parse::<Name>().unwrap())
-        .dbg_expect("INTERNAL BUG: bond missing `payment_frequency_seconds`")
-        .to_owned()
-        .try_into()
-        .dbg_expect("`payment_frequency_seconds` not of the `u64` type");
-    let payment_frequency = Fixed::try_from(payment_frequency_seconds as f64)
-        .dbg_expect("Payment frequency overflow");
-
-    let payment_fraction = payment_frequency
-        .checked_div(Fixed::try_from(ONE_YEAR_IN_SECONDS as f64).unwrap())
-        .dbg_expect("Payment fraction overflow");
-
-    let current_coupon_payment_fraction = yearly_coupon_rate
-        .checked_mul(payment_fraction)
-        .expect("Coupon payment overflow");
-
-    let nominal_value: Fixed = bond
-        .metadata()
-        .get(&"nominal_value".parse::<Name>().unwrap())
-        .dbg_expect("INTERNAL BUG: bond missing `nominal_value`")
-        .to_owned()
-        .try_into()
-        .dbg_expect("`nominal_value` not of the `NumericValue::Fixed` type");
+    if !terms.bond_type.pays_coupons() {
+        trace!(&format!("{bond_id}: Bond pays no coupons, skipping coupon payments"));
 
-    let currency: AssetDefinitionId = bond
-        .metadata()
-        .get("currency")
-        .dbg_expect("Currency not found")
-        .to_owned()
-        .try_into()
-        .dbg_expect("`currency` not of the `AssetDefinitionId` type");
+        return;
+    }
+
+    let period = match coupon_number {
+        Some(n) => terms.scheduled_coupon_period(n),
+        None => terms.coupon_period_ended_by(now_ms),
+    };
+    let Some(period) = period else {
+        trace!(&format!("{bond_id}: No coupon period has ended, skipping coupon payments"));
+
+        return;
+    };
+    // Holdings are unregistered at maturation, so the final coupon is paid with the principal
+    if period.end_ms >= terms.maturation_date_ms {
+        trace!(&format!("{bond_id}: Final coupon is paid at maturation, skipping coupon payments"));
+
+        return;
+    }
+
+    // Floating-rate notes pay at the rate fixed when the period started
+    let fixing = terms.floating_rate.as_ref().map(|floating_rate| {
+        match RateFixing::from_bond_metadata(bond.metadata()) {
+            Some(fixing) if fixing.period_start_ms == period.start_ms => fixing,
+            _ => {
+                error!(&format!(
+                    "{bond_id}: Rate of the period starting at {} ms was not fixed, fixing it now",
+                    period.start_ms
+                ));
+                fix_rate(&bond_id, floating_rate, period.start_ms)
+            }
+        }
+    });
+    let coupon_rate = terms
+        .current_coupon_rate(fixing.as_ref())
+        .dbg_expect("INTERNAL BUG: Coupon rate not fixed");
+    // Coupons of inflation-linked bonds apply to the principal scaled by the current index ratio
+    let index_ratio = terms
+        .inflation_index
+        .as_ref()
+        .map(|inflation_index| find_index_ratio(&bond_id, &terms, inflation_index));
+
+    let currency = terms.currency.clone();
+    let residual_key = residual_key(&bond_id);
 
     for issued_bond in issued_bonds {
         let buyer = issued_bond.id().account_id().clone();
@@ -103,10 +128,21 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
             .to_owned()
             .try_into()
             .dbg_expect("INTERNAL BUG: bond quantity is not of the `u32` type");
-        let amount = Fixed::try_from(quantity as f64)
-            .and_then(|qty| qty.checked_mul(nominal_value))
-            .and_then(|qty| qty.checked_mul(current_coupon_payment_fraction))
-            .dbg_expect("Bond total price overflow");
+        // Residual left over from rounding previous coupons is paid out once it adds up
+        let coupon = terms
+            .coupon_amount(quantity, period, coupon_rate)
+            .and_then(|amount| terms.index_adjusted(amount, index_ratio))
+            .and_then(|amount| amount.checked_add(find_residual(&buyer, &residual_key)))
+            .map(|amount| terms.round(amount))
+            .dbg_expect("Coupon payment overflow");
+        let amount = coupon
+            .amount
+            .to_fixed()
+            .dbg_expect("Coupon payment overflow");
+        let residual = coupon
+            .residual
+            .to_fixed()
+            .dbg_expect("Coupon payment overflow");
 
         trace!(&format!(
                 "{bond_id}: Transferring {amount} {issuer_money} from {issuer} to {buyer}"
@@ -116,7 +152,11 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
             .execute()
             .dbg_expect("Failed to pay bond interest");
 
-        let coupon_payment_idx = find_coupon_payment_idx(&buyer);
+        SetKeyValueExpr::new(buyer.clone(), residual_key.clone(), residual.clone())
+            .execute()
+            .dbg_expect("Failed to set coupon residual to buyer's metadata");
+
+        let coupon_payment_idx = next_payment_idx(&buyer, "coupon_payment_idx");
         trace!(&format!("{bond_id}: index of coupon payment: {coupon_payment_idx}"));
 
         let transfer_metadata_id: Name = format!(
@@ -131,12 +171,60 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
         transfer_metadata
             .insert_with_limits("amount".parse().unwrap(), amount.into(), LIMITS)
             .unwrap();
+        transfer_metadata
+            .insert_with_limits("residual".parse().unwrap(), residual.into(), LIMITS)
+            .unwrap();
         transfer_metadata
             .insert_with_limits("currency".parse().unwrap(), issuer_money.into(), LIMITS)
             .unwrap();
         transfer_metadata
             .insert_with_limits("bond_asset_id".parse().unwrap(), bond_id.clone().into(), LIMITS)
             .unwrap();
+        transfer_metadata
+            .insert_with_limits("period_start_ms".parse().unwrap(), period.start_ms.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits("period_end_ms".parse().unwrap(), period.end_ms.into(), LIMITS)
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits(
+                "day_count_convention".parse().unwrap(),
+                Value::String(terms.day_count.to_string()),
+                LIMITS,
+            )
+            .unwrap();
+        transfer_metadata
+            .insert_with_limits(
+                "coupon_rate".parse().unwrap(),
+                coupon_rate.to_fixed().dbg_expect("Coupon rate overflow").into(),
+                LIMITS,
+            )
+            .unwrap();
+        if let Some(fixing) = fixing {
+            transfer_metadata
+                .insert_with_limits(
+                    "reference_rate".parse().unwrap(),
+                    fixing.reference_rate.to_fixed().dbg_expect("Reference rate overflow").into(),
+                    LIMITS,
+                )
+                .unwrap();
+        }
+        if let Some(index_ratio) = index_ratio {
+            transfer_metadata
+                .insert_with_limits(
+                    "index_value".parse().unwrap(),
+                    index_ratio.index_value.to_fixed().dbg_expect("Index value overflow").into(),
+                    LIMITS,
+                )
+                .unwrap();
+            transfer_metadata
+                .insert_with_limits(
+                    "index_ratio".parse().unwrap(),
+                    index_ratio.ratio.to_fixed().dbg_expect("Index ratio overflow").into(),
+                    LIMITS,
+                )
+                .unwrap();
+        }
 
         SetKeyValueExpr::new(buyer, transfer_metadata_id, transfer_metadata)
             .execute()
@@ -149,29 +237,61 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
         );
     }
 
-    fn find_coupon_payment_idx(buyer: &AccountId) -> u32 {
-        let coupon_payment_idx_key: Name = "coupon_payment_idx"
-            .parse()
-            .dbg_expect("INTERNAL BUG: Unable to parse coupon payment index key");
+    if let Some(floating_rate) = &terms.floating_rate {
+        if period.end_ms < terms.maturation_date_ms {
+            fix_rate(&bond_id, floating_rate, period.end_ms);
+        }
+    }
 
-        let current_idx = FindAccountById::new(buyer.clone())
+    /// Fixes the rate of the period starting at the given time to the currently published one
+    fn fix_rate(
+        bond_id: &AssetDefinitionId,
+        floating_rate: &FloatingRate,
+        period_start_ms: u64,
+    ) -> RateFixing {
+        let oracle = FindAccountById::new(floating_rate.oracle.clone())
             .execute()
-            .dbg_expect("INTERNAL BUG: Account not found")
-            .metadata()
-            .get(&coupon_payment_idx_key)
-            .map(|idx| {
-                idx.to_owned()
-                    .try_into()
-                    .dbg_expect("INTERNAL BUG: `coupon_payment_idx` not of the `u32` type")
-            })
-            .unwrap_or(0_u32);
+            .dbg_expect(&format!("{bond_id}: Rate oracle not found"));
+        let reference_rate = published_reference_rate(&oracle, &floating_rate.reference_rate)
+            .dbg_expect(&format!(
+                "{bond_id}: Reference rate {} not published",
+                floating_rate.reference_rate
+            ));
+        let fixing = floating_rate
+            .fix(period_start_ms, reference_rate)
+            .dbg_expect("Coupon rate overflow");
 
-        let new_idx = current_idx + 1;
+        info!(&format!(
+            "{bond_id}: Fixed coupon rate {} for the period starting at {period_start_ms} ms",
+            fixing.coupon_rate
+        ));
+        SetKeyValueExpr::new(
+            bond_id.clone(),
+            RATE_FIXING_KEY
+                .parse::<Name>()
+                .dbg_expect("INTERNAL BUG: Unable to parse rate fixing key"),
+            fixing,
+        )
+        .execute()
+        .dbg_expect("Failed to set rate fixing to bond's metadata");
 
-        SetKeyValueExpr::new(buyer.clone(), coupon_payment_idx_key, Value::Numeric(new_idx.into()))
+        fixing
+    }
+
+    fn find_residual(buyer: &AccountId, residual_key: &Name) -> Decimal {
+        FindAccountById::new(buyer.clone())
             .execute()
-            .dbg_expect("Failed to set coupon payment index to buyer's metadata");
+            .dbg_expect("INTERNAL BUG: Account not found")
+            .metadata()
+            .get(residual_key)
+            .map_or(Decimal::ZERO, |residual| {
+                let residual: Fixed = residual
+                    .to_owned()
+                    .try_into()
+                    .dbg_expect("INTERNAL BUG: coupon residual not of the `Fixed` type");
 
-        new_idx
+                Decimal::from_fixed(residual)
+                    .dbg_expect("INTERNAL BUG: coupon residual out of range")
+            })
     }
 }
@@ -1,4 +1,4 @@
-//! Smart contract for redeeming bonds
+//! Smart contract for requesting the early redemption of puttable bonds
 #![no_std]
 
 extern crate alloc;
@@ -9,6 +9,12 @@ use core::num::NonZeroU32;
 
 use alloc::{borrow::ToOwned as _, format};
 
+use bond_terms::{
+    call::CallNotice,
+    host::block_time_ms,
+    put::{put_request_key, PutRequest},
+    BondTerms,
+};
 use dlmalloc::GlobalDlmalloc;
 use iroha_trigger::{
     data_model::prelude::*,
@@ -22,11 +28,7 @@ use iroha_trigger::log::info;
 #[global_allocator]
 static ALLOC: GlobalDlmalloc = GlobalDlmalloc;
 
-const LIMITS: MetadataLimits = MetadataLimits::new(256, 256);
-
 struct RedeemBondsOrder {
-    /// Who's buying back the bonds
-    issuer: AccountId,
     /// Who's selling the bond
     seller: AccountId,
     /// Which bond to redeem
@@ -36,7 +38,7 @@ struct RedeemBondsOrder {
 }
 
 impl RedeemBondsOrder {
-    fn from_metadata(metadata: &Metadata, issuer: AccountId, seller: AccountId) -> Self {
+    fn from_metadata(metadata: &Metadata, seller: AccountId) -> Self {
         let bond_id: AssetDefinitionId = metadata
             .get("bond")
             .dbg_expect("Bond asset definition not found")
@@ -55,7 +57,6 @@ impl RedeemBondsOrder {
             .dbg_expect(&format!("{bond_id}: asset definition not found"));
 
         Self {
-            issuer,
             seller,
             bond,
             quantity: NonZeroU32::new(quantity).dbg_expect("Bond quantity is zero"),
@@ -86,108 +87,81 @@ impl RedeemBondsOrder {
         }
     }
 
-    fn find_redeem_bond_payment_idx(buyer: &AccountId) -> u32 {
-        let buy_bond_payment_idx_key: Name = "redeem_bond_payment_idx"
-            .parse()
-            .dbg_expect("INTERNAL BUG: Unable to parse redeem bond payment index key");
-
-        let current_idx = FindAccountById::new(buyer.clone())
-            .execute()
-            .dbg_expect("INTERNAL BUG: Account not found")
-            .metadata()
-            .get(&buy_bond_payment_idx_key)
-            .map(|idx| {
-                idx.to_owned()
-                    .try_into()
-                    .dbg_expect("INTERNAL BUG: `redeem_bond_payment_idx` not of the `u32` type")
-            })
-            .unwrap_or(0_u32);
-
-        let new_idx = current_idx + 1;
-
-        SetKeyValueExpr::new(buyer.clone(), buy_bond_payment_idx_key, Value::Numeric(new_idx.into()))
-            .execute()
-            .dbg_expect("Failed to set redeem bond payment index to seller's metadata");
-
-        new_idx
-    }
-
+    /// Queues the put until the settlement of the open put window
+    ///
+    /// Bonds are redeemed by the put settlement trigger of the window at the put price, so the
+    /// order is only accepted while a put window of a puttable bond is open. Orders placed in
+    /// the same window add up to a single request.
     fn execute(self) {
-        let bond_currency: AssetDefinitionId = self
-            .bond
-            .metadata()
-            .get("currency")
-            .dbg_expect("Currency not found")
-            .to_owned()
-            .try_into()
-            .dbg_expect("`currency` not of the `AssetDefinitionId` type");
-
-        let bond_nominal_value: Fixed = self
-            .bond
-            .metadata()
-            .get("nominal_value")
-            .dbg_expect("Nominal value not found")
-            .to_owned()
-            .try_into()
-            .dbg_expect("`nominal_value` not of the `NumericValue::Fixed` type");
-
-        let bonds_total_price = Fixed::try_from(self.quantity.get() as f64)
-            .and_then(|qty| qty.checked_mul(bond_nominal_value))
-            .dbg_expect("Bond total price overflow");
-
-        let bond_seller_bonds = AssetId::new(self.bond.id().clone(), self.seller.clone());
-        let bond_issuer_money = AssetId::new(bond_currency.clone(), self.issuer.clone());
+        let bond_id = self.bond.id();
+        let terms = BondTerms::try_from(self.bond.metadata())
+            .dbg_expect(&format!("{bond_id}: Invalid bond terms"));
+        if !terms.is_puttable() {
+            error!(&format!("{bond_id}: Bond is not puttable"));
+            return;
+        }
 
-        if !Self::check_account_asset_amount(&bond_issuer_money, bonds_total_price.into()) {
+        let request_time_ms = block_time_ms();
+        let Some(window) = terms.put_schedule.window_at(request_time_ms) else {
+            error!(&format!("{bond_id}: No put window open at {request_time_ms} ms"));
             return;
+        };
+        let settlement_date_ms = terms
+            .put_schedule
+            .settlement_date_ms(window)
+            .dbg_expect("INTERNAL BUG: Put window not in the put schedule");
+        // Called bonds are redeemed by the call, puts settled after it would find no bonds
+        if let Some(notice) = CallNotice::from_bond_metadata(self.bond.metadata()) {
+            if settlement_date_ms >= notice.redemption_date_ms {
+                error!(&format!(
+                    "{bond_id}: Bond called for redemption at {} ms, before the put settlement",
+                    notice.redemption_date_ms
+                ));
+                return;
+            }
         }
-        if !Self::check_account_asset_amount(&bond_seller_bonds, self.quantity.get().into()) {
+
+        let seller = FindAccountById::new(self.seller.clone())
+            .execute()
+            .dbg_expect("INTERNAL BUG: Account not found");
+        let queued_quantity = match PutRequest::from_holder_metadata(seller.metadata(), bond_id) {
+            None => 0,
+            Some(request) if request.window as usize == window => request.quantity,
+            Some(request) => {
+                error!(&format!(
+                    "{bond_id}: Put requested in window {} not settled yet",
+                    request.window
+                ));
+                return;
+            }
+        };
+        let Some(quantity) = queued_quantity.checked_add(self.quantity.get()) else {
+            error!("Put quantity overflow");
+            return;
+        };
+
+        let bond_seller_bonds = AssetId::new(bond_id.clone(), self.seller.clone());
+        if !Self::check_account_asset_amount(&bond_seller_bonds, quantity.into()) {
             return;
         }
 
-        let redeem_bond_payment_idx = Self::find_redeem_bond_payment_idx(&self.seller);
-        let transfer_metadata_id: Name = format!(
-            "redeem_bond_payment_{}%%{}%%idx%%{}",
-            self.bond.id().name().to_owned(),
-            self.bond.id().domain_id().to_owned(),
-            redeem_bond_payment_idx.to_owned())
-            .parse()
-            .dbg_expect("INTERNAL BUG: Unable to parse transfer metadata id");
-
-        let mut transfer_metadata = Metadata::new();
-        transfer_metadata
-            .insert_with_limits("amount".parse().unwrap(), bonds_total_price.into(), LIMITS)
-            .unwrap();
-        transfer_metadata
-            .insert_with_limits("quantity".parse().unwrap(), self.quantity.get().into(), LIMITS)
-            .unwrap();
-        transfer_metadata
-            .insert_with_limits("currency".parse().unwrap(), bond_currency.into(), LIMITS)
-            .unwrap();
-        transfer_metadata
-            .insert_with_limits("bond_asset_id".parse().unwrap(), self.bond.id().clone().into(), LIMITS)
-            .unwrap();
-
-        let issuer = self.issuer.clone();
-        let seller = self.seller.clone();
+        let request = PutRequest {
+            window: window as u32,
+            quantity,
+            request_time_ms,
+        };
         info!(&format!(
-                "Transferring {bonds_total_price} {bond_issuer_money} from {issuer} to {seller}"
-            ));
-
-        TransferExpr::new(bond_issuer_money.clone(), bonds_total_price, self.seller.clone())
-            .execute()
-            .dbg_expect("Sending money failed");
-        BurnExpr::new(self.quantity.get(), bond_seller_bonds)
-            .execute()
-            .dbg_expect("Burning bonds failed");
-        SetKeyValueExpr::new(self.seller, transfer_metadata_id, transfer_metadata)
+            "{bond_id}: Queueing put of {quantity} bonds of {} until {settlement_date_ms} ms",
+            self.seller
+        ));
+        SetKeyValueExpr::new(self.seller, put_request_key(bond_id), request)
             .execute()
-            .dbg_expect("Failed to set redeem bond info to sellers's metadata");
+            .dbg_expect("Failed to set put request to seller's metadata");
     }
 }
 
 #[iroha_trigger::main]
-fn main(_id: TriggerId, issuer: AccountId, event: Event) {
+fn main(_id: TriggerId, _issuer: AccountId, event: Event) {
     let redeem_bonds_key = "redeem_bonds".parse().unwrap();
 
     let Event::Data(DataEvent::Account(AccountEvent::MetadataInserted(event))) = event else {
@@ -207,7 +181,7 @@ fn main(_id: TriggerId, issuer: AccountId, event: Event) {
     };
 
     let seller = event.target_id().clone();
-    RedeemBondsOrder::from_metadata(metadata, issuer, seller.clone()).execute();
+    RedeemBondsOrder::from_metadata(metadata, seller.clone()).execute();
     RemoveKeyValueExpr::new(seller, redeem_bonds_key)
         .execute()
         .dbg_unwrap();
@@ -5,20 +5,91 @@ extern crate alloc;
 #[cfg(not(test))]
 extern crate panic_halt;
 
-use alloc::{borrow::ToOwned as _, format};
-use core::time::Duration;
+use alloc::{borrow::ToOwned as _, format, string::ToString as _};
+use core::fmt;
 
+use bond_terms::{
+    floating_rate::{published_reference_rate, RateFixing, RATE_FIXING_KEY},
+    inflation::published_index_value,
+    triggers::{
+        bond_maturation_filter, bond_maturation_trigger_id, coupon_payment_filter,
+        coupon_payment_trigger_id, interest_payments_filter, interest_payments_trigger_id,
+        principal_repayment_filter, principal_repayment_trigger_id, put_settlement_filter,
+        put_settlement_trigger_id, subscription_close_filter, subscription_close_trigger_id,
+    },
+    BondTerms, InvalidTerms,
+};
 use dlmalloc::GlobalDlmalloc;
+use iroha_trigger::data_model::{metadata::MetadataError, query::account::model::FindAccountById};
 use iroha_trigger::{
     data_model::prelude::*,
     debug::{dbg_panic, DebugExpectExt as _},
-    log::{info, trace},
+    log::{error, info, trace},
     prelude::*,
 };
 
 #[global_allocator]
 static ALLOC: GlobalDlmalloc = GlobalDlmalloc;
 
+/// Entries are bounded by the peer's `max_entry_byte_size` for trigger metadata
+const LIMITS: MetadataLimits = MetadataLimits::new(256, 4096);
+
+/// Reason why a bond was not registered
+enum Rejection {
+    /// Value of the `bond` key is not a new asset definition
+    MalformedBond,
+    /// Bond terms couldn't be read from the asset definition metadata
+    MalformedTerms(bond_terms::Error),
+    /// Bond terms were read but don't describe a valid bond
+    InvalidTerms(InvalidTerms),
+    /// Bond with the same id is already registered
+    AlreadyRegistered,
+    /// Bond currency is not a registered asset definition
+    CurrencyNotFound(AssetDefinitionId),
+    /// Fee recipient is not a registered account
+    FeeRecipientNotFound(AccountId),
+    /// Oracle of the reference rate is not a registered account
+    RateOracleNotFound(AccountId),
+    /// Oracle hasn't published the reference rate the first coupon rate is fixed to
+    ReferenceRateNotPublished(Name),
+    /// Coupon rate fixed to the published reference rate is out of range
+    CouponRateOverflow(Name),
+    /// Oracle of the inflation index is not a registered account
+    IndexOracleNotFound(AccountId),
+    /// Oracle hasn't published the inflation index the principal is scaled by
+    InflationIndexNotPublished(Name),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedBond => f.write_str("`bond` not of the `NewAssetDefinition` type"),
+            Self::MalformedTerms(err) => write!(f, "malformed bond terms: {err}"),
+            Self::InvalidTerms(err) => write!(f, "invalid bond terms: {err}"),
+            Self::AlreadyRegistered => f.write_str("bond already registered"),
+            Self::CurrencyNotFound(currency) => write!(f, "{currency}: currency not found"),
+            Self::FeeRecipientNotFound(account) => {
+                write!(f, "{account}: fee recipient account not found")
+            }
+            Self::RateOracleNotFound(account) => {
+                write!(f, "{account}: rate oracle account not found")
+            }
+            Self::ReferenceRateNotPublished(reference_rate) => {
+                write!(f, "{reference_rate}: reference rate not published")
+            }
+            Self::CouponRateOverflow(reference_rate) => {
+                write!(f, "{reference_rate}: coupon rate out of range")
+            }
+            Self::IndexOracleNotFound(account) => {
+                write!(f, "{account}: index oracle account not found")
+            }
+            Self::InflationIndexNotPublished(index) => {
+                write!(f, "{index}: inflation index not published")
+            }
+        }
+    }
+}
+
 struct RegisterBond {
     /// Authority issuing the bond
     issuer: AccountId,
@@ -27,108 +98,177 @@ struct RegisterBond {
 }
 
 impl RegisterBond {
-    fn from_metadata(metadata: &Value, issuer: AccountId) -> Self {
+    fn from_metadata(metadata: &Value, issuer: AccountId) -> Result<Self, Rejection> {
         let new_bond: NewAssetDefinition = metadata
             .to_owned()
             .try_into()
-            .dbg_expect("`bond` not of the `NewAssetDefinition` type");
+            .map_err(|_| Rejection::MalformedBond)?;
 
-        Self { issuer, new_bond }
+        Ok(Self { issuer, new_bond })
     }
 
-    // TODO:
-    fn register_interest_payments_trigger(&self) {
+    /// Checks that the bond can be issued before anything is registered:
+    ///
+    /// * The bond terms are well-formed and valid
+    /// * The bond isn't already registered
+    /// * The bond currency and fee recipient exist
+    /// * The reference rate of a floating-rate note is published
+    /// * The inflation index of an inflation-linked bond is published
+    fn validate(&self) -> Result<(BondTerms, Option<RateFixing>), Rejection> {
+        let terms =
+            BondTerms::try_from(self.new_bond.metadata()).map_err(Rejection::MalformedTerms)?;
+        terms.validate().map_err(Rejection::InvalidTerms)?;
+
+        if FindAssetDefinitionById::new(self.new_bond.id().clone())
+            .execute()
+            .is_ok()
+        {
+            return Err(Rejection::AlreadyRegistered);
+        }
+        if FindAssetDefinitionById::new(terms.currency.clone())
+            .execute()
+            .is_err()
+        {
+            return Err(Rejection::CurrencyNotFound(terms.currency));
+        }
+        if FindAccountById::new(terms.fee_recipient_account_id.clone())
+            .execute()
+            .is_err()
+        {
+            return Err(Rejection::FeeRecipientNotFound(
+                terms.fee_recipient_account_id,
+            ));
+        }
+
+        let fixing = match &terms.floating_rate {
+            None => None,
+            Some(floating_rate) => {
+                let oracle = FindAccountById::new(floating_rate.oracle.clone())
+                    .execute()
+                    .map_err(|_| Rejection::RateOracleNotFound(floating_rate.oracle.clone()))?;
+                let reference_rate = published_reference_rate(
+                    &oracle,
+                    &floating_rate.reference_rate,
+                )
+                .ok_or_else(|| {
+                    Rejection::ReferenceRateNotPublished(floating_rate.reference_rate.clone())
+                })?;
+
+                Some(
+                    floating_rate
+                        .fix(terms.registration_time_ms, reference_rate)
+                        .ok_or_else(|| {
+                            Rejection::CouponRateOverflow(floating_rate.reference_rate.clone())
+                        })?,
+                )
+            }
+        };
+
+        if let Some(inflation_index) = &terms.inflation_index {
+            let oracle = FindAccountById::new(inflation_index.oracle.clone())
+                .execute()
+                .map_err(|_| Rejection::IndexOracleNotFound(inflation_index.oracle.clone()))?;
+            if published_index_value(&oracle, &inflation_index.index).is_none() {
+                return Err(Rejection::InflationIndexNotPublished(
+                    inflation_index.index.clone(),
+                ));
+            }
+        }
+
+        Ok((terms, fixing))
+    }
+
+    /// Registers a single periodic trigger, or one trigger per date if the bond has a coupon schedule
+    fn register_interest_payments_trigger(&self, terms: &BondTerms) {
         const WASM: &[u8] =
             core::include_bytes!(concat!(core::env!("OUT_DIR"), "/interest_payments.wasm"));
 
-        let registration_time_ms: NumericValue = self
-            .new_bond
-            .metadata()
-            .get(&"registration_time_ms".parse::<Name>().unwrap())
-            .dbg_expect("INTERNAL BUG: bond missing `registration_time_ms`")
-            .to_owned()
-            .try_into()
-            .dbg_expect("`registration_time_ms` not of the `NumericValue` type");
-        let registration_time = Duration::from_millis(registration_time_ms.try_into().dbg_expect(
-            "INTERNAL BUG: `registration_time_ms` not of the `NumericValue::U64` type",
-        ));
+        let Some(schedule) = &terms.coupon_schedule else {
+            let interest_payments_trigger_id = interest_payments_trigger_id(self.new_bond.id());
+            let interest_payments_trigger = Trigger::new(
+                interest_payments_trigger_id.clone(),
+                Action::new(
+                    WasmSmartContract::from_compiled(WASM.to_vec()),
+                    Repeats::Indefinitely,
+                    self.issuer.clone(),
+                    interest_payments_filter(terms),
+                ),
+            );
 
-        let payment_frequency_seconds: u64 = self
-            .new_bond
-            .metadata()
-            .get(&"payment_frequency_seconds".parse::<Name>().unwrap())
-            .expect("INTERNAL BUG: bond missing `payment_frequency_seconds`")
-            .to_owned()
-            .try_into()
-            .expect("`payment_frequency_seconds` not of the `u64` type");
-        let payment_frequency = Duration::from_secs(payment_frequency_seconds);
-
-        let bond_id = self.new_bond.id();
-        let interest_payments_trigger_id: TriggerId = format!(
-            "{}%%{}%%interest_payments",
-            bond_id.name(),
-            bond_id.domain_id()
-        )
-        .parse()
-        .unwrap();
-        let interest_payments_trigger = Trigger::new(
-            interest_payments_trigger_id.clone(),
+            info!(&format!(
+                "{interest_payments_trigger_id}: Registering interest payments trigger"
+            ));
+            RegisterExpr::new(interest_payments_trigger)
+                .execute()
+                .unwrap();
+
+            return;
+        };
+
+        for (idx, payment_date_ms) in schedule.payment_dates_ms.iter().enumerate() {
+            let coupon_payment_trigger_id = coupon_payment_trigger_id(self.new_bond.id(), idx + 1);
+            let coupon_payment_trigger = Trigger::new(
+                coupon_payment_trigger_id.clone(),
+                Action::new(
+                    WasmSmartContract::from_compiled(WASM.to_vec()),
+                    Repeats::Exactly(1),
+                    self.issuer.clone(),
+                    coupon_payment_filter(*payment_date_ms),
+                ),
+            );
+
+            info!(&format!(
+                "{coupon_payment_trigger_id}: Registering coupon payment trigger for {payment_date_ms} ms"
+            ));
+            RegisterExpr::new(coupon_payment_trigger).execute().unwrap();
+        }
+    }
+
+    /// Registers the trigger closing the subscription of a bond with a subscription period
+    fn register_subscription_close_trigger(&self, terms: &BondTerms) {
+        const WASM: &[u8] =
+            core::include_bytes!(concat!(core::env!("OUT_DIR"), "/subscription_close.wasm"));
+
+        let Some(subscription) = &terms.subscription else {
+            return;
+        };
+
+        let close_trigger_id = subscription_close_trigger_id(self.new_bond.id());
+        let close_trigger = Trigger::new(
+            close_trigger_id.clone(),
             Action::new(
                 WasmSmartContract::from_compiled(WASM.to_vec()),
-                Repeats::Indefinitely,
+                Repeats::Exactly(1),
                 self.issuer.clone(),
-                // TODO: This is simplified in RC22
-                TriggeringFilterBox::from(TimeEventFilter::new(ExecutionTime::Schedule(
-                    TimeSchedule::starting_at(registration_time).with_period(payment_frequency),
-                ))),
+                subscription_close_filter(subscription),
             ),
         );
 
         info!(&format!(
-            "{interest_payments_trigger_id}: Registering interest payments trigger"
+            "{close_trigger_id}: Registering subscription close trigger for {} ms",
+            subscription.end_ms
         ));
-        RegisterExpr::new(interest_payments_trigger)
-            .execute()
-            .unwrap();
+        RegisterExpr::new(close_trigger).execute().unwrap();
     }
 
-    fn register_bond_maturation_trigger(&self) {
+    fn register_bond_maturation_trigger(&self, terms: &BondTerms) {
         const WASM: &[u8] =
             core::include_bytes!(concat!(core::env!("OUT_DIR"), "/bond_maturation.wasm"));
 
-        let maturation_date_ms: NumericValue = self
-            .new_bond
-            .metadata()
-            .get(&"maturation_date_ms".parse::<Name>().unwrap())
-            .dbg_expect("INTERNAL BUG: bond missing `maturation_date_ms`")
-            .to_owned()
-            .try_into()
-            .dbg_expect("`maturation_date_ms` not of the `NumericValue` type");
-        let maturation_date =
-            Duration::from_millis(maturation_date_ms.try_into().dbg_expect(
-                "INTERNAL BUG: `maturation_date_ms` not of the `NumericValue::U64` type",
-            ));
-
-        info!(&format!("Bond maturation date: {maturation_date_ms}"));
+        info!(&format!(
+            "Bond maturation date: {}, repaid at {} ms",
+            terms.maturation_date_ms,
+            terms.maturity_payment_date_ms()
+        ));
 
-        let bond_id = self.new_bond.id();
-        let maturation_trigger_id: TriggerId = format!(
-            "{}%%{}%%bond_maturation",
-            bond_id.name(),
-            bond_id.domain_id()
-        )
-            .parse()
-            .unwrap();
+        let maturation_trigger_id = bond_maturation_trigger_id(self.new_bond.id());
         let maturation_trigger = Trigger::new(
             maturation_trigger_id.clone(),
             Action::new(
                 WasmSmartContract::from_compiled(WASM.to_vec()),
                 Repeats::Exactly(1),
                 self.issuer.clone(),
-                // TODO: This is simplified in RC22
-                TriggeringFilterBox::from(TimeEventFilter::new(ExecutionTime::Schedule(
-                    TimeSchedule::starting_at(maturation_date),
-                ))),
+                bond_maturation_filter(terms),
             ),
         );
 
@@ -138,25 +278,156 @@ impl RegisterBond {
         RegisterExpr::new(maturation_trigger).execute().unwrap();
     }
 
-    fn execute(self) {
-        self.register_interest_payments_trigger();
-        self.register_bond_maturation_trigger();
+    /// Registers one trigger per date of the amortization schedule,
+    /// they run the bond maturation contract which makes the repayment
+    fn register_principal_repayment_triggers(&self, terms: &BondTerms) {
+        const WASM: &[u8] =
+            core::include_bytes!(concat!(core::env!("OUT_DIR"), "/bond_maturation.wasm"));
+
+        for (idx, repayment) in terms.amortization.repayments.iter().enumerate() {
+            let repayment_trigger_id = principal_repayment_trigger_id(self.new_bond.id(), idx + 1);
+            let repayment_trigger = Trigger::new(
+                repayment_trigger_id.clone(),
+                Action::new(
+                    WasmSmartContract::from_compiled(WASM.to_vec()),
+                    Repeats::Exactly(1),
+                    self.issuer.clone(),
+                    principal_repayment_filter(repayment.date_ms),
+                ),
+            );
+
+            info!(&format!(
+                "{repayment_trigger_id}: Registering principal repayment trigger for {} ms",
+                repayment.date_ms
+            ));
+            RegisterExpr::new(repayment_trigger).execute().unwrap();
+        }
+    }
+
+    /// Registers a trigger settling the puts of each put window of a puttable bond,
+    /// it runs the bond maturation contract
+    fn register_put_settlement_triggers(&self, terms: &BondTerms) {
+        const WASM: &[u8] =
+            core::include_bytes!(concat!(core::env!("OUT_DIR"), "/bond_maturation.wasm"));
+
+        for n in 1..=terms.put_schedule.windows.len() {
+            let settlement_date_ms = terms
+                .put_schedule
+                .settlement_date_ms(n)
+                .dbg_expect("INTERNAL BUG: Put window not in the put schedule");
+            let settlement_trigger_id = put_settlement_trigger_id(self.new_bond.id(), n);
+            let settlement_trigger = Trigger::new(
+                settlement_trigger_id.clone(),
+                Action::new(
+                    WasmSmartContract::from_compiled(WASM.to_vec()),
+                    Repeats::Exactly(1),
+                    self.issuer.clone(),
+                    put_settlement_filter(settlement_date_ms),
+                ),
+            );
+
+            info!(&format!(
+                "{settlement_trigger_id}: Registering put settlement trigger for {settlement_date_ms} ms"
+            ));
+            RegisterExpr::new(settlement_trigger).execute().unwrap();
+        }
+    }
+
+    fn execute(self, register_bond_trigger_id: TriggerId) {
+        let (terms, fixing) = match self.validate() {
+            Ok(validated) => validated,
+            Err(rejection) => {
+                reject(
+                    register_bond_trigger_id,
+                    Some(self.new_bond.id()),
+                    &rejection,
+                )
+                .unwrap_or_else(|err| {
+                    error!(&format!(
+                        "{}: Failed to record bond rejection ({err:?})",
+                        self.new_bond.id()
+                    ));
+                });
+                return;
+            }
+        };
 
         RegisterExpr::new(self.new_bond.clone()).execute().unwrap();
 
         let bond_asset_id = AssetId::new(self.new_bond.id().clone(), self.issuer.clone());
-        let quantity: u32 = self.new_bond
-            .metadata()
-            .get(&"quantity".parse::<Name>().unwrap())
-            .dbg_expect("INTERNAL BUG: bond missing `quantity`")
-            .to_owned()
-            .try_into()
-            .dbg_expect("`quantity` not of the `u32` type");
+        MintExpr::new(terms.quantity, bond_asset_id)
+            .execute()
+            .unwrap();
+
+        // Rate of the first coupon period of a floating-rate note is fixed at registration
+        if let Some(fixing) = fixing {
+            SetKeyValueExpr::new(
+                self.new_bond.id().clone(),
+                RATE_FIXING_KEY.parse::<Name>().unwrap(),
+                fixing,
+            )
+            .execute()
+            .unwrap();
+        }
 
-        MintExpr::new(quantity, bond_asset_id).execute().unwrap();
+        if terms.bond_type.pays_coupons() {
+            self.register_interest_payments_trigger(&terms);
+        }
+        self.register_principal_repayment_triggers(&terms);
+        self.register_put_settlement_triggers(&terms);
+        self.register_subscription_close_trigger(&terms);
+        self.register_bond_maturation_trigger(&terms);
     }
 }
 
+/// Records the rejection in the metadata of the `register_bond` trigger
+///
+/// Rejections are recorded per bond, or under `rejected_bond` if the bond couldn't be read.
+/// Fails if the rejection doesn't fit into the trigger's metadata.
+fn reject(
+    register_bond_trigger_id: TriggerId,
+    bond_id: Option<&AssetDefinitionId>,
+    rejection: &Rejection,
+) -> Result<(), MetadataError> {
+    let mut rejection_metadata = Metadata::new();
+    let rejection_metadata_id: Name = match bond_id {
+        Some(bond_id) => {
+            error!(&format!(
+                "{bond_id}: Bond registration rejected ({rejection})"
+            ));
+            rejection_metadata.insert_with_limits(
+                "bond".parse().unwrap(),
+                bond_id.clone().into(),
+                LIMITS,
+            )?;
+
+            format!("rejected_bond_{}%%{}", bond_id.name(), bond_id.domain_id())
+                .parse()
+                .dbg_expect("INTERNAL BUG: Unable to parse rejection metadata id")
+        }
+        None => {
+            error!(&format!("Bond registration rejected ({rejection})"));
+            "rejected_bond".parse().unwrap()
+        }
+    };
+
+    rejection_metadata.insert_with_limits(
+        "reason".parse().unwrap(),
+        Value::String(rejection.to_string()),
+        LIMITS,
+    )?;
+
+    SetKeyValueExpr::new(
+        register_bond_trigger_id,
+        rejection_metadata_id,
+        rejection_metadata,
+    )
+    .execute()
+    .dbg_expect("Failed to set bond rejection to trigger's metadata");
+
+    Ok(())
+}
+
 #[iroha_trigger::main]
 fn main(id: TriggerId, issuer: AccountId, event: Event) {
     let register_bond_key = "bond".parse().unwrap();
@@ -177,9 +448,15 @@ fn main(id: TriggerId, issuer: AccountId, event: Event) {
     if *event.key() != register_bond_key {
         // TODO: Can we filter more precisely to avoid invoking trigger?
         trace!("Triggered by account metadata insert event with another key");
+        return;
     }
 
-    RegisterBond::from_metadata(event.value(), issuer).execute();
+    match RegisterBond::from_metadata(event.value(), issuer) {
+        Ok(register_bond) => register_bond.execute(id.clone()),
+        Err(rejection) => reject(id.clone(), None, &rejection).unwrap_or_else(|err| {
+            error!(&format!("Failed to record bond rejection ({err:?})"));
+        }),
+    }
     RemoveKeyValueExpr::new(id, register_bond_key)
         .execute()
         .unwrap();
@@ -0,0 +1,542 @@
+//! Scheduled time trigger closing the subscription of a bond
+//!
+//! Escrowed subscription orders are allotted and auction bids filled at the close,
+//! before the unsold bonds are burnt or retained and the allocation is published.
+#![no_std]
+
+extern crate alloc;
+#[cfg(not(test))]
+extern crate panic_halt;
+
+use alloc::{borrow::ToOwned as _, format, vec::Vec};
+
+use bond_terms::{
+    allotment::{subscription_order_key, Allotment, SubscriptionOrder},
+    auction::{auction_bid_key, Auction, AuctionBid, AuctionResult, AUCTION_RESULT_KEY},
+    host::next_payment_idx,
+    inflation::find_index_ratio,
+    money::Decimal,
+    subscription::{Allocation, UnsoldBonds, ALLOCATION_KEY},
+    BondTerms,
+};
+use dlmalloc::GlobalDlmalloc;
+use iroha_trigger::data_model::query::account::model::{FindAccountById, FindAllAccounts};
+use iroha_trigger::{
+    data_model::prelude::*,
+    debug::{dbg_panic, DebugExpectExt as _},
+    log::{error, info},
+    prelude::*,
+};
+
+#[global_allocator]
+static ALLOC: GlobalDlmalloc = GlobalDlmalloc;
+
+const LIMITS: MetadataLimits = MetadataLimits::new(256, 256);
+
+#[iroha_trigger::main]
+fn main(id: TriggerId, issuer: AccountId, event: Event) {
+    let bond_id: AssetDefinitionId = id
+        .name()
+        .as_ref()
+        .strip_suffix("%%subscription_close")
+        .dbg_expect("INTERNAL BUG: Trigger name must end with `%%subscription_close`")
+        .replace("%%", "#")
+        .parse()
+        .dbg_expect(
+            "INTERNAL BUG: Unable to parse bond id from trigger name prefix.
+                Prefix trigger name with the id of the bond it's registered for",
+        );
+
+    if !matches!(event, Event::Time(_)) {
+        dbg_panic(
+            "INTERNAL BUG: Triggering event is not TimeEvent.
+            To avoid this error, register the trigger using the correct filter",
+        );
+    }
+
+    let bond = FindAssetDefinitionById::new(bond_id.clone())
+        .execute()
+        .dbg_expect(&format!("{bond_id}: Bond not found"));
+    let terms =
+        BondTerms::try_from(bond.metadata()).dbg_expect(&format!("{bond_id}: Invalid bond terms"));
+    let subscription = terms
+        .subscription
+        .dbg_expect(&format!("{bond_id}: Bond has no subscription period"));
+
+    if let Some(allotment) = &terms.allotment {
+        allot_orders(&bond_id, &terms, allotment, &issuer);
+    }
+    if let Some(auction) = terms.auction {
+        run_auction(&bond_id, &terms, auction, &issuer, subscription.end_ms);
+    }
+
+    let issued_bonds = FindAssetsByAssetDefinitionId::new(bond_id.clone())
+        .execute()
+        .dbg_expect(&format!("{bond_id}: Bond not found"));
+
+    let mut sold = 0_u32;
+    let mut unsold = 0_u32;
+    let mut investors = 0_u32;
+    for issued_bond in issued_bonds {
+        let quantity: u32 = issued_bond
+            .value()
+            .to_owned()
+            .try_into()
+            .dbg_expect("INTERNAL BUG: bond quantity is not of the `u32` type");
+
+        if *issued_bond.id().account_id() == issuer {
+            unsold = quantity;
+        } else if quantity > 0 {
+            sold += quantity;
+            investors += 1;
+        }
+    }
+
+    if subscription.unsold_bonds == UnsoldBonds::Burn && unsold > 0 {
+        info!(&format!("{bond_id}: Burning {unsold} unsold bonds"));
+
+        BurnExpr::new(unsold, AssetId::new(bond_id.clone(), issuer))
+            .execute()
+            .dbg_expect("Burning unsold bonds failed");
+    }
+
+    let allocation = Allocation {
+        close_time_ms: subscription.end_ms,
+        offered: terms.quantity,
+        sold,
+        unsold,
+        investors,
+        unsold_bonds: subscription.unsold_bonds,
+    };
+    SetKeyValueExpr::new(
+        bond_id.clone(),
+        ALLOCATION_KEY.parse::<Name>().unwrap(),
+        allocation,
+    )
+    .execute()
+    .dbg_expect("Failed to set allocation to bond's metadata");
+
+    info!(&format!(
+        "{bond_id}: Subscription closed, {sold} of {} bonds sold to {investors} investors",
+        terms.quantity
+    ));
+}
+
+/// Allot the issuer's inventory to the subscription orders, refunding the funds locked for
+/// bonds that weren't allotted
+fn allot_orders(
+    bond_id: &AssetDefinitionId,
+    terms: &BondTerms,
+    allotment: &Allotment,
+    issuer: &AccountId,
+) {
+    let available = issuer_bonds(bond_id, issuer);
+
+    let mut orders = Vec::new();
+    for account in FindAllAccounts::new()
+        .execute()
+        .dbg_expect("INTERNAL BUG: Unable to query accounts")
+    {
+        if let Some(order) = SubscriptionOrder::from_buyer_metadata(account.metadata(), bond_id) {
+            orders.push((account.id().clone(), order));
+        }
+    }
+    // Stable sort keeps the query order of orders placed in the same block
+    orders.sort_by_key(|(_, order)| order.order_time_ms);
+
+    let allotted = allotment.allot(
+        &orders
+            .iter()
+            .map(|(_, order)| (order.tier, order.quantity))
+            .collect::<Vec<_>>(),
+        available,
+    );
+    let requested: u64 = orders
+        .iter()
+        .map(|(_, order)| u64::from(order.quantity))
+        .sum();
+    info!(&format!(
+        "{bond_id}: Allotting {} of {available} bonds to {} orders for {requested} bonds",
+        allotted.iter().sum::<u32>(),
+        orders.len()
+    ));
+
+    for ((buyer, order), allotted) in orders.into_iter().zip(allotted) {
+        settle(
+            bond_id,
+            terms,
+            issuer,
+            Settlement {
+                buyer,
+                order_key: subscription_order_key(bond_id),
+                requested: order.quantity,
+                allotted,
+                clean_price: share(terms, order.clean_price, allotted, order.quantity),
+                accrued_interest: share(terms, order.accrued_interest, allotted, order.quantity),
+                fee: order.fee,
+                locked: order
+                    .locked_amount()
+                    .dbg_expect("Subscription order amount overflow"),
+                bid: None,
+            },
+        );
+    }
+}
+
+/// Auction the issuer's inventory to the sealed bids, refunding the funds locked for
+/// bonds that weren't allotted, and publish the auction result
+fn run_auction(
+    bond_id: &AssetDefinitionId,
+    terms: &BondTerms,
+    auction: Auction,
+    issuer: &AccountId,
+    close_time_ms: u64,
+) {
+    let offered = issuer_bonds(bond_id, issuer);
+    // Bonds are issued at the close, inflation-linked ones at the index published by then
+    let index_ratio = terms
+        .inflation_index
+        .as_ref()
+        .map(|inflation_index| find_index_ratio(bond_id, terms, inflation_index));
+
+    let mut bids = Vec::new();
+    for account in FindAllAccounts::new()
+        .execute()
+        .dbg_expect("INTERNAL BUG: Unable to query accounts")
+    {
+        if let Some(bid) = AuctionBid::from_bidder_metadata(account.metadata(), bond_id) {
+            bids.push((account.id().clone(), bid));
+        }
+    }
+    // Stable sort keeps the query order of bids placed in the same block
+    bids.sort_by_key(|(_, bid)| bid.bid_time_ms);
+
+    let mut competitive = Vec::new();
+    let mut non_competitive = Vec::new();
+    for (bidder, bid) in bids {
+        match bid.bid {
+            Some(_) => competitive.push((bidder, bid)),
+            None => non_competitive.push((bidder, bid)),
+        }
+    }
+
+    let clearing = auction
+        .clear(
+            terms,
+            &competitive.iter().map(|(_, bid)| *bid).collect::<Vec<_>>(),
+            &non_competitive
+                .iter()
+                .map(|(_, bid)| *bid)
+                .collect::<Vec<_>>(),
+            offered,
+            index_ratio,
+        )
+        .dbg_expect("Auction clearing overflow");
+    let stop_out = clearing.stop_out;
+    let bid_quantity: u64 = competitive
+        .iter()
+        .map(|(_, bid)| u64::from(bid.quantity))
+        .sum();
+    info!(&format!(
+        "{bond_id}: Auctioning {} of {offered} bonds to {} bids for {bid_quantity} bonds",
+        clearing.allotted.iter().sum::<u32>(),
+        competitive.len()
+    ));
+
+    let mut competitive_sold = 0_u32;
+    let mut paid = Decimal::ZERO;
+    for (((bidder, bid), allotted), clean_price) in competitive
+        .iter()
+        .cloned()
+        .zip(clearing.allotted)
+        .zip(clearing.clean_prices)
+    {
+        let settled = settle(
+            bond_id,
+            terms,
+            issuer,
+            Settlement {
+                buyer: bidder,
+                order_key: auction_bid_key(bond_id),
+                requested: bid.quantity,
+                allotted,
+                clean_price,
+                accrued_interest: share(terms, bid.accrued_interest, allotted, bid.quantity),
+                fee: bid.fee,
+                locked: bid
+                    .locked_amount()
+                    .dbg_expect("Auction bid amount overflow"),
+                bid: bid.bid,
+            },
+        );
+        if settled > 0 {
+            competitive_sold += settled;
+            paid = paid
+                .checked_add(clean_price)
+                .dbg_expect("Auction total price overflow");
+        }
+    }
+
+    let non_competitive_quantity: u64 = non_competitive
+        .iter()
+        .map(|(_, bid)| u64::from(bid.quantity))
+        .sum();
+    let mut non_competitive_sold = 0_u32;
+    for ((bidder, bid), allotted) in non_competitive
+        .iter()
+        .cloned()
+        .zip(clearing.non_competitive_allotted)
+    {
+        let clean_price = clearing
+            .non_competitive_price
+            .unwrap_or(Decimal::ZERO)
+            .checked_mul_ratio(allotted.into(), 1, terms.rounding)
+            .dbg_expect("Allotted amount overflow");
+
+        let settled =
+            settle_non_competitive(bond_id, terms, issuer, bidder, bid, allotted, clean_price);
+        if settled > 0 {
+            non_competitive_sold += settled;
+            paid = paid
+                .checked_add(clean_price)
+                .dbg_expect("Auction total price overflow");
+        }
+    }
+
+    let sold = competitive_sold + non_competitive_sold;
+    info!(&format!(
+        "{bond_id}: Allotted {non_competitive_sold} bonds to {} non-competitive orders for {non_competitive_quantity} bonds",
+        non_competitive.len(),
+    ));
+
+    let result = AuctionResult {
+        close_time_ms,
+        pricing: auction.pricing,
+        basis: auction.basis,
+        offered,
+        bids: u32::try_from(competitive.len()).dbg_expect("Too many bids"),
+        bid_quantity,
+        sold,
+        stop_out,
+        stop_out_price: stop_out.map(|stop_out| {
+            terms
+                .bid_price(1, stop_out)
+                .and_then(|price| terms.index_adjusted(price, index_ratio))
+                .dbg_expect("Bond price overflow")
+        }),
+        average_price: (sold > 0).then(|| {
+            paid.checked_mul_ratio(1, sold.into(), terms.rounding)
+                .dbg_expect("Bond price overflow")
+        }),
+        non_competitive_orders: u32::try_from(non_competitive.len())
+            .dbg_expect("Too many non-competitive orders"),
+        non_competitive_quantity,
+        non_competitive_sold,
+        non_competitive_price: clearing.non_competitive_price,
+    };
+    SetKeyValueExpr::new(
+        bond_id.clone(),
+        AUCTION_RESULT_KEY.parse::<Name>().unwrap(),
+        result,
+    )
+    .execute()
+    .dbg_expect("Failed to set auction result to bond's metadata");
+}
+
+/// Settle a non-competitive order at the clearing price of the bonds allotted to it
+fn settle_non_competitive(
+    bond_id: &AssetDefinitionId,
+    terms: &BondTerms,
+    issuer: &AccountId,
+    bidder: AccountId,
+    bid: AuctionBid,
+    allotted: u32,
+    clean_price: Decimal,
+) -> u32 {
+    settle(
+        bond_id,
+        terms,
+        issuer,
+        Settlement {
+            buyer: bidder,
+            order_key: auction_bid_key(bond_id),
+            requested: bid.quantity,
+            allotted,
+            clean_price,
+            accrued_interest: share(terms, bid.accrued_interest, allotted, bid.quantity),
+            fee: bid.fee,
+            locked: bid
+                .locked_amount()
+                .dbg_expect("Auction bid amount overflow"),
+            bid: None,
+        },
+    )
+}
+
+/// Share of the amount locked for an order falling on the bonds allotted to it
+fn share(terms: &BondTerms, amount: Decimal, allotted: u32, requested: u32) -> Decimal {
+    amount
+        .checked_mul_ratio(allotted.into(), requested.into(), terms.rounding)
+        .dbg_expect("Allotted amount overflow")
+}
+
+/// Number of bonds in the issuer's inventory
+fn issuer_bonds(bond_id: &AssetDefinitionId, issuer: &AccountId) -> u32 {
+    FindAssetById::new(AssetId::new(bond_id.clone(), issuer.clone()))
+        .execute()
+        .dbg_expect(&format!("{bond_id}: Issuer's bonds not found"))
+        .value()
+        .to_owned()
+        .try_into()
+        .dbg_expect("INTERNAL BUG: bond quantity is not of the `u32` type")
+}
+
+/// Order placed during the subscription and the bonds allotted to it at the close
+struct Settlement {
+    /// Who placed the order
+    buyer: AccountId,
+    /// Key of the buyer's metadata holding the order
+    order_key: Name,
+    /// Number of bonds ordered
+    requested: u32,
+    /// Number of bonds allotted
+    allotted: u32,
+    /// Exact price of the bonds allotted, excluding accrued interest
+    clean_price: Decimal,
+    /// Exact interest accrued on the bonds allotted
+    accrued_interest: Decimal,
+    /// Purchase fee, charged in full on any allotment
+    fee: Decimal,
+    /// Funds locked with the issuer for the order
+    locked: Decimal,
+    /// Price or yield bid, for auctioned bonds
+    bid: Option<Decimal>,
+}
+
+/// Transfer the bonds allotted to the buyer, charge the price of the bonds from the locked
+/// funds, refund the rest and record the purchase in the buyer's metadata
+///
+/// Returns the number of bonds transferred. An order whose locked funds don't pay for
+/// the bonds allotted to it is refunded in full and gets none.
+fn settle(
+    bond_id: &AssetDefinitionId,
+    terms: &BondTerms,
+    issuer: &AccountId,
+    order: Settlement,
+) -> u32 {
+    let clean_price = terms.round(order.clean_price);
+    let accrued_interest = terms.round(order.accrued_interest);
+    let fee = if order.allotted > 0 {
+        order.fee
+    } else {
+        Decimal::ZERO
+    };
+    let residual = clean_price
+        .residual
+        .checked_add(accrued_interest.residual)
+        .and_then(Decimal::to_fixed)
+        .dbg_expect("Bond price residual overflow");
+    let amount = clean_price
+        .amount
+        .checked_add(accrued_interest.amount)
+        .dbg_expect("Bond total price overflow");
+    let refund = order
+        .locked
+        .checked_sub(amount)
+        .and_then(|locked| locked.checked_sub(fee))
+        .dbg_expect("Refund overflow");
+    if refund.is_negative() {
+        error!(&format!(
+            "{bond_id}: Funds locked by {} don't pay for the {} bonds allotted, allotting none",
+            order.buyer, order.allotted
+        ));
+
+        return settle(
+            bond_id,
+            terms,
+            issuer,
+            Settlement {
+                allotted: 0,
+                clean_price: Decimal::ZERO,
+                accrued_interest: Decimal::ZERO,
+                ..order
+            },
+        );
+    }
+    let Settlement {
+        buyer, allotted, ..
+    } = order;
+    let amount = amount.to_fixed().dbg_expect("Bond total price overflow");
+    let clean_price = clean_price
+        .amount
+        .to_fixed()
+        .dbg_expect("Bond total price overflow");
+    let accrued_interest = accrued_interest
+        .amount
+        .to_fixed()
+        .dbg_expect("Accrued interest overflow");
+    let fee = fee.to_fixed().dbg_expect("Bond fee overflow");
+    let is_refunded = refund > Decimal::ZERO;
+    let refund = refund.to_fixed().dbg_expect("Refund overflow");
+
+    let issuer_money = AssetId::new(terms.currency.clone(), issuer.clone());
+    if allotted > 0 {
+        TransferExpr::new(
+            AssetId::new(bond_id.clone(), issuer.clone()),
+            allotted,
+            buyer.clone(),
+        )
+        .execute()
+        .dbg_expect("Sending bond failed");
+        TransferExpr::new(
+            issuer_money.clone(),
+            fee,
+            terms.fee_recipient_account_id.clone(),
+        )
+        .execute()
+        .dbg_expect("Sending fee failed");
+    }
+    if is_refunded {
+        TransferExpr::new(issuer_money, refund, buyer.clone())
+            .execute()
+            .dbg_expect("Refunding money failed");
+    }
+    RemoveKeyValueExpr::new(buyer.clone(), order.order_key)
+        .execute()
+        .dbg_expect("Failed to remove order from buyer's metadata");
+
+    let buy_bond_payment_idx = next_payment_idx(&buyer, "buy_bond_payment_idx");
+    let transfer_metadata_id: Name = format!(
+        "buy_bond_payment_{}%%{}%%idx%%{buy_bond_payment_idx}",
+        bond_id.name(),
+        bond_id.domain_id(),
+    )
+    .parse()
+    .dbg_expect("INTERNAL BUG: Unable to parse transfer metadata id");
+
+    let mut transfer_metadata = Metadata::new();
+    let mut insert = |key: &str, value: Value| {
+        transfer_metadata
+            .insert_with_limits(key.parse().unwrap(), value, LIMITS)
+            .unwrap();
+    };
+    insert("amount", amount.into());
+    insert("clean_price", clean_price.into());
+    insert("accrued_interest", accrued_interest.into());
+    insert("fee", fee.into());
+    insert("quantity", allotted.into());
+    insert("requested", order.requested.into());
+    insert("refund", refund.into());
+    if let Some(bid) = order.bid {
+        insert("bid", bid.to_fixed().dbg_expect("Bid overflow").into());
+    }
+    insert("currency", terms.currency.clone().into());
+    insert("bond_asset_id", bond_id.clone().into());
+    insert("residual", residual.into());
+
+    SetKeyValueExpr::new(buyer, transfer_metadata_id, transfer_metadata)
+        .execute()
+        .dbg_expect("Failed to set buy bond info to buyer's metadata");
+
+    allotted
+}
@@ -0,0 +1,102 @@
+//! Genesis block with the bond triggers preinstalled
+
+use std::{fs, path::Path};
+
+use eyre::{eyre, Result, WrapErr as _};
+use iroha_client::data_model::prelude::*;
+use serde_json::Value as Json;
+
+use crate::{
+    keystore::Keystore,
+    triggers::{bond_triggers, build_wasm_bytes, BondTrigger},
+};
+
+/// Add the bond trigger registrations to the genesis and build the executor it refers to
+///
+/// The triggers are registered in a transaction of their own. Transactions registering
+/// the same triggers, e.g. left by a previous run, are replaced so the genesis can be
+/// regenerated whenever a trigger changes.
+///
+/// Accounts registered in the genesis with a key in the keystore are given its public key
+/// as their signatory, so that no private key has to be shipped with the genesis.
+pub fn write_genesis(path: &Path, authority: &AccountId, keystore: &Keystore) -> Result<()> {
+    let mut genesis: Json = serde_json::from_str(
+        &fs::read_to_string(path)
+            .wrap_err_with(|| format!("{}: Failed to read genesis", path.display()))?,
+    )?;
+
+    let triggers = bond_triggers(authority)?;
+    let trigger_ids = triggers
+        .iter()
+        .map(|trigger| trigger.id().to_string())
+        .collect::<Vec<_>>();
+
+    let transactions = genesis
+        .get_mut("transactions")
+        .and_then(Json::as_array_mut)
+        .ok_or_else(|| eyre!("{}: Genesis has no `transactions`", path.display()))?;
+    transactions.retain(|transaction| {
+        !transaction.as_array().is_some_and(|instructions| {
+            instructions.iter().any(|instruction| {
+                registered_trigger_id(instruction)
+                    .is_some_and(|id| trigger_ids.iter().any(|trigger_id| trigger_id == id))
+            })
+        })
+    });
+
+    for instruction in transactions
+        .iter_mut()
+        .filter_map(Json::as_array_mut)
+        .flatten()
+    {
+        let Some(account) = registered_account_mut(instruction) else {
+            continue;
+        };
+        let Some(account_id) = account
+            .get("id")
+            .and_then(Json::as_str)
+            .and_then(|id| id.parse::<AccountId>().ok())
+        else {
+            continue;
+        };
+        if keystore.contains(&account_id) {
+            let public_key = keystore.public_key(&account_id)?;
+            account["signatories"] = Json::Array(vec![Json::String(public_key.to_string())]);
+            println!("{account_id}: Signatory set to {public_key}");
+        }
+    }
+
+    let registrations = triggers
+        .into_iter()
+        .flat_map(BondTrigger::register)
+        .map(|instruction| serde_json::to_value(instruction))
+        .collect::<Result<Vec<_>, _>>()?;
+    transactions.push(Json::Array(registrations));
+
+    let executor = genesis
+        .get("executor")
+        .and_then(Json::as_str)
+        .ok_or_else(|| eyre!("{}: Genesis has no `executor` path", path.display()))?;
+    let executor_path = path.parent().unwrap_or(Path::new(".")).join(executor);
+    fs::write(&executor_path, build_wasm_bytes("executor")?)?;
+    println!("Executor written to {}", executor_path.display());
+
+    fs::write(path, serde_json::to_string_pretty(&genesis)?)?;
+    println!("Bond triggers added to {}", path.display());
+
+    Ok(())
+}
+
+/// Id of the trigger registered by the instruction, if it's a trigger registration
+fn registered_trigger_id(instruction: &Json) -> Option<&str> {
+    instruction
+        .get("Register")?
+        .get("Trigger")?
+        .get("id")?
+        .as_str()
+}
+
+/// Account registered by the instruction, if it's an account registration
+fn registered_account_mut(instruction: &mut Json) -> Option<&mut Json> {
+    instruction.get_mut("Register")?.get_mut("NewAccount")
+}
@@ -0,0 +1,54 @@
+//! Holiday calendars stored in domain metadata
+//!
+//! Coupon dates of bonds with a coupon schedule are adjusted to the business days of
+//! a holiday calendar when the bond is specified, see [`bond_terms::calendar`].
+
+use bond_terms::calendar::{holiday_calendar_key, HolidayCalendar};
+use eyre::{eyre, Result, WrapErr as _};
+use iroha_client::{client::Client, data_model::prelude::*};
+
+use crate::spec::parse_date_ms;
+
+/// Read the holiday calendar with the given name from the domain's metadata
+pub fn holiday_calendar(
+    iroha: &Client,
+    domain_id: &DomainId,
+    name: &str,
+) -> Result<HolidayCalendar> {
+    let calendar = iroha
+        .request(FindDomainKeyValueByIdAndKey::new(
+            domain_id.clone(),
+            holiday_calendar_key(name),
+        ))
+        .wrap_err_with(|| format!("{name}: Holiday calendar not found in `{domain_id}`"))?;
+
+    HolidayCalendar::try_from(&calendar)
+        .map_err(|_| eyre!("{name}: Holiday calendar is not a list of dates"))
+}
+
+/// Replace the holidays of the calendar with the given name in the domain's metadata
+///
+/// Dates are ISO-8601 calendar dates, e.g. `2026-12-25`.
+pub fn set_holidays(
+    iroha: &Client,
+    domain_id: DomainId,
+    name: &str,
+    dates: &[String],
+) -> Result<()> {
+    let holidays = dates
+        .iter()
+        .map(|date| parse_date_ms("dates", &format!("{date}T00:00:00Z")))
+        .collect::<Result<Vec<_>>>()?;
+
+    println!(
+        "Setting {} holidays of the {name} calendar...",
+        holidays.len()
+    );
+    iroha.submit_blocking(SetKeyValueExpr::new(
+        domain_id,
+        holiday_calendar_key(name),
+        Value::from(&HolidayCalendar::new(holidays)),
+    ))?;
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Local keystore holding one key pair file per account
+//!
+//! Key files are named after the account id, e.g. `keys/citizen@palau.json`, and store
+//! the private key either in plain text or encrypted with a passphrase.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{Aead as _, AeadCore as _, KeyInit as _, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use eyre::{eyre, Result, WrapErr as _};
+use iroha_client::{
+    crypto::{Algorithm, KeyPair, PrivateKey, PublicKey},
+    data_model::prelude::*,
+};
+use rand_core::RngCore as _;
+use serde::{Deserialize, Serialize};
+
+/// Environment variable consulted for the passphrase before prompting for it
+const PASSPHRASE_ENV: &str = "PALAU_KEYSTORE_PASSPHRASE";
+const SALT_LEN: usize = 16;
+
+/// Key pair file of a single account
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    public_key: PublicKey,
+    #[serde(flatten)]
+    private_key: StoredPrivateKey,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StoredPrivateKey {
+    PrivateKey(PrivateKey),
+    EncryptedPrivateKey(EncryptedPrivateKey),
+}
+
+/// Private key payload encrypted with XChaCha20-Poly1305 under an Argon2 derived key
+#[derive(Serialize, Deserialize)]
+struct EncryptedPrivateKey {
+    digest_function: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Directory of account key files
+pub struct Keystore {
+    dir: PathBuf,
+}
+
+impl Keystore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn key_file_path(&self, account_id: &AccountId) -> PathBuf {
+        self.dir.join(format!("{account_id}.json"))
+    }
+
+    /// Load the key pair of the given account, asking for a passphrase if it's encrypted
+    ///
+    /// # Errors
+    ///
+    /// Fails if there is no key file for the account or it can't be decrypted
+    pub fn load(&self, account_id: &AccountId) -> Result<KeyPair> {
+        let path = self.key_file_path(account_id);
+        let content = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("{account_id}: No key in keystore ({})", path.display()))?;
+        let key_file: KeyFile = serde_json::from_str(&content)
+            .wrap_err_with(|| format!("{}: Malformed key file", path.display()))?;
+
+        let private_key = match key_file.private_key {
+            StoredPrivateKey::PrivateKey(private_key) => private_key,
+            StoredPrivateKey::EncryptedPrivateKey(encrypted) => {
+                decrypt(&encrypted, &passphrase(account_id)?)
+                    .wrap_err_with(|| format!("{account_id}: Failed to decrypt private key"))?
+            }
+        };
+
+        Ok(KeyPair::new(key_file.public_key, private_key)?)
+    }
+
+    /// Whether the given account has a key file
+    pub fn contains(&self, account_id: &AccountId) -> bool {
+        self.key_file_path(account_id).exists()
+    }
+
+    /// Read the public key of the given account without touching the private key
+    ///
+    /// # Errors
+    ///
+    /// Fails if there is no key file for the account
+    pub fn public_key(&self, account_id: &AccountId) -> Result<PublicKey> {
+        let path = self.key_file_path(account_id);
+        let content = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("{account_id}: No key in keystore ({})", path.display()))?;
+        let key_file: KeyFile = serde_json::from_str(&content)
+            .wrap_err_with(|| format!("{}: Malformed key file", path.display()))?;
+
+        Ok(key_file.public_key)
+    }
+
+    /// Generate and store a new key pair for the given account
+    ///
+    /// # Errors
+    ///
+    /// Fails if the account already has a key file or it can't be written
+    pub fn generate(&self, account_id: &AccountId, encrypt: bool) -> Result<PublicKey> {
+        let path = self.key_file_path(account_id);
+        if path.exists() {
+            return Err(eyre!(
+                "{account_id}: Key already exists ({})",
+                path.display()
+            ));
+        }
+
+        let key_pair = KeyPair::generate()?;
+        let private_key = if encrypt {
+            let encrypted = encrypt_private_key(key_pair.private_key(), &passphrase(account_id)?)?;
+            StoredPrivateKey::EncryptedPrivateKey(encrypted)
+        } else {
+            StoredPrivateKey::PrivateKey(key_pair.private_key().clone())
+        };
+        let key_file = KeyFile {
+            public_key: key_pair.public_key().clone(),
+            private_key,
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        write_private(&path, &serde_json::to_string_pretty(&key_file)?)?;
+
+        Ok(key_file.public_key)
+    }
+}
+
+fn passphrase(account_id: &AccountId) -> Result<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+
+    Ok(rpassword::prompt_password(format!(
+        "Keystore passphrase for {account_id}: "
+    ))?)
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305> {
+    let mut key = [0_u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| eyre!("Key derivation failed: {err}"))?;
+
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn encrypt_private_key(private_key: &PrivateKey, passphrase: &str) -> Result<EncryptedPrivateKey> {
+    let mut salt = [0_u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(&nonce, private_key.payload())
+        .map_err(|_| eyre!("Private key encryption failed"))?;
+
+    Ok(EncryptedPrivateKey {
+        digest_function: private_key.digest_function().to_string(),
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn decrypt(encrypted: &EncryptedPrivateKey, passphrase: &str) -> Result<PrivateKey> {
+    let salt = hex::decode(&encrypted.salt)?;
+    let nonce = hex::decode(&encrypted.nonce)?;
+    let ciphertext = hex::decode(&encrypted.ciphertext)?;
+    if nonce.len() != 24 {
+        return Err(eyre!("Nonce must be 24 bytes long"));
+    }
+
+    let payload = cipher(passphrase, &salt)?
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| eyre!("Wrong passphrase"))?;
+    let digest_function: Algorithm = encrypted
+        .digest_function
+        .parse()
+        .map_err(|err| eyre!("Unknown digest function: {err:?}"))?;
+
+    Ok(PrivateKey::from_hex(
+        digest_function,
+        hex::encode(payload).as_ref(),
+    )?)
+}
+
+/// Write a file readable only by the current user
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::{io::Write as _, os::unix::fs::OpenOptionsExt as _};
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(content.as_bytes())?;
+    }
+    #[cfg(not(unix))]
+    fs::write(path, content)?;
+
+    Ok(())
+}
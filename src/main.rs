@@ -1,240 +1,624 @@
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+mod genesis;
+mod holidays;
+mod keystore;
+mod onboarding;
+mod rates;
+mod spec;
+mod triggers;
 
-use eyre::Result;
+use std::{num::NonZeroU32, path::PathBuf};
+
+use bond_terms::{
+    allotment::SubscriptionOrder,
+    auction::{AuctionBid, AuctionResult},
+    calendar::HolidayCalendar,
+    call::CallNotice,
+    money::Decimal,
+    put::PutRequest,
+    subscription::Allocation,
+    BondTerms,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use eyre::{eyre, Result};
+use genesis::write_genesis;
+use holidays::{holiday_calendar, set_holidays};
 use iroha_client::{
-    client::Client,
-    crypto::{Algorithm, KeyPair, PrivateKey},
+    client::{Client, QueryResult},
+    crypto::PublicKey,
     data_model::{
-        asset::{AssetDefinition, AssetValueType},
+        asset::AssetDefinition,
         metadata::{Limits, Metadata},
-        prelude::{TransactionBuilder, *},
+        prelude::*,
         Registered,
     },
 };
 use iroha_config::{base::proxy::LoadFromDisk, client::ConfigurationProxy};
+use keystore::Keystore;
+use onboarding::{onboard_investor, Kyc};
+use rates::{publish_inflation_index, publish_reference_rate};
+use spec::BondSpec;
 
-fn register_triggers(iroha: &Client) -> Result<()> {
-    // TODO: Get from config in RC22
-    let account_id: AccountId = "government@palau".parse().unwrap();
-
-    println!("Building register_bond trigger...");
-    let register_bond_wasm = WasmSmartContract::from_compiled(
-        iroha_wasm_builder::Builder::new("smart_contracts/register_bond")
-            // TODO: Available in RC22
-            //.show_output()
-            .build()?
-            .optimize()?
-            .into_bytes()?,
-    );
-    println!("Building buy_bonds trigger...");
-    let buy_bonds_wasm = WasmSmartContract::from_compiled(
-        iroha_wasm_builder::Builder::new("smart_contracts/buy_bonds")
-            // TODO: Available in RC22
-            //.show_output()
-            .build()?
-            .optimize()?
-            .into_bytes()?,
-    );
-    println!("Building redeem_bonds trigger...");
-    let redeem_bonds_wasm = WasmSmartContract::from_compiled(
-        iroha_wasm_builder::Builder::new("smart_contracts/redeem_bonds")
-            // TODO: Available in RC22
-            //.show_output()
-            .build()?
-            .optimize()?
-            .into_bytes()?,
-    );
+fn register_bond(iroha: &Client, new_bond: <AssetDefinition as Registered>::With) -> Result<()> {
+    let register_bond_trigger_id: TriggerId = "register_bond".parse()?;
+    let bond_id = new_bond.id().clone();
 
-    let register_bond_trigger_id: TriggerId = "register_bond".parse().unwrap();
-    let register_bond_trigger = Trigger::new(
-        register_bond_trigger_id.clone(),
-        Action::new(
-            register_bond_wasm,
-            Repeats::Indefinitely,
-            account_id.clone(),
-            // TODO: Can be simplified in RC22
-            TriggeringFilterBox::from(BySome(DataEntityFilter::from(BySome(TriggerFilter::new(
-                BySome(OriginFilter::new(register_bond_trigger_id)),
-                BySome(TriggerEventFilter::ByMetadataInserted),
-            ))))),
-        ),
-    );
+    // Fail early, the trigger performs the same checks before registering anything
+    BondTerms::try_from(new_bond.metadata())?.validate()?;
+    if iroha
+        .request(FindAssetDefinitionById::new(bond_id.clone()))
+        .is_ok()
+    {
+        return Err(eyre!("{bond_id}: Bond already registered"));
+    }
 
-    let buy_bonds_trigger_id: TriggerId = "buy_bonds_trigger".parse().unwrap();
-    let buy_bonds_trigger = Trigger::new(
-        buy_bonds_trigger_id.clone(),
-        Action::new(
-            buy_bonds_wasm,
-            Repeats::Indefinitely,
-            account_id.clone(),
-            // TODO: Can be simplified in RC22
-            TriggeringFilterBox::from(BySome(DataEntityFilter::from(BySome(AccountFilter::new(
-                AcceptAll,
-                BySome(AccountEventFilter::ByMetadataInserted),
-            ))))),
-        ),
+    let set_key = SetKeyValueExpr::new(
+        register_bond_trigger_id.clone(),
+        "bond".parse::<Name>()?,
+        new_bond.clone(),
     );
 
-    let redeem_bonds_trigger_id: TriggerId = "redeem_bonds_trigger".parse().unwrap();
-    let redeem_bonds_trigger = Trigger::new(
-        redeem_bonds_trigger_id.clone(),
-        Action::new(
-            redeem_bonds_wasm,
-            Repeats::Indefinitely,
-            account_id.clone(),
-            // TODO: Can be simplified in RC22
-            TriggeringFilterBox::from(BySome(DataEntityFilter::from(BySome(AccountFilter::new(
-                AcceptAll,
-                BySome(AccountEventFilter::ByMetadataInserted),
-            ))))),
-        ),
-    );
+    println!("Registering new bond...");
+    iroha.submit_blocking(set_key)?;
 
+    if iroha
+        .request(FindAssetDefinitionById::new(bond_id.clone()))
+        .is_ok()
+    {
+        return Ok(());
+    }
 
-    println!("Registering register_bond trigger...");
-    iroha.submit_blocking(RegisterExpr::new(register_bond_trigger))?;
-    println!("Registering buy_bonds trigger...");
-    iroha.submit_blocking(RegisterExpr::new(buy_bonds_trigger))?;
-    println!("Registering redeem_bonds trigger...");
-    iroha.submit_blocking(RegisterExpr::new(redeem_bonds_trigger))?;
+    // The trigger records why it refused to register the bond
+    let rejection_key: Name =
+        format!("rejected_bond_{}%%{}", bond_id.name(), bond_id.domain_id()).parse()?;
+    let Value::LimitedMetadata(rejection) = iroha.request(FindTriggerKeyValueByIdAndKey::new(
+        register_bond_trigger_id,
+        rejection_key,
+    ))?
+    else {
+        return Err(eyre!(
+            "{bond_id}: Bond rejection not of the `LimitedMetadata` type"
+        ));
+    };
+    let Some(Value::String(reason)) = rejection.get("reason") else {
+        return Err(eyre!("{bond_id}: Bond rejection is missing `reason`"));
+    };
 
-    Ok(())
+    Err(eyre!("{bond_id}: Bond registration rejected: {reason}"))
 }
 
-fn register_bond(iroha: &Client, new_bond: <AssetDefinition as Registered>::With) -> Result<()> {
-    let register_bond_trigger_id: TriggerId = "register_bond".parse()?;
+fn call_bond(iroha: &Client, bond_id: AssetDefinitionId) -> Result<()> {
+    let call_bond_trigger_id: TriggerId = "call_bond".parse()?;
 
     let set_key = SetKeyValueExpr::new(
-        register_bond_trigger_id,
+        call_bond_trigger_id.clone(),
         "bond".parse::<Name>()?,
-        new_bond.clone(),
+        bond_id.clone(),
     );
 
-    println!("Registering new bond...");
+    println!("Calling bond...");
     iroha.submit_blocking(set_key)?;
 
+    let bond = iroha.request(FindAssetDefinitionById::new(bond_id.clone()))?;
+    if let Some(notice) = CallNotice::from_bond_metadata(bond.metadata()) {
+        println!(
+            "{bond_id}: Bond called at {}, redeemed at {} ms",
+            notice.price, notice.redemption_date_ms
+        );
+        return Ok(());
+    }
+
+    // The trigger records why it refused to call the bond
+    let rejection_key: Name =
+        format!("rejected_call_{}%%{}", bond_id.name(), bond_id.domain_id()).parse()?;
+    let Value::LimitedMetadata(rejection) = iroha.request(FindTriggerKeyValueByIdAndKey::new(
+        call_bond_trigger_id,
+        rejection_key,
+    ))?
+    else {
+        return Err(eyre!(
+            "{bond_id}: Bond call rejection not of the `LimitedMetadata` type"
+        ));
+    };
+    let Some(Value::String(reason)) = rejection.get("reason") else {
+        return Err(eyre!("{bond_id}: Bond call rejection is missing `reason`"));
+    };
+
+    Err(eyre!("{bond_id}: Bond call rejected: {reason}"))
+}
+
+fn buy_bonds(
+    iroha: &Client,
+    bond_id: AssetDefinitionId,
+    quantity: NonZeroU32,
+    bid: Option<Decimal>,
+) -> Result<()> {
+    println!("Buying bond...");
+    submit_bonds_order(iroha, "buy_bonds", bond_id.clone(), quantity, bid)?;
+
+    // Orders for allotted bonds are escrowed until the subscription closes
+    let buyer = iroha.request(FindAccountById::new(iroha.account_id.clone()))?;
+    if let Some(order) = SubscriptionOrder::from_buyer_metadata(buyer.metadata(), &bond_id) {
+        let locked = order
+            .locked_amount()
+            .ok_or_else(|| eyre!("{bond_id}: Locked amount overflow"))?;
+        println!(
+            "{bond_id}: {} bonds ordered in priority tier {}, {locked} locked until the allotment",
+            order.quantity, order.tier
+        );
+    }
+    // Bids for auctioned bonds are sealed until the auction closes
+    if let Some(bid) = AuctionBid::from_bidder_metadata(buyer.metadata(), &bond_id) {
+        let locked = bid
+            .locked_amount()
+            .ok_or_else(|| eyre!("{bond_id}: Locked amount overflow"))?;
+        match bid.bid {
+            Some(price) => println!(
+                "{bond_id}: Bid of {price} for {} bonds sealed, {locked} locked until the auction closes",
+                bid.quantity
+            ),
+            None => println!(
+                "{bond_id}: Non-competitive order for {} bonds placed, {locked} locked until the auction closes",
+                bid.quantity
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn redeem_bonds(iroha: &Client, bond_id: AssetDefinitionId, quantity: NonZeroU32) -> Result<()> {
+    println!("Requesting bond redemption...");
+    submit_bonds_order(iroha, "redeem_bonds", bond_id.clone(), quantity, None)?;
+
+    // The trigger queues the put until the settlement of the open put window
+    let holder = iroha.request(FindAccountById::new(iroha.account_id.clone()))?;
+    let Some(request) = PutRequest::from_holder_metadata(holder.metadata(), &bond_id) else {
+        return Err(eyre!(
+            "{bond_id}: Redemption not queued, the bond must be puttable with a put window open"
+        ));
+    };
+
+    println!(
+        "{bond_id}: {} bonds queued for redemption in put window {}",
+        request.quantity, request.window
+    );
     Ok(())
 }
 
-fn create_new_bond() -> <AssetDefinition as Registered>::With {
-    let curr_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    let currency_id: AssetDefinitionId = "USD#palau".parse().unwrap();
+/// Insert a bonds order into the signer's metadata for the order trigger to pick up
+fn submit_bonds_order(
+    iroha: &Client,
+    order_key: &str,
+    bond_id: AssetDefinitionId,
+    quantity: NonZeroU32,
+    bid: Option<Decimal>,
+) -> Result<()> {
     let limits = Limits::new(1024, 1024);
-    let fee_recipient_account_id: AccountId = "government@palau".parse().unwrap();
+    let mut order = Metadata::new();
 
-    let mut bond_metadata = Metadata::new();
-    bond_metadata
-        .insert_with_limits("currency".parse().unwrap(), currency_id.into(), limits)
-        .unwrap();
-    bond_metadata
-        .insert_with_limits(
-            "quantity".parse().unwrap(),
-            100_u32.into(),
-            limits,
-        )
-        .unwrap();
-    bond_metadata
-        .insert_with_limits(
-            "nominal_value".parse().unwrap(),
-            100_000_f64.try_into().unwrap(), // 100_000$ to make the final value 0.19$
-            limits,
-        )
-        .unwrap();
-    bond_metadata
-        .insert_with_limits(
-            "coupon_rate".parse().unwrap(),
-            0.1.try_into().unwrap(), //10%
-            limits,
-        )
-        .unwrap();
-    bond_metadata
-        .insert_with_limits(
-            "fixed_fee".parse().unwrap(),
-            0.1_f64.try_into().unwrap(),
-            limits,
-        )
-        .unwrap();
-    bond_metadata
-        .insert_with_limits(
-            "fee_recipient_account_id".parse().unwrap(),
-            fee_recipient_account_id.into(),
-            limits,
-        )
+    order
+        .insert_with_limits("bond".parse().unwrap(), bond_id.into(), limits)
         .unwrap();
 
-    bond_metadata
-        .insert_with_limits(
-            "maturation_date_ms".parse().unwrap(),
-            ((curr_time + Duration::from_secs(120)).as_millis() as u64).into(),
-            limits,
-        )
-        .unwrap();
-    bond_metadata
-        .insert_with_limits(
-            "registration_time_ms".parse().unwrap(),
-            (curr_time.as_millis() as u64).into(),
-            limits,
-        )
+    order
+        .insert_with_limits("quantity".parse().unwrap(), quantity.get().into(), limits)
         .unwrap();
 
-    let payment_frequency_seconds = 60_u64;
-    bond_metadata
-        .insert_with_limits(
-            "payment_frequency_seconds".parse().unwrap(),
-            payment_frequency_seconds.try_into().unwrap(),
-            limits,
-        )
-        .unwrap();
+    if let Some(bid) = bid {
+        let bid = bid.to_fixed().ok_or_else(|| eyre!("Bid out of range"))?;
+        order
+            .insert_with_limits("bid".parse().unwrap(), bid.into(), limits)
+            .unwrap();
+    }
 
-    AssetDefinition::new("t-bond#palau".parse().unwrap(), AssetValueType::Quantity)
-        .with_metadata(bond_metadata)
+    iroha.submit_blocking(SetKeyValueExpr::new(
+        iroha.account_id.clone(),
+        order_key.parse::<Name>()?,
+        order,
+    ))?;
+
+    Ok(())
 }
 
-fn buy_bonds(iroha: &Client) -> Result<()> {
-    let buyer: AccountId = "citizen@palau".parse().unwrap();
-    let bond_id: AssetDefinitionId = "t-bond#palau".parse()?;
+/// Find all asset definitions that carry bond terms
+fn list_bonds(iroha: &Client) -> Result<()> {
+    let asset_definitions = iroha
+        .request(FindAllAssetsDefinitions::new())?
+        .collect::<QueryResult<Vec<_>>>()?;
 
-    let limits = Limits::new(1024, 1024);
-    let mut buy_order = Metadata::new();
+    for asset_definition in asset_definitions {
+        if let Ok(terms) = BondTerms::try_from(asset_definition.metadata()) {
+            println!(
+                "{}: {} x {} {}, matures at {} ms",
+                asset_definition.id(),
+                terms.quantity,
+                terms.nominal_value,
+                terms.currency,
+                terms.maturation_date_ms
+            );
+        }
+    }
 
-    buy_order
-        .insert_with_limits("bond".parse().unwrap(), bond_id.into(), limits)
-        .unwrap();
+    Ok(())
+}
 
-    buy_order
-        .insert_with_limits("quantity".parse().unwrap(), 1_u32.into(), limits)
-        .unwrap();
+fn show_bond(iroha: &Client, bond_id: AssetDefinitionId) -> Result<()> {
+    let bond = iroha.request(FindAssetDefinitionById::new(bond_id.clone()))?;
+    let terms = BondTerms::try_from(bond.metadata())?;
+    println!("{bond_id}: {terms:#?}");
+    if let Some(allocation) = Allocation::from_bond_metadata(bond.metadata()) {
+        println!(
+            "{bond_id}: Subscription closed, {} of {} bonds sold to {} investors, {} unsold ({})",
+            allocation.sold,
+            allocation.offered,
+            allocation.investors,
+            allocation.unsold,
+            allocation.unsold_bonds
+        );
+    }
+    if let Some(result) = AuctionResult::from_bond_metadata(bond.metadata()) {
+        println!(
+            "{bond_id}: Auction closed, {} of {} bonds sold to {} bids for {} bonds ({} pricing)",
+            result.sold, result.offered, result.bids, result.bid_quantity, result.pricing
+        );
+        if let (Some(stop_out), Some(stop_out_price)) = (result.stop_out, result.stop_out_price) {
+            println!(
+                "{bond_id}: Stop-out {} {stop_out} at {stop_out_price} per bond",
+                result.basis
+            );
+        }
+        if let Some(average_price) = result.average_price {
+            println!("{bond_id}: Average price {average_price} per bond");
+        }
+        if result.non_competitive_orders > 0 {
+            println!(
+                "{bond_id}: {} of {} bonds allotted to {} non-competitive orders",
+                result.non_competitive_sold,
+                result.non_competitive_quantity,
+                result.non_competitive_orders
+            );
+        }
+        if let Some(non_competitive_price) = result.non_competitive_price {
+            println!("{bond_id}: Non-competitive price {non_competitive_price} per bond");
+        }
+    }
 
-    println!("Buying bond...");
-    let keypair = KeyPair::new(
-        "ed01207233BFC89DCBD68C19FDE6CE6158225298EC1131B6A130D1AEB454C1AB5183C0".parse()?,
-        PrivateKey::from_hex(Algorithm::Ed25519, "9AC47ABF59B356E0BD7DCBBBB4DEC080E302156A48CA907E47CB6AEA1D32719E7233BFC89DCBD68C19FDE6CE6158225298EC1131B6A130D1AEB454C1AB5183C0".as_ref())?,
-    )?;
+    let holders = iroha
+        .request(FindAssetsByAssetDefinitionId::new(bond_id))?
+        .collect::<QueryResult<Vec<_>>>()?;
+    for holder in holders {
+        println!("{}: {}", holder.id().account_id(), holder.value());
+    }
+
+    Ok(())
+}
+
+/// Bond permission tokens defined by the executor that can be granted after genesis
+#[derive(Clone, Copy, ValueEnum)]
+enum BondPermission {
+    /// `CanBuyBond`
+    Buy,
+    /// `CanRedeemBond`
+    Redeem,
+    /// `CanAdministerBond`
+    Administer,
+}
+
+impl BondPermission {
+    fn token(self, bond_id: &AssetDefinitionId) -> Result<PermissionToken> {
+        let definition_id = match self {
+            Self::Buy => "CanBuyBond",
+            Self::Redeem => "CanRedeemBond",
+            Self::Administer => "CanAdministerBond",
+        };
 
-    let tx = TransactionBuilder::new(buyer.clone())
-        .with_instructions([SetKeyValueExpr::new(
-            buyer,
-            "buy_bonds".parse::<Name>()?,
-            buy_order,
-        )])
-        .sign(keypair)?;
+        Ok(PermissionToken::new(
+            definition_id.parse()?,
+            &serde_json::json!({ "asset_definition_id": bond_id.to_string() }),
+        ))
+    }
+}
 
-    iroha.submit_transaction_blocking(&tx)?;
+fn grant_bond_permission(
+    iroha: &Client,
+    account_id: AccountId,
+    permission: BondPermission,
+    bond_id: AssetDefinitionId,
+) -> Result<()> {
+    println!("Granting permission...");
+    iroha.submit_blocking(GrantExpr::new(permission.token(&bond_id)?, account_id))?;
 
     Ok(())
 }
 
-fn main() -> Result<()> {
-    // Prepare blockchain
-    let iroha = Client::new(&ConfigurationProxy::from_path("configs/client.json").build()?)?;
-    register_triggers(&iroha)?;
+fn show_holdings(iroha: &Client, account_id: AccountId) -> Result<()> {
+    let assets = iroha
+        .request(FindAssetsByAccountId::new(account_id))?
+        .collect::<QueryResult<Vec<_>>>()?;
 
-    // Register new bond
-    let new_bond = create_new_bond();
-    register_bond(&iroha, new_bond)?;
+    for asset in assets {
+        println!("{}: {}", asset.id().definition_id(), asset.value());
+    }
 
-    // Buy some bonds
-    buy_bonds(&iroha)?;
+    Ok(())
+}
+
+/// Print bond payment records the triggers keep in the account's metadata
+fn show_payments(iroha: &Client, account_id: AccountId) -> Result<()> {
+    const PAYMENT_PREFIXES: [&str; 6] = [
+        "buy_bond_payment_",
+        "redeem_bond_payment_",
+        "coupon_payment_",
+        "principal_repayment_",
+        "maturity_payment_",
+        "call_payment_",
+    ];
+
+    let account = iroha.request(FindAccountById::new(account_id))?;
+    for (key, value) in account.metadata().iter() {
+        let key: &str = key.as_ref();
+        if !PAYMENT_PREFIXES
+            .iter()
+            .any(|prefix| key.starts_with(prefix))
+        {
+            continue;
+        }
+        // Skip the `*_idx` counters
+        let Value::LimitedMetadata(payment) = value else {
+            continue;
+        };
+
+        println!("{key}:");
+        for (field, value) in payment.iter() {
+            println!("    {field}: {value}");
+        }
+    }
 
     Ok(())
 }
+
+/// Client for Palau T-bonds
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// Path to the client configuration
+    #[arg(long, default_value = "configs/client.json")]
+    config: PathBuf,
+    /// Directory with one key file per account
+    #[arg(long, global = true, default_value = "keys")]
+    keystore: PathBuf,
+    /// Account to sign transactions as, instead of the one in the client configuration
+    #[arg(long = "as", global = true)]
+    signer: Option<AccountId>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Build the executor and add the bond triggers and keystore signatories to the genesis of a fresh network
+    Genesis {
+        /// Genesis to update in place
+        #[arg(long, default_value = "configs/peer/genesis.json")]
+        genesis: PathBuf,
+        /// Authority the triggers are executed as
+        #[arg(long, default_value = "government@palau")]
+        authority: AccountId,
+    },
+    /// Register a new bond described by a specification file and mint it to the issuer
+    IssueBond {
+        /// Path to the `.toml` or `.json` bond specification
+        #[arg(long)]
+        spec: PathBuf,
+    },
+    /// Replace the holidays of a calendar stored in the metadata of a domain
+    SetHolidays {
+        /// Domain whose metadata holds the calendar
+        #[arg(long, default_value = "palau")]
+        domain: DomainId,
+        /// Name of the calendar bond specifications refer to
+        #[arg(long)]
+        calendar: String,
+        /// Holidays as ISO-8601 dates, e.g. `2026-12-25`
+        #[arg(long = "date", value_delimiter = ',')]
+        dates: Vec<String>,
+    },
+    /// Publish the current value of a reference rate as the signer, e.g. `--as oracle@palau`
+    PublishRate {
+        /// Name of the reference rate bond specifications refer to, e.g. `SOFR`
+        #[arg(long)]
+        reference_rate: Name,
+        /// Yearly rate, e.g. `0.0525` for 5.25%
+        #[arg(long)]
+        rate: String,
+    },
+    /// Publish the current value of an inflation index as the signer, e.g. `--as oracle@palau`
+    PublishIndex {
+        /// Name of the inflation index bond specifications refer to, e.g. `CPI`
+        #[arg(long)]
+        index: Name,
+        /// Index level, e.g. `310.326`
+        #[arg(long)]
+        value: String,
+    },
+    /// Generate a key pair for an account and store it in the keystore
+    NewKey {
+        /// Account the key pair belongs to
+        #[arg(long)]
+        account: AccountId,
+        /// Encrypt the private key with a passphrase
+        #[arg(long)]
+        encrypt: bool,
+    },
+    /// Register an investor with its KYC record and grant the bond triggers access to it
+    OnboardInvestor {
+        /// Account to register
+        #[arg(long)]
+        account: AccountId,
+        /// Public key of the account, read from the keystore if not given
+        #[arg(long)]
+        public_key: Option<PublicKey>,
+        /// Currencies the investor pays in
+        #[arg(long = "currency", default_value = "USD#palau")]
+        currencies: Vec<AssetDefinitionId>,
+        /// Party that verified the investor's identity
+        #[arg(long)]
+        kyc_provider: String,
+        /// Reference of the verification with the provider
+        #[arg(long)]
+        kyc_reference: String,
+        /// ISO 3166-1 alpha-2 country code of the investor
+        #[arg(long)]
+        country: String,
+    },
+    /// Buy bonds from the issuer as the signer
+    Buy {
+        /// Bond to buy
+        #[arg(long)]
+        bond: AssetDefinitionId,
+        /// Number of bonds to buy
+        #[arg(long, default_value = "1")]
+        quantity: NonZeroU32,
+        /// Price, as a percentage of par, or yield bid for auctioned bonds,
+        /// a non-competitive order if omitted
+        #[arg(long)]
+        bid: Option<Decimal>,
+    },
+    /// Request early redemption of puttable bonds as the signer,
+    /// settled after the open put window closes
+    ///
+    /// Bonds are no longer sold back to the issuer on the spot: bonds without put windows
+    /// can't be redeemed before maturity, and the command fails unless the put is queued.
+    Redeem {
+        /// Bond to redeem
+        #[arg(long)]
+        bond: AssetDefinitionId,
+        /// Number of bonds to redeem
+        #[arg(long, default_value = "1")]
+        quantity: NonZeroU32,
+    },
+    /// Call a callable bond for early redemption after its notice period,
+    /// requires `CanAdministerBond`
+    CallBond {
+        /// Bond to call
+        #[arg(long)]
+        bond: AssetDefinitionId,
+    },
+    /// List all registered bonds
+    ListBonds,
+    /// Show terms and holders of a bond
+    ShowBond {
+        /// Bond to show
+        #[arg(long)]
+        bond: AssetDefinitionId,
+    },
+    /// Grant a bond permission to an account, signed by the bond issuer
+    Grant {
+        /// Account receiving the permission
+        #[arg(long)]
+        account: AccountId,
+        /// Permission to grant
+        #[arg(long, value_enum)]
+        permission: BondPermission,
+        /// Bond the permission is granted for
+        #[arg(long)]
+        bond: AssetDefinitionId,
+    },
+    /// Show assets held by an account
+    Holdings {
+        /// Account whose assets to show
+        #[arg(long)]
+        account: AccountId,
+    },
+    /// Show bond payments made to or by an account
+    Payments {
+        /// Account whose payments to show
+        #[arg(long)]
+        account: AccountId,
+    },
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let keystore = Keystore::new(&args.keystore);
+
+    // Commands that don't talk to the network
+    match &args.command {
+        Command::NewKey { account, encrypt } => {
+            let public_key = keystore.generate(account, *encrypt)?;
+            println!("{account}: {public_key}");
+            return Ok(());
+        }
+        Command::Genesis { genesis, authority } => {
+            return write_genesis(genesis, authority, &keystore)
+        }
+        _ => {}
+    }
+
+    let mut config = ConfigurationProxy::from_path(&args.config);
+    if let Some(signer) = args.signer {
+        let key_pair = keystore.load(&signer)?;
+        config.account_id = Some(signer);
+        config.public_key = Some(key_pair.public_key().clone());
+        config.private_key = Some(key_pair.private_key().clone());
+    }
+    let iroha = Client::new(&config.build()?)?;
+
+    match args.command {
+        Command::IssueBond { spec } => {
+            let spec = BondSpec::from_path(spec)?;
+            let holidays = match spec.holiday_calendar() {
+                Some(name) => holiday_calendar(&iroha, spec.id.domain_id(), name)?,
+                None => HolidayCalendar::default(),
+            };
+            register_bond(&iroha, spec.new_bond(&holidays)?)
+        }
+        Command::SetHolidays {
+            domain,
+            calendar,
+            dates,
+        } => set_holidays(&iroha, domain, &calendar, &dates),
+        Command::PublishRate {
+            reference_rate,
+            rate,
+        } => publish_reference_rate(&iroha, &reference_rate, &rate),
+        Command::PublishIndex { index, value } => publish_inflation_index(&iroha, &index, &value),
+        Command::NewKey { .. } | Command::Genesis { .. } => {
+            unreachable!("Handled before connecting to the network")
+        }
+        Command::OnboardInvestor {
+            account,
+            public_key,
+            currencies,
+            kyc_provider,
+            kyc_reference,
+            country,
+        } => {
+            let public_key = match public_key {
+                Some(public_key) => public_key,
+                None => keystore.public_key(&account)?,
+            };
+            let kyc = Kyc {
+                provider: kyc_provider,
+                reference: kyc_reference,
+                country,
+            };
+            onboard_investor(&iroha, account, public_key, kyc, &currencies)
+        }
+        Command::Buy {
+            bond,
+            quantity,
+            bid,
+        } => buy_bonds(&iroha, bond, quantity, bid),
+        Command::Redeem { bond, quantity } => redeem_bonds(&iroha, bond, quantity),
+        Command::CallBond { bond } => call_bond(&iroha, bond),
+        Command::ListBonds => list_bonds(&iroha),
+        Command::ShowBond { bond } => show_bond(&iroha, bond),
+        Command::Grant {
+            account,
+            permission,
+            bond,
+        } => grant_bond_permission(&iroha, account, permission, bond),
+        Command::Holdings { account } => show_holdings(&iroha, account),
+        Command::Payments { account } => show_payments(&iroha, account),
+    }
+}
@@ -0,0 +1,108 @@
+//! Onboarding of investor accounts
+//!
+//! A single transaction registers the investor with its KYC record and grants the
+//! bond triggers' authority the permissions it needs to settle the investor's orders.
+//! It must be signed by the triggers' authority holding `CanOnboardInvestor`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bond_terms::investor::{kyc, KYC_KEY};
+use eyre::{eyre, Result};
+use iroha_client::{
+    client::Client,
+    crypto::PublicKey,
+    data_model::{
+        metadata::{Limits, Metadata},
+        prelude::*,
+    },
+};
+
+/// Identity verification of an investor
+pub struct Kyc {
+    pub provider: String,
+    pub reference: String,
+    pub country: String,
+}
+
+impl Kyc {
+    fn into_metadata(self, verified_at_ms: u64) -> Result<Metadata> {
+        let limits = Limits::new(1024, 1024);
+        let mut record = Metadata::new();
+
+        for (key, value) in [
+            (kyc::PROVIDER, Value::String(self.provider)),
+            (kyc::REFERENCE, Value::String(self.reference)),
+            (kyc::COUNTRY, Value::String(self.country)),
+            (kyc::VERIFIED_AT_MS, verified_at_ms.into()),
+        ] {
+            record.insert_with_limits(key.parse()?, value, limits)?;
+        }
+
+        let mut metadata = Metadata::new();
+        metadata.insert_with_limits(KYC_KEY.parse()?, record.into(), limits)?;
+
+        Ok(metadata)
+    }
+}
+
+/// Register an investor and grant the signer what the bond triggers need over its account
+///
+/// `CanTransferUserAsset` is granted for the investor's asset of every given currency.
+pub fn onboard_investor(
+    iroha: &Client,
+    account_id: AccountId,
+    public_key: PublicKey,
+    kyc: Kyc,
+    currencies: &[AssetDefinitionId],
+) -> Result<()> {
+    if iroha
+        .request(FindAccountById::new(account_id.clone()))
+        .is_ok()
+    {
+        return Err(eyre!("{account_id}: Account already registered"));
+    }
+
+    let verified_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)?
+        .as_millis()
+        .try_into()?;
+    let new_account = Account::new(account_id.clone(), [public_key])
+        .with_metadata(kyc.into_metadata(verified_at_ms)?);
+
+    let mut instructions: Vec<InstructionExpr> = vec![RegisterExpr::new(new_account).into()];
+    for currency in currencies {
+        let asset_id = AssetId::new(currency.clone(), account_id.clone());
+        instructions.push(grant(
+            "CanTransferUserAsset",
+            serde_json::json!({ "asset_id": asset_id.to_string() }),
+            &iroha.account_id,
+        )?);
+    }
+    for definition_id in [
+        "CanSetKeyValueInUserAccount",
+        "CanRemoveKeyValueInUserAccount",
+    ] {
+        instructions.push(grant(
+            definition_id,
+            serde_json::json!({ "account_id": account_id.to_string() }),
+            &iroha.account_id,
+        )?);
+    }
+
+    println!("Onboarding {account_id}...");
+    iroha.submit_all_blocking(instructions)?;
+
+    Ok(())
+}
+
+fn grant(
+    definition_id: &str,
+    payload: serde_json::Value,
+    destination_id: &AccountId,
+) -> Result<InstructionExpr> {
+    Ok(GrantExpr::new(
+        PermissionToken::new(definition_id.parse()?, &payload),
+        destination_id.clone(),
+    )
+    .into())
+}
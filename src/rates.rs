@@ -0,0 +1,53 @@
+//! Reference rates and inflation indices published by an oracle account
+//!
+//! Floating-rate notes fix their coupon rate to the value the oracle named in the bond terms
+//! has published in its own metadata, see [`bond_terms::floating_rate`]. Inflation-linked bonds
+//! scale their principal by a published index the same way, see [`bond_terms::inflation`].
+
+use bond_terms::{floating_rate::reference_rate_key, inflation::index_key, money::Decimal};
+use eyre::{eyre, Result, WrapErr as _};
+use iroha_client::{client::Client, data_model::prelude::*};
+
+/// Publish the current value of a reference rate in the signer's metadata
+///
+/// The rate is a yearly decimal rate, e.g. `0.0525` for 5.25%.
+pub fn publish_reference_rate(iroha: &Client, reference_rate: &Name, rate: &str) -> Result<()> {
+    let rate = rate
+        .parse::<Decimal>()
+        .wrap_err("`rate` is not a valid decimal")?
+        .to_fixed()
+        .ok_or_else(|| eyre!("`rate` is out of range"))?;
+
+    println!("Publishing {reference_rate} rate {rate}...");
+    iroha.submit_blocking(SetKeyValueExpr::new(
+        iroha.account_id.clone(),
+        reference_rate_key(reference_rate),
+        rate,
+    ))?;
+
+    Ok(())
+}
+
+/// Publish the current value of an inflation index in the signer's metadata
+///
+/// The value is a positive decimal index level, e.g. `310.326` for CPI.
+pub fn publish_inflation_index(iroha: &Client, index: &Name, value: &str) -> Result<()> {
+    let value = value
+        .parse::<Decimal>()
+        .wrap_err("`value` is not a valid decimal")?;
+    if value.is_negative() || value.is_zero() {
+        return Err(eyre!("`value` must be positive"));
+    }
+    let value = value
+        .to_fixed()
+        .ok_or_else(|| eyre!("`value` is out of range"))?;
+
+    println!("Publishing {index} index value {value}...");
+    iroha.submit_blocking(SetKeyValueExpr::new(
+        iroha.account_id.clone(),
+        index_key(index),
+        value,
+    ))?;
+
+    Ok(())
+}
@@ -0,0 +1,615 @@
+//! Declarative bond specification files
+//!
+//! A bond specification describes a single issuance in TOML or JSON and is
+//! converted into the [`BondTerms`] stored in the bond's asset definition.
+
+use std::{fs, path::Path, time::UNIX_EPOCH};
+
+use bond_terms::{
+    allotment::{Allotment, AllotmentRounding},
+    amortization::{AmortizationSchedule, PrincipalRepayment},
+    auction::{Auction, BidBasis, NonCompetitiveTranche},
+    bond_type::BondType,
+    calendar::{BusinessDayConvention, HolidayCalendar, ScheduleRule},
+    day_count::DayCount,
+    floating_rate::FloatingRate,
+    inflation::InflationIndex,
+    money::{Decimal, RoundingMode},
+    price::{Price, PriceStep},
+    put::{PutSchedule, PutWindow},
+    subscription::{SubscriptionPeriod, UnsoldBonds},
+    BondTerms,
+};
+use eyre::{eyre, Result, WrapErr as _};
+use iroha_client::data_model::{
+    asset::{AssetDefinition, AssetValueType},
+    prelude::*,
+    Registered,
+};
+use serde::Deserialize;
+
+/// Version of the specification format understood by this client
+pub const SPEC_VERSION: u32 = 1;
+
+/// Bond specification as written in the file
+///
+/// Amounts are decimal strings so that they are never rounded through a float.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BondSpec {
+    /// Version of the specification format
+    pub version: u32,
+    /// Id of the bond asset definition, e.g. `t-bond#palau`
+    pub id: AssetDefinitionId,
+    /// `fixed_rate` (the default), `floating_rate`, `zero_coupon` or `inflation_linked`
+    pub bond_type: Option<String>,
+    /// Currency in which the bond is bought and pays out, e.g. `USD#palau`
+    pub currency: AssetDefinitionId,
+    /// Number of bonds minted to the issuer
+    pub quantity: u32,
+    /// Face value of a single bond, e.g. `"100000"`
+    pub nominal_value: String,
+    /// Yearly coupon rate, e.g. `"0.1"` for 10%, omitted for zero-coupon and floating-rate bonds
+    pub coupon_rate: Option<String>,
+    /// Absolute fee charged per purchase, e.g. `"0.1"`
+    pub fixed_fee: String,
+    /// Fractional digits of the smallest currency unit, e.g. `2` for cents (the default)
+    #[serde(default = "default_currency_precision")]
+    pub currency_precision: u32,
+    /// Rounding of amounts to the currency precision, `half_even` (the default) or `down`
+    pub rounding_mode: Option<String>,
+    /// Reference rate the coupons of a floating-rate note follow
+    pub floating_rate: Option<FloatingRateSpec>,
+    /// Price index the principal of an inflation-linked bond is scaled by
+    pub inflation_index: Option<InflationIndexSpec>,
+    /// Price of a single bond, an amount or a percentage of par, e.g. `"98.5%"` (par if omitted)
+    pub issue_price: Option<String>,
+    /// Prices replacing the issue price during the subscription period
+    #[serde(default)]
+    pub price_schedule: Vec<PriceStepSpec>,
+    /// Prices at which the issuer can call the bond from the given dates on
+    #[serde(default)]
+    pub call_schedule: Vec<PriceStepSpec>,
+    /// Human-readable time between a call and the redemption, e.g. `30days`
+    pub call_notice_period: Option<String>,
+    /// Windows in which holders can request early redemption of the bond
+    #[serde(default)]
+    pub put_windows: Vec<PutWindowSpec>,
+    /// Human-readable time between the close of a put window and the settlement, e.g. `30days`
+    pub put_notice_period: Option<String>,
+    /// Deduction from the principal repaid on a put, a percentage of it, e.g. `"2%"`,
+    /// or forfeited coupons, e.g. `"1 coupon"`
+    pub put_penalty: Option<String>,
+    /// Period during which the bond is sold, until maturity if omitted
+    pub subscription: Option<SubscriptionSpec>,
+    /// Account receiving the purchase fee
+    pub fee_recipient: AccountId,
+    /// ISO-8601 (RFC 3339) issue date, e.g. `2026-11-01T00:00:00Z`
+    pub issue_date: String,
+    /// ISO-8601 (RFC 3339) maturity date
+    pub maturity_date: String,
+    /// Human-readable period between coupon payments, e.g. `3months`,
+    /// exclusive with `coupon_schedule`
+    pub payment_frequency: Option<String>,
+    /// Day-count convention coupons accrue by, e.g. `ACT/ACT ICMA` (`SECONDS/365` if omitted)
+    pub day_count_convention: Option<String>,
+    /// Calendar dates on which coupons are paid, exclusive with `payment_frequency`
+    pub coupon_schedule: Option<CouponScheduleSpec>,
+    /// Slices of the principal repaid before maturity, the rest is repaid at maturity
+    #[serde(default)]
+    pub amortization: Vec<RepaymentSpec>,
+}
+
+/// Rule generating the coupon dates of a bond
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CouponScheduleSpec {
+    /// `monthly`, `quarterly`, `semi-annual` or `annual`
+    pub frequency: String,
+    /// ISO-8601 (RFC 3339) date coupon dates are rolled from (the issue date if omitted)
+    pub anchor_date: Option<String>,
+    /// Keep coupons on the last day of the month if the anchor date is one
+    #[serde(default)]
+    pub end_of_month: bool,
+    /// `unadjusted` (the default), `following` or `modified_following`
+    pub business_day_convention: Option<String>,
+    /// Name of the holiday calendar stored in the metadata of the bond's domain
+    pub holiday_calendar: Option<String>,
+}
+
+/// Reference rate and margins of a floating-rate note
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FloatingRateSpec {
+    /// Name of the published reference rate, e.g. `SOFR`
+    pub reference_rate: Name,
+    /// Account publishing the reference rate
+    pub oracle: AccountId,
+    /// Margin added to the reference rate, e.g. `"0.005"` for 50 basis points
+    pub spread: String,
+    /// Lowest coupon rate paid
+    pub floor: Option<String>,
+    /// Highest coupon rate paid
+    pub cap: Option<String>,
+}
+
+/// Price index and its base value of an inflation-linked bond
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InflationIndexSpec {
+    /// Name of the published index, e.g. `CPI`
+    pub index: Name,
+    /// Account publishing the index
+    pub oracle: AccountId,
+    /// Index level the nominal value is expressed at, e.g. `"310.326"`
+    pub base_value: String,
+}
+
+/// Price that applies to purchases, or calls, from the given date on
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PriceStepSpec {
+    /// ISO-8601 (RFC 3339) date from which the price applies
+    pub from: String,
+    /// Price of a single bond, an amount or a percentage of par
+    pub price: String,
+}
+
+/// Primary offering period of a bond
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubscriptionSpec {
+    /// ISO-8601 (RFC 3339) date the subscription opens
+    pub start: String,
+    /// ISO-8601 (RFC 3339) date the subscription closes
+    pub end: String,
+    /// `burn` or `retain` (the default) the bonds unsold at the close
+    pub unsold_bonds: Option<String>,
+    /// Allotment of the orders escrowed until the close, bonds are sold first-come if omitted
+    pub allotment: Option<AllotmentSpec>,
+    /// Auction of the bonds to the bids sealed until the close, exclusive with `allotment`
+    pub auction: Option<AuctionSpec>,
+}
+
+/// Sealed-bid auction of the bonds at the close of the subscription
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuctionSpec {
+    /// `uniform` (every winning bid pays the stop-out price) or `discriminatory`
+    pub pricing: String,
+    /// `price` (a percentage of par, the default) or `yield` (zero-coupon bonds only)
+    pub bid_basis: Option<String>,
+    /// Smallest number of bonds that can be bid for or allotted (1 if omitted)
+    pub lot_size: Option<u32>,
+    /// `down` (the default) or `largest_remainder` rounding of the allotment at the stop-out
+    pub rounding: Option<String>,
+    /// Lowest price or highest yield accepted, e.g. `"97"` or `"0.05"`
+    pub reserve: Option<String>,
+    /// Tranche for orders without a bid, filled first at the clearing price
+    pub non_competitive: Option<NonCompetitiveSpec>,
+}
+
+/// Caps of the non-competitive tranche of an auction
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NonCompetitiveSpec {
+    /// Most bonds a single investor can order
+    pub investor_cap: u32,
+    /// Most bonds of the tranche
+    pub total_cap: u32,
+}
+
+/// Allotment of the bonds ordered during the subscription period
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AllotmentSpec {
+    /// Smallest number of bonds that can be ordered or allotted (1 if omitted)
+    pub lot_size: Option<u32>,
+    /// `down` (the default) or `largest_remainder` rounding of pro-rata allotments to lots
+    pub rounding: Option<String>,
+    /// Countries of the investors served first, tier by tier, e.g. `[["PW"], ["FM", "MH"]]`
+    #[serde(default)]
+    pub priority_tiers: Vec<Vec<String>>,
+}
+
+/// Window in which holders can request a put
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PutWindowSpec {
+    /// ISO-8601 (RFC 3339) date the window opens
+    pub start: String,
+    /// ISO-8601 (RFC 3339) date the window closes
+    pub end: String,
+}
+
+/// Slice of the principal repaid on a date
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RepaymentSpec {
+    /// ISO-8601 (RFC 3339) date of the repayment
+    pub date: String,
+    /// Repaid fraction of the face value, e.g. `"0.25"` for a quarter
+    pub fraction: String,
+}
+
+impl BondSpec {
+    /// Read a specification from a `.toml` or `.json` file
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be read or parsed, or has an unsupported version
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .wrap_err_with(|| format!("{}: Failed to read bond specification", path.display()))?;
+
+        let spec: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content)?,
+            Some("json") => serde_json::from_str(&content)?,
+            _ => {
+                return Err(eyre!(
+                    "{}: Bond specification must be a `.toml` or `.json` file",
+                    path.display()
+                ))
+            }
+        };
+
+        if spec.version != SPEC_VERSION {
+            return Err(eyre!(
+                "{}: Unsupported bond specification version {} (expected {SPEC_VERSION})",
+                path.display(),
+                spec.version
+            ));
+        }
+
+        Ok(spec)
+    }
+
+    /// Name of the holiday calendar coupon dates are adjusted by, if any
+    pub fn holiday_calendar(&self) -> Option<&str> {
+        self.coupon_schedule
+            .as_ref()
+            .and_then(|schedule| schedule.holiday_calendar.as_deref())
+    }
+
+    /// Convert the specification into validated bond terms
+    ///
+    /// Coupon dates are adjusted to the business days of the given holiday calendar.
+    ///
+    /// # Errors
+    ///
+    /// Fails if any field can't be parsed or the resulting terms are invalid
+    pub fn terms(&self, holidays: &HolidayCalendar) -> Result<BondTerms> {
+        let registration_time_ms = parse_date_ms("issue_date", &self.issue_date)?;
+        let maturation_date_ms = parse_date_ms("maturity_date", &self.maturity_date)?;
+
+        let bond_type = match &self.bond_type {
+            Some(bond_type) => bond_type
+                .parse()
+                .wrap_err("`bond_type` is not a known bond type")?,
+            None => BondType::FixedRate,
+        };
+
+        let (payment_frequency_seconds, coupon_schedule) =
+            match (&self.payment_frequency, &self.coupon_schedule) {
+                (None, None) if !bond_type.pays_coupons() => (0, None),
+                (Some(payment_frequency), None) => (
+                    humantime::parse_duration(payment_frequency)
+                        .wrap_err("`payment_frequency` is not a valid duration")?
+                        .as_secs(),
+                    None,
+                ),
+                (None, Some(schedule)) => (
+                    0,
+                    Some(schedule.rule(registration_time_ms)?.generate(
+                        registration_time_ms,
+                        maturation_date_ms,
+                        holidays,
+                    )),
+                ),
+                _ => {
+                    return Err(eyre!(
+                        "{}: Give either `payment_frequency` or `coupon_schedule`",
+                        self.id
+                    ))
+                }
+            };
+
+        let terms = BondTerms {
+            bond_type,
+            currency: self.currency.clone(),
+            quantity: self.quantity,
+            nominal_value: parse_decimal("nominal_value", &self.nominal_value)?,
+            coupon_rate: match &self.coupon_rate {
+                Some(coupon_rate) => parse_decimal("coupon_rate", coupon_rate)?,
+                None if !bond_type.has_fixed_coupon_rate() => Decimal::ZERO,
+                None => return Err(eyre!("{}: `coupon_rate` is required", self.id)),
+            },
+            floating_rate: self
+                .floating_rate
+                .as_ref()
+                .map(FloatingRateSpec::floating_rate)
+                .transpose()?,
+            inflation_index: self
+                .inflation_index
+                .as_ref()
+                .map(|inflation_index| {
+                    Ok(InflationIndex {
+                        index: inflation_index.index.clone(),
+                        oracle: inflation_index.oracle.clone(),
+                        base_value: parse_decimal(
+                            "inflation_index.base_value",
+                            &inflation_index.base_value,
+                        )?,
+                    })
+                })
+                .transpose()?,
+            fixed_fee: parse_decimal("fixed_fee", &self.fixed_fee)?,
+            fee_recipient_account_id: self.fee_recipient.clone(),
+            registration_time_ms,
+            maturation_date_ms,
+            payment_frequency_seconds,
+            day_count: match &self.day_count_convention {
+                Some(day_count) => day_count
+                    .parse()
+                    .wrap_err("`day_count_convention` is not a known convention")?,
+                None => DayCount::Seconds365,
+            },
+            coupon_schedule,
+            currency_precision: self.currency_precision,
+            rounding: match &self.rounding_mode {
+                Some(rounding) => rounding
+                    .parse()
+                    .wrap_err("`rounding_mode` is not a known rounding mode")?,
+                None => RoundingMode::HalfEven,
+            },
+            amortization: AmortizationSchedule {
+                repayments: self
+                    .amortization
+                    .iter()
+                    .map(|repayment| {
+                        Ok(PrincipalRepayment {
+                            date_ms: parse_date_ms("amortization.date", &repayment.date)?,
+                            fraction: parse_decimal("amortization.fraction", &repayment.fraction)?,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            },
+            issue_price: self
+                .issue_price
+                .as_deref()
+                .map(|price| parse_price("issue_price", price))
+                .transpose()?,
+            price_schedule: self
+                .price_schedule
+                .iter()
+                .map(|step| {
+                    Ok(PriceStep {
+                        from_ms: parse_date_ms("price_schedule.from", &step.from)?,
+                        price: parse_price("price_schedule.price", &step.price)?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+            call_schedule: self
+                .call_schedule
+                .iter()
+                .map(|step| {
+                    Ok(PriceStep {
+                        from_ms: parse_date_ms("call_schedule.from", &step.from)?,
+                        price: parse_price("call_schedule.price", &step.price)?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+            call_notice_period_seconds: match &self.call_notice_period {
+                Some(call_notice_period) => humantime::parse_duration(call_notice_period)
+                    .wrap_err("`call_notice_period` is not a valid duration")?
+                    .as_secs(),
+                None => 0,
+            },
+            put_schedule: PutSchedule {
+                windows: self
+                    .put_windows
+                    .iter()
+                    .map(|window| {
+                        Ok(PutWindow {
+                            start_ms: parse_date_ms("put_windows.start", &window.start)?,
+                            end_ms: parse_date_ms("put_windows.end", &window.end)?,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+                notice_period_seconds: match &self.put_notice_period {
+                    Some(put_notice_period) => humantime::parse_duration(put_notice_period)
+                        .wrap_err("`put_notice_period` is not a valid duration")?
+                        .as_secs(),
+                    None => 0,
+                },
+                penalty: self
+                    .put_penalty
+                    .as_deref()
+                    .map(|penalty| {
+                        penalty
+                            .parse()
+                            .wrap_err("`put_penalty` is not a percentage or forfeited coupons")
+                    })
+                    .transpose()?,
+            },
+            subscription: self
+                .subscription
+                .as_ref()
+                .map(|subscription| {
+                    Ok(SubscriptionPeriod {
+                        start_ms: parse_date_ms("subscription.start", &subscription.start)?,
+                        end_ms: parse_date_ms("subscription.end", &subscription.end)?,
+                        unsold_bonds: match &subscription.unsold_bonds {
+                            Some(unsold_bonds) => unsold_bonds.parse().wrap_err(
+                                "`subscription.unsold_bonds` is not `burn` or `retain`",
+                            )?,
+                            None => UnsoldBonds::Retain,
+                        },
+                    })
+                })
+                .transpose()?,
+            allotment: self
+                .subscription
+                .as_ref()
+                .and_then(|subscription| subscription.allotment.as_ref())
+                .map(|allotment| {
+                    Ok(Allotment {
+                        lot_size: allotment.lot_size.unwrap_or(1),
+                        rounding: match &allotment.rounding {
+                            Some(rounding) => rounding.parse().wrap_err(
+                                "`subscription.allotment.rounding` is not `down` or `largest_remainder`",
+                            )?,
+                            None => AllotmentRounding::Down,
+                        },
+                        priority_tiers: allotment.priority_tiers.clone(),
+                    })
+                })
+                .transpose()?,
+            auction: self
+                .subscription
+                .as_ref()
+                .and_then(|subscription| subscription.auction.as_ref())
+                .map(|auction| {
+                    Ok(Auction {
+                        pricing: auction.pricing.parse().wrap_err(
+                            "`subscription.auction.pricing` is not `uniform` or `discriminatory`",
+                        )?,
+                        basis: match &auction.bid_basis {
+                            Some(basis) => basis.parse().wrap_err(
+                                "`subscription.auction.bid_basis` is not `price` or `yield`",
+                            )?,
+                            None => BidBasis::Price,
+                        },
+                        lot_size: auction.lot_size.unwrap_or(1),
+                        rounding: match &auction.rounding {
+                            Some(rounding) => rounding.parse().wrap_err(
+                                "`subscription.auction.rounding` is not `down` or `largest_remainder`",
+                            )?,
+                            None => AllotmentRounding::Down,
+                        },
+                        reserve: auction
+                            .reserve
+                            .as_deref()
+                            .map(|reserve| parse_decimal("subscription.auction.reserve", reserve))
+                            .transpose()?,
+                        non_competitive: auction.non_competitive.as_ref().map(|tranche| {
+                            NonCompetitiveTranche {
+                                investor_cap: tranche.investor_cap,
+                                total_cap: tranche.total_cap,
+                            }
+                        }),
+                    })
+                })
+                .transpose()?,
+        };
+        terms
+            .validate()
+            .wrap_err_with(|| format!("{}: Invalid bond specification", self.id))?;
+
+        Ok(terms)
+    }
+
+    /// Convert the specification into the asset definition submitted to `register_bond`
+    ///
+    /// # Errors
+    ///
+    /// Fails if the specification doesn't describe valid bond terms
+    pub fn new_bond(
+        &self,
+        holidays: &HolidayCalendar,
+    ) -> Result<<AssetDefinition as Registered>::With> {
+        Ok(
+            AssetDefinition::new(self.id.clone(), AssetValueType::Quantity)
+                .with_metadata(Metadata::try_from(self.terms(holidays)?)?),
+        )
+    }
+}
+
+impl FloatingRateSpec {
+    fn floating_rate(&self) -> Result<FloatingRate> {
+        Ok(FloatingRate {
+            reference_rate: self.reference_rate.clone(),
+            oracle: self.oracle.clone(),
+            spread: parse_decimal("floating_rate.spread", &self.spread)?,
+            floor: self
+                .floor
+                .as_deref()
+                .map(|floor| parse_decimal("floating_rate.floor", floor))
+                .transpose()?,
+            cap: self
+                .cap
+                .as_deref()
+                .map(|cap| parse_decimal("floating_rate.cap", cap))
+                .transpose()?,
+        })
+    }
+}
+
+impl CouponScheduleSpec {
+    fn rule(&self, registration_time_ms: u64) -> Result<ScheduleRule> {
+        let frequency_months = match self.frequency.as_str() {
+            "monthly" => 1,
+            "quarterly" => 3,
+            "semi-annual" => 6,
+            "annual" => 12,
+            _ => {
+                return Err(eyre!(
+                    "`frequency` must be one of `monthly`, `quarterly`, `semi-annual`, `annual`"
+                ))
+            }
+        };
+
+        Ok(ScheduleRule {
+            frequency_months,
+            anchor_date_ms: match &self.anchor_date {
+                Some(anchor_date) => parse_date_ms("anchor_date", anchor_date)?,
+                None => registration_time_ms,
+            },
+            end_of_month: self.end_of_month,
+            business_day_convention: match &self.business_day_convention {
+                Some(convention) => convention
+                    .parse()
+                    .wrap_err("`business_day_convention` is not a known convention")?,
+                None => BusinessDayConvention::Unadjusted,
+            },
+        })
+    }
+}
+
+const fn default_currency_precision() -> u32 {
+    2
+}
+
+fn parse_decimal(field: &str, value: &str) -> Result<Decimal> {
+    let decimal: Decimal = value
+        .parse()
+        .wrap_err_with(|| format!("`{field}` is not a valid decimal"))?;
+    if decimal.to_fixed().is_none() {
+        return Err(eyre!("`{field}` is out of range"));
+    }
+
+    Ok(decimal)
+}
+
+fn parse_price(field: &str, value: &str) -> Result<Price> {
+    let price: Price = value
+        .parse()
+        .wrap_err_with(|| format!("`{field}` is not a valid price"))?;
+    let (Price::Absolute(amount) | Price::PercentOfPar(amount)) = price;
+    if amount.to_fixed().is_none() {
+        return Err(eyre!("`{field}` is out of range"));
+    }
+
+    Ok(price)
+}
+
+pub fn parse_date_ms(field: &str, value: &str) -> Result<u64> {
+    let date = humantime::parse_rfc3339_weak(value)
+        .wrap_err_with(|| format!("`{field}` is not an ISO-8601 date"))?;
+    let since_epoch = date
+        .duration_since(UNIX_EPOCH)
+        .wrap_err_with(|| format!("`{field}` is before UNIX epoch"))?;
+
+    Ok(since_epoch.as_millis().try_into()?)
+}
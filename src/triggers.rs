@@ -0,0 +1,137 @@
+//! Bond triggers registered in genesis
+//!
+//! The hash of the compiled executable is stored in the metadata of every trigger so that
+//! the code running on chain can be checked against a local build. After genesis the executor
+//! doesn't let client transactions register triggers, so changed triggers are rolled out
+//! with a new genesis.
+
+use eyre::Result;
+use iroha_client::{crypto::Hash, data_model::prelude::*};
+
+/// Trigger metadata key under which the hash of the trigger executable is stored
+pub const EXECUTABLE_HASH_KEY: &str = "executable_hash";
+
+/// Trigger together with the hash of its compiled executable
+pub struct BondTrigger {
+    pub trigger: Trigger<TriggeringFilterBox, Executable>,
+    pub executable_hash: Hash,
+}
+
+impl BondTrigger {
+    fn new(
+        id: TriggerId,
+        wasm: &[u8],
+        repeats: Repeats,
+        authority: AccountId,
+        filter: TriggeringFilterBox,
+    ) -> Self {
+        Self {
+            trigger: Trigger::new(
+                id,
+                Action::new(
+                    WasmSmartContract::from_compiled(wasm.to_vec()),
+                    repeats,
+                    authority,
+                    filter,
+                ),
+            ),
+            executable_hash: Hash::new(wasm),
+        }
+    }
+
+    pub fn id(&self) -> &TriggerId {
+        self.trigger.id()
+    }
+
+    /// Instructions registering the trigger and recording its executable hash
+    pub fn register(self) -> [InstructionExpr; 2] {
+        let id = self.id().clone();
+
+        [
+            RegisterExpr::new(self.trigger).into(),
+            SetKeyValueExpr::new(
+                id,
+                EXECUTABLE_HASH_KEY.parse::<Name>().unwrap(),
+                Value::String(self.executable_hash.to_string()),
+            )
+            .into(),
+        ]
+    }
+}
+
+/// Compile and optimize a smart contract crate from `smart_contracts/`
+pub fn build_wasm_bytes(name: &str) -> Result<Vec<u8>> {
+    println!("Building {name}...");
+
+    iroha_wasm_builder::Builder::new(&format!("smart_contracts/{name}"))
+        // TODO: Available in RC22
+        //.show_output()
+        .build()?
+        .optimize()?
+        .into_bytes()
+}
+
+/// Build the `register_bond`, `call_bond`, `buy_bonds_trigger` and `redeem_bonds_trigger` triggers
+pub fn bond_triggers(account_id: &AccountId) -> Result<Vec<BondTrigger>> {
+    let register_bond_wasm = build_wasm_bytes("register_bond")?;
+    let call_bond_wasm = build_wasm_bytes("call_bond")?;
+    let buy_bonds_wasm = build_wasm_bytes("buy_bonds")?;
+    let redeem_bonds_wasm = build_wasm_bytes("redeem_bonds")?;
+
+    let register_bond_trigger_id: TriggerId = "register_bond".parse().unwrap();
+    let register_bond_trigger = BondTrigger::new(
+        register_bond_trigger_id.clone(),
+        &register_bond_wasm,
+        Repeats::Indefinitely,
+        account_id.clone(),
+        // TODO: Can be simplified in RC22
+        TriggeringFilterBox::from(BySome(DataEntityFilter::from(BySome(TriggerFilter::new(
+            BySome(OriginFilter::new(register_bond_trigger_id)),
+            BySome(TriggerEventFilter::ByMetadataInserted),
+        ))))),
+    );
+
+    let call_bond_trigger_id: TriggerId = "call_bond".parse().unwrap();
+    let call_bond_trigger = BondTrigger::new(
+        call_bond_trigger_id.clone(),
+        &call_bond_wasm,
+        Repeats::Indefinitely,
+        account_id.clone(),
+        // TODO: Can be simplified in RC22
+        TriggeringFilterBox::from(BySome(DataEntityFilter::from(BySome(TriggerFilter::new(
+            BySome(OriginFilter::new(call_bond_trigger_id)),
+            BySome(TriggerEventFilter::ByMetadataInserted),
+        ))))),
+    );
+
+    let buy_bonds_trigger = BondTrigger::new(
+        "buy_bonds_trigger".parse().unwrap(),
+        &buy_bonds_wasm,
+        Repeats::Indefinitely,
+        account_id.clone(),
+        // TODO: Can be simplified in RC22
+        TriggeringFilterBox::from(BySome(DataEntityFilter::from(BySome(AccountFilter::new(
+            AcceptAll,
+            BySome(AccountEventFilter::ByMetadataInserted),
+        ))))),
+    );
+
+    let redeem_bonds_trigger = BondTrigger::new(
+        "redeem_bonds_trigger".parse().unwrap(),
+        &redeem_bonds_wasm,
+        Repeats::Indefinitely,
+        account_id.clone(),
+        // TODO: Can be simplified in RC22
+        TriggeringFilterBox::from(BySome(DataEntityFilter::from(BySome(AccountFilter::new(
+            AcceptAll,
+            BySome(AccountEventFilter::ByMetadataInserted),
+        ))))),
+    );
+
+    Ok(vec![
+        register_bond_trigger,
+        call_bond_trigger,
+        buy_bonds_trigger,
+        redeem_bonds_trigger,
+    ])
+}